@@ -30,7 +30,6 @@ use jj_lib::local_working_copy::LocalWorkingCopy;
 use jj_lib::op_store::OperationId;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::ReadonlyRepo;
-use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::Signer;
 use jj_lib::store::Store;
@@ -42,6 +41,7 @@ use jj_lib::working_copy::ResetError;
 use jj_lib::working_copy::SnapshotError;
 use jj_lib::working_copy::SnapshotOptions;
 use jj_lib::working_copy::SnapshotStats;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::working_copy::WorkingCopy;
 use jj_lib::working_copy::WorkingCopyFactory;
 use jj_lib::working_copy::WorkingCopyStateError;
@@ -165,7 +165,7 @@ impl WorkingCopy for ConflictsWorkingCopy {
         self.inner.tree_id()
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         self.inner.sparse_patterns()
     }
 
@@ -275,13 +275,13 @@ impl LockedWorkingCopy for LockedConflictsWorkingCopy {
         self.inner.recover(commit)
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         self.inner.sparse_patterns()
     }
 
     fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
+        new_sparse_patterns: SparsePatterns,
         options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         self.inner.set_sparse_patterns(new_sparse_patterns, options)
@@ -45,6 +45,7 @@ use clap::ArgAction;
 use clap::ArgMatches;
 use clap::Command;
 use clap::FromArgMatches as _;
+use clap::ValueEnum as _;
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use indexmap::IndexMap;
@@ -140,6 +141,7 @@ use tracing_chrome::ChromeLayerBuilder;
 use tracing_subscriber::prelude::*;
 
 use crate::command_error::cli_error;
+use crate::command_error::config_error;
 use crate::command_error::config_error_with_message;
 use crate::command_error::handle_command_result;
 use crate::command_error::internal_error;
@@ -282,13 +284,12 @@ impl TracingSubscription {
 #[derive(Clone)]
 pub struct CommandHelper {
     data: Rc<CommandHelperData>,
+    args: Rc<CommandArgs>,
 }
 
 struct CommandHelperData {
     app: Command,
     cwd: PathBuf,
-    string_args: Vec<String>,
-    matches: ArgMatches,
     global_args: GlobalArgs,
     config_env: ConfigEnv,
     config_migrations: Vec<ConfigMigrationRule>,
@@ -303,6 +304,13 @@ struct CommandHelperData {
     workspace_loader_factory: Box<dyn WorkspaceLoaderFactory>,
 }
 
+/// Arguments of the command being run, which differ from the ones of the
+/// invocation if the command is run by another command.
+struct CommandArgs {
+    string_args: Vec<String>,
+    matches: ArgMatches,
+}
+
 impl CommandHelper {
     pub fn app(&self) -> &Command {
         &self.data.app
@@ -317,11 +325,31 @@ impl CommandHelper {
     }
 
     pub fn string_args(&self) -> &Vec<String> {
-        &self.data.string_args
+        &self.args.string_args
     }
 
     pub fn matches(&self) -> &ArgMatches {
-        &self.data.matches
+        &self.args.matches
+    }
+
+    /// Returns a helper for running another subcommand given by `args` (not
+    /// including the program name) with the global arguments of the current
+    /// invocation.
+    pub(crate) fn for_subcommand(&self, args: &[&str]) -> Result<Self, clap::Error> {
+        let string_args = itertools::chain!(
+            self.string_args().first().cloned(),
+            self.global_args().to_string_args(self.matches()),
+            args.iter().map(|&arg| arg.to_owned()),
+        )
+        .collect_vec();
+        let (matches, _) = parse_args(self.app(), &string_args)?;
+        Ok(CommandHelper {
+            data: self.data.clone(),
+            args: Rc::new(CommandArgs {
+                string_args,
+                matches,
+            }),
+        })
     }
 
     pub fn global_args(&self) -> &GlobalArgs {
@@ -358,6 +386,7 @@ impl CommandHelper {
         let mut raw_config = self.data.raw_config.clone();
         let repo_path = workspace_root.join(".jj").join("repo");
         config_env.reset_repo_path(&repo_path);
+        config_env.reset_workspace_id(Some(&WorkspaceId::default()));
         config_env.reload_repo_config(&mut raw_config)?;
         let mut config = config_env.resolve_config(&raw_config)?;
         // No migration messages here, which would usually be emitted before.
@@ -406,7 +435,7 @@ impl CommandHelper {
             .map_err(Clone::clone)
     }
 
-    fn new_workspace_loader_at(
+    pub fn new_workspace_loader_at(
         &self,
         workspace_root: &Path,
     ) -> Result<Box<dyn WorkspaceLoader>, CommandError> {
@@ -649,7 +678,7 @@ impl CommandHelper {
                     )?;
                     let base_repo = repo_loader.load_at(&op_heads[0])?;
                     // TODO: It may be helpful to print each operation we're merging here
-                    let mut tx = start_repo_transaction(&base_repo, &self.data.string_args);
+                    let mut tx = start_repo_transaction(&base_repo, &self.args.string_args);
                     for other_op_head in op_heads.into_iter().skip(1) {
                         tx.merge_operation(other_op_head)?;
                         let num_rebased = tx.repo_mut().rebase_descendants()?;
@@ -2537,7 +2566,11 @@ jj git init --colocate",
         WorkspaceLoadError::RepoDoesNotExist(repo_dir) => user_error(format!(
             "The repository directory at {} is missing. Was it moved?",
             repo_dir.display(),
-        )),
+        ))
+        .hinted(
+            "Run `jj workspace repair` in the workspace containing the repository to reattach \
+             this workspace.",
+        ),
         WorkspaceLoadError::StoreLoadError(err @ StoreLoadError::UnsupportedType { .. }) => {
             internal_error_with_message(
                 "This version of the jj binary doesn't support this type of repo",
@@ -2548,6 +2581,8 @@ jj git init --colocate",
             err @ (StoreLoadError::ReadError { .. } | StoreLoadError::Backend(_)),
         ) => internal_error_with_message("The repository appears broken or inaccessible", err),
         WorkspaceLoadError::StoreLoadError(StoreLoadError::Signing(err)) => user_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::MergeDrivers(err)) => config_error(err),
+        WorkspaceLoadError::StoreLoadError(StoreLoadError::Config(err)) => config_error(err),
         WorkspaceLoadError::WorkingCopyState(err) => internal_error(err),
         WorkspaceLoadError::NonUnicodePath | WorkspaceLoadError::Path(_) => user_error(err),
     }
@@ -3251,6 +3286,48 @@ pub struct GlobalArgs {
     pub early_args: EarlyArgs,
 }
 
+impl GlobalArgs {
+    /// Returns the command-line arguments which specify these options.
+    fn to_string_args(&self, matches: &ArgMatches) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(repository) = &self.repository {
+            args.push(format!("--repository={repository}"));
+        }
+        if self.ignore_working_copy {
+            args.push("--ignore-working-copy".to_owned());
+        }
+        if self.ignore_immutable {
+            args.push("--ignore-immutable".to_owned());
+        }
+        if let Some(operation) = &self.at_operation {
+            args.push(format!("--at-operation={operation}"));
+        }
+        if self.debug {
+            args.push("--debug".to_owned());
+        }
+        let early_args = &self.early_args;
+        if let Some(color) = early_args.color {
+            let value = color.to_possible_value().unwrap();
+            args.push(format!("--color={}", value.get_name()));
+        }
+        if early_args.quiet.unwrap_or_default() {
+            args.push("--quiet".to_owned());
+        }
+        if early_args.no_pager.unwrap_or_default() {
+            args.push("--no-pager".to_owned());
+        }
+        for (kind, value) in early_args.merged_config_args(matches) {
+            let name = match kind {
+                ConfigArgKind::Item => "config",
+                ConfigArgKind::Toml => "config-toml",
+                ConfigArgKind::File => "config-file",
+            };
+            args.push(format!("--{name}={value}"));
+        }
+        args
+    }
+}
+
 #[derive(clap::Args, Clone, Debug)]
 pub struct EarlyArgs {
     /// When to colorize output
@@ -3827,6 +3904,7 @@ impl CliRunner {
         config_env.reload_user_config(&mut raw_config)?;
         if let Ok(loader) = &maybe_cwd_workspace_loader {
             config_env.reset_repo_path(loader.repo_path());
+            config_env.reset_workspace_id(loader.workspace_id().as_ref());
             config_env.reload_repo_config(&mut raw_config)?;
         }
         let mut config = config_env.resolve_config(&raw_config)?;
@@ -3877,6 +3955,7 @@ impl CliRunner {
                 .create(&abs_path)
                 .map_err(|err| map_workspace_load_error(err, Some(path)))?;
             config_env.reset_repo_path(loader.repo_path());
+            config_env.reset_workspace_id(loader.workspace_id().as_ref());
             config_env.reload_repo_config(&mut raw_config)?;
             Ok(loader)
         } else {
@@ -3901,8 +3980,6 @@ impl CliRunner {
         let command_helper_data = CommandHelperData {
             app: self.app,
             cwd,
-            string_args,
-            matches,
             global_args: args.global_args,
             config_env,
             config_migrations: self.config_migrations,
@@ -3918,6 +3995,10 @@ impl CliRunner {
         };
         let command_helper = CommandHelper {
             data: Rc::new(command_helper_data),
+            args: Rc::new(CommandArgs {
+                string_args,
+                matches,
+            }),
         };
         for start_hook_fn in self.start_hook_fns {
             start_hook_fn(ui, &command_helper)?;
@@ -4050,4 +4131,43 @@ mod tests {
             vec![("foo", 1), ("bar", 2), ("foo", 3)]
         );
     }
+
+    #[test]
+    fn test_global_args_to_string_args() {
+        let app = crate::commands::default_app();
+        let to_string_args = |args: &[&str]| -> Vec<String> {
+            let string_args = args.iter().map(|&arg| arg.to_owned()).collect_vec();
+            let (matches, args) = parse_args(&app, &string_args).unwrap();
+            args.global_args.to_string_args(&matches)
+        };
+
+        assert_eq!(to_string_args(&["jj", "log"]), Vec::<String>::new());
+        let args = to_string_args(&[
+            "jj",
+            "-R",
+            "repo",
+            "--config-file=a.toml",
+            "log",
+            "--at-op",
+            "abc",
+            "--ignore-immutable",
+            "--color=never",
+            "--config",
+            "x=1",
+        ]);
+        assert_eq!(
+            args,
+            [
+                "--repository=repo",
+                "--ignore-immutable",
+                "--at-operation=abc",
+                "--color=never",
+                "--config-file=a.toml",
+                "--config=x=1",
+            ]
+        );
+        // The arguments are parsed back to the same options
+        let reparsed_args = itertools::chain!(["jj"], args.iter().map(String::as_str), ["log"]);
+        assert_eq!(to_string_args(&reparsed_args.collect_vec()), args);
+    }
 }
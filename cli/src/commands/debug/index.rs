@@ -53,6 +53,13 @@ pub fn cmd_debug_index(
         )?;
         writeln!(ui.stdout(), "Number of heads: {}", stats.num_heads)?;
         writeln!(ui.stdout(), "Number of changes: {}", stats.num_changes)?;
+        if stats.num_changed_path_filters > 0 {
+            writeln!(
+                ui.stdout(),
+                "Number of changed-path filters: {}",
+                stats.num_changed_path_filters
+            )?;
+        }
         writeln!(ui.stdout(), "Stats per level:")?;
         for (i, level) in stats.levels.iter().enumerate() {
             writeln!(ui.stdout(), "  Level {i}:")?;
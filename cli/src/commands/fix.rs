@@ -15,16 +15,20 @@
 use std::collections::HashMap;
 use std::io::Write as _;
 use std::path::Path;
+use std::process::Command;
 use std::process::Stdio;
+use std::slice;
 
 use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::FileId;
+use jj_lib::diff::find_line_ranges;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::fix::fix_files;
+use jj_lib::fix::ChangedLines;
 use jj_lib::fix::FileToFix;
 use jj_lib::fix::FixError;
 use jj_lib::fix::ParallelFileFixer;
@@ -41,6 +45,8 @@ use crate::command_error::config_error;
 use crate::command_error::print_parse_diagnostics;
 use crate::command_error::CommandError;
 use crate::complete;
+use crate::config::find_all_variables;
+use crate::config::interpolate_variables;
 use crate::config::CommandNameAndArgs;
 use crate::ui::Ui;
 
@@ -56,7 +62,9 @@ use crate::ui::Ui;
 /// their versions of the same files through the same tools, which will ensure
 /// that the fixes are not lost. This will never result in new conflicts. Files
 /// with existing conflicts will be updated on all sides of the conflict, which
-/// can potentially increase or decrease the number of conflict markers.
+/// can potentially increase or decrease the number of conflict markers. The
+/// updated sides are merged again, so a conflict that only exists because one
+/// side was reformatted is usually resolved.
 ///
 /// The external tools must accept the current file content on standard input,
 /// and return the updated file content on standard output. A tool's output will
@@ -70,7 +78,14 @@ use crate::ui::Ui;
 ///    which will be replaced with the repo-relative path of the file being
 ///    fixed. It is useful to provide the path to tools that include the path in
 ///    error messages, or behave differently based on the directory or file
-///    name.
+///    name. An argument containing the substrings `$first_line` and
+///    `$last_line` will be repeated for each range of lines that were changed
+///    relative to the parent revision, with the substrings replaced by the
+///    1-based inclusive line numbers of the range. For example,
+///    `--lines=$first_line:$last_line` may become `--lines=1:3 --lines=10:12`.
+///    A tool using them is not run on files where lines were only removed.
+///    This is useful for tools that can format only part of a file, to avoid
+///    reformatting unrelated code.
 ///  - `patterns`: Determines which files the tool will affect. If this list is
 ///    empty, no files will be affected by the tool. If there are multiple
 ///    patterns, the tool is applied only once to each file in the union of the
@@ -143,9 +158,14 @@ pub(crate) fn cmd_fix(
         .to_matcher();
 
     let mut tx = workspace_command.start_transaction();
+    let uses_line_ranges = tools_config
+        .tools
+        .iter()
+        .any(|tool_config| tool_config.uses_line_ranges);
     let parallel_fixer = ParallelFileFixer::new(|store, file_to_fix| {
         fix_one_file(&workspace_root, &tools_config, store, file_to_fix)
-    });
+    })
+    .with_changed_lines(uses_line_ranges);
     let summary = fix_files(
         root_commits,
         &matcher,
@@ -192,11 +212,17 @@ fn fix_one_file(
         let mut old_content = vec![];
         let mut read = store.read_file(&file_to_fix.repo_path, &file_to_fix.file_id)?;
         read.read_to_end(&mut old_content)?;
+        let line_ranges = line_ranges(&file_to_fix.changed_lines, &old_content);
         let new_content = matching_tools.fold(old_content.clone(), |prev_content, tool_config| {
+            if tool_config.uses_line_ranges && line_ranges.is_empty() {
+                // There are no lines to fix.
+                return prev_content;
+            }
             match run_tool(
                 workspace_root,
                 &tool_config.command,
                 file_to_fix,
+                &line_ranges,
                 &prev_content,
             ) {
                 Ok(next_content) => next_content,
@@ -217,10 +243,53 @@ fn fix_one_file(
     Ok(None)
 }
 
+/// Variables that make a tool argument be repeated for each range of changed
+/// lines.
+const LINE_RANGE_VARIABLES: [&str; 2] = ["first_line", "last_line"];
+
+/// Returns the changed lines of a file as 1-based inclusive ranges.
+fn line_ranges(changed_lines: &ChangedLines, content: &[u8]) -> Vec<(usize, usize)> {
+    match changed_lines {
+        ChangedLines::All => match find_line_ranges(content).len() {
+            0 => vec![],
+            num_lines => vec![(1, num_lines)],
+        },
+        ChangedLines::Ranges(ranges) => ranges
+            .iter()
+            .map(|range| (range.start + 1, range.end))
+            .collect(),
+    }
+}
+
+/// Repeats each argument that uses the [`LINE_RANGE_VARIABLES`] once for each
+/// of the `line_ranges`.
+fn expand_line_range_args(args: &[String], line_ranges: &[(usize, usize)]) -> Vec<String> {
+    let mut expanded = vec![];
+    for arg in args {
+        if !uses_line_range_variables(slice::from_ref(arg)) {
+            expanded.push(arg.clone());
+            continue;
+        }
+        for (first_line, last_line) in line_ranges {
+            let vars = HashMap::from([
+                ("first_line", first_line.to_string()),
+                ("last_line", last_line.to_string()),
+            ]);
+            expanded.extend(interpolate_variables(slice::from_ref(arg), &vars));
+        }
+    }
+    expanded
+}
+
+fn uses_line_range_variables(args: &[String]) -> bool {
+    find_all_variables(args).any(|name| LINE_RANGE_VARIABLES.contains(&name))
+}
+
 /// Runs the `tool_command` to fix the given file content.
 ///
 /// The `old_content` is assumed to be that of the `file_to_fix`'s `FileId`, but
-/// this is not verified.
+/// this is not verified. The `line_ranges` refer to the committed content of
+/// the file, even if a previous tool already changed it.
 ///
 /// Returns the new file content, whose value will be the same as `old_content`
 /// unless the command introduced changes. Returns `None` if there were any
@@ -229,13 +298,22 @@ fn run_tool(
     workspace_root: &Path,
     tool_command: &CommandNameAndArgs,
     file_to_fix: &FileToFix,
+    line_ranges: &[(usize, usize)],
     old_content: &[u8],
 ) -> Result<Vec<u8>, ()> {
     // TODO: Pipe stderr so we can tell the user which commit, file, and tool it is
     // associated with.
     let mut vars: HashMap<&str, &str> = HashMap::new();
     vars.insert("path", file_to_fix.repo_path.as_internal_file_string());
-    let mut command = tool_command.to_command_with_variables(&vars);
+    let (name, args) = tool_command.split_name_and_args();
+    let mut command = Command::new(name.as_ref());
+    if let CommandNameAndArgs::Structured { env, .. } = tool_command {
+        command.envs(env);
+    }
+    command.args(interpolate_variables(
+        &expand_line_range_args(&args, line_ranges),
+        &vars,
+    ));
     tracing::debug!(?command, ?file_to_fix.repo_path, "spawning fix tool");
     let mut child = command
         .current_dir(workspace_root)
@@ -267,6 +345,8 @@ struct ToolConfig {
     matcher: Box<dyn Matcher>,
     /// Whether the tool is enabled
     enabled: bool,
+    /// Whether the command uses the [`LINE_RANGE_VARIABLES`].
+    uses_line_ranges: bool,
     // TODO: Store the `name` field here and print it with the command's stderr, to clearly
     // associate any errors/warnings with the tool and its configuration entry.
 }
@@ -321,10 +401,12 @@ fn get_tools_config(ui: &mut Ui, settings: &UserSettings) -> Result<ToolsConfig,
                     .try_collect()?,
             );
             print_parse_diagnostics(ui, &format!("In `fix.tools.{name}`"), &diagnostics)?;
+            let uses_line_ranges = uses_line_range_variables(&tool.command.split_name_and_args().1);
             Ok(ToolConfig {
                 command: tool.command,
                 matcher: expression.to_matcher(),
                 enabled: tool.enabled,
+                uses_line_ranges,
             })
         })
         .try_collect()?;
@@ -43,6 +43,7 @@ use jj_lib::refs::LocalAndRemoteRef;
 use jj_lib::repo::Repo;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::settings::UserSettings;
+use jj_lib::signing::SigStatus;
 use jj_lib::signing::SignBehavior;
 use jj_lib::str_util::StringPattern;
 use jj_lib::view::View;
@@ -54,6 +55,7 @@ use crate::cli_util::RevisionArg;
 use crate::cli_util::WorkspaceCommandHelper;
 use crate::cli_util::WorkspaceCommandTransaction;
 use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
 use crate::command_error::CommandError;
 use crate::commands::git::get_single_remote;
 use crate::complete;
@@ -345,7 +347,9 @@ pub fn cmd_git_push(
         return Ok(());
     }
 
-    let sign_behavior = if tx.settings().get_bool("git.sign-on-push")? {
+    let sign_behavior = if tx.settings().get_bool("git.sign-on-push")?
+        || tx.settings().sign_settings().behavior == SignBehavior::OnPush
+    {
         Some(SignBehavior::Own)
     } else {
         None
@@ -483,6 +487,12 @@ fn validate_commits_ready_to_push(
         .parse_revset(ui, &private_revset_str)?
         .evaluate()?
         .containing_fn();
+    let require_signed_revset_str =
+        RevisionArg::from(settings.get_string("git.require-signed-commits")?);
+    let requires_signature = workspace_helper
+        .parse_revset(ui, &require_signed_revset_str)?
+        .evaluate()?
+        .containing_fn();
     let sign_settings = sign_behavior.map(|sign_behavior| {
         let mut sign_settings = settings.sign_settings();
         sign_settings.behavior = sign_behavior;
@@ -518,6 +528,32 @@ fn validate_commits_ready_to_push(
         if !args.allow_private && is_private {
             reasons.push("it is private");
         }
+        let will_be_signed = !commit.is_signed()
+            && sign_settings
+                .as_ref()
+                .is_some_and(|sign_settings| sign_settings.should_sign(commit.store_commit()));
+        let mut has_invalid_signature = false;
+        if requires_signature(commit.id())? && !will_be_signed {
+            let verification = commit.verification().map_err(|err| {
+                user_error_with_message(
+                    format!(
+                        "Failed to verify signature of commit {}",
+                        short_commit_hash(commit.id())
+                    ),
+                    err,
+                )
+            })?;
+            let reason = match verification.map(|verification| verification.status) {
+                Some(SigStatus::Good) => None,
+                Some(SigStatus::Unknown) => Some("its signature can't be verified"),
+                Some(SigStatus::Bad) => Some("its signature is bad"),
+                None => Some("it is not signed"),
+            };
+            if let Some(reason) = reason {
+                reasons.push(reason);
+                has_invalid_signature = true;
+            }
+        }
         if !reasons.is_empty() {
             let mut error = user_error(format!(
                 "Won't push commit {} since {}",
@@ -534,12 +570,15 @@ fn validate_commits_ready_to_push(
                     "Configured git.private-commits: '{private_revset_str}'",
                 ));
             }
+            if has_invalid_signature {
+                error.add_hint(format!(
+                    "Configured git.require-signed-commits: '{require_signed_revset_str}'",
+                ));
+            }
             return Err(error);
         }
-        if let Some(sign_settings) = &sign_settings {
-            if !commit.is_signed() && sign_settings.should_sign(commit.store_commit()) {
-                commits_to_sign.push(commit);
-            }
+        if will_be_signed {
+            commits_to_sign.push(commit);
         }
     }
     Ok(commits_to_sign)
@@ -12,17 +12,22 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashSet;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
 use jj_lib::config::ConfigGetError;
 use jj_lib::config::ConfigGetResultExt as _;
+use jj_lib::fileset::FilesetExpression;
 use jj_lib::graph::reverse_graph;
 use jj_lib::graph::GraphEdge;
 use jj_lib::graph::GraphEdgeType;
 use jj_lib::graph::TopoGroupedGraphIterator;
 use jj_lib::repo::Repo as _;
+use jj_lib::revset::Revset;
 use jj_lib::revset::RevsetEvaluationError;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetFilterPredicate;
@@ -34,12 +39,20 @@ use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::CommandError;
 use crate::commit_templater::CommitTemplateLanguage;
 use crate::complete;
 use crate::diff_util::DiffFormatArgs;
+use crate::formatter::Formatter;
 use crate::graphlog::get_graphlog;
+use crate::graphlog::GraphLog;
 use crate::graphlog::GraphStyle;
+use crate::log_ui;
+use crate::log_ui::LogView;
+use crate::log_ui::SharedBuffer;
+use crate::revset_util::RevsetExpressionEvaluator;
+use crate::templater::TemplateRenderer;
 use crate::ui::Ui;
 
 /// Show revision history
@@ -118,6 +131,20 @@ pub(crate) struct LogArgs {
     patch: bool,
     #[command(flatten)]
     diff_format: DiffFormatArgs,
+    /// Browse the log interactively
+    ///
+    /// Opens a full-screen view of the graph where revisions can be selected
+    /// with the arrow keys (or `j` and `k`). Press `Enter` to show or hide the
+    /// diff of the selected revision, and `?` to list the other actions, such
+    /// as `e` to edit the revision, `n` to create a new revision on top of it,
+    /// or `r` to rebase it onto another revision. Actions can be undone with
+    /// `u`.
+    #[arg(
+        long,
+        short,
+        conflicts_with_all = ["no_graph", "reversed"],
+    )]
+    interactive: bool,
 }
 
 #[instrument(skip_all)]
@@ -126,29 +153,17 @@ pub(crate) fn cmd_log(
     command: &CommandHelper,
     args: &LogArgs,
 ) -> Result<(), CommandError> {
+    if args.interactive {
+        return log_ui::run_log_ui(ui, command, |ui, expanded| {
+            render_log_view(ui, command, args, expanded)
+        });
+    }
+
     let workspace_command = command.workspace_helper(ui)?;
     let settings = workspace_command.settings();
 
     let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
-    let revset_expression = {
-        // only use default revset if neither revset nor path are specified
-        let mut expression = if args.revisions.is_empty() && args.paths.is_empty() {
-            let revset_string = settings.get_string("revsets.log")?;
-            workspace_command.parse_revset(ui, &RevisionArg::from(revset_string))?
-        } else if !args.revisions.is_empty() {
-            workspace_command.parse_union_revsets(ui, &args.revisions)?
-        } else {
-            // a path was specified so we use all() and add path filter later
-            workspace_command.attach_revset_evaluator(RevsetExpression::all())
-        };
-        if !args.paths.is_empty() {
-            // Beware that args.paths = ["root:."] is not identical to []. The
-            // former will filter out empty commits.
-            let predicate = RevsetFilterPredicate::File(fileset_expression.clone());
-            expression.intersect_with(&RevsetExpression::filter(predicate));
-        }
-        expression
-    };
+    let revset_expression = parse_log_revset(ui, &workspace_command, args, &fileset_expression)?;
 
     let repo = workspace_command.repo();
     let matcher = fileset_expression.to_matcher();
@@ -158,34 +173,9 @@ pub(crate) fn cmd_log(
     let diff_renderer = workspace_command.diff_renderer_for_log(&args.diff_format, args.patch)?;
     let graph_style = GraphStyle::from_settings(settings)?;
 
-    let use_elided_nodes = settings.get_bool("ui.log-synthetic-elided-nodes")?;
     let with_content_format = LogContentFormat::new(ui, settings)?;
 
-    let template;
-    let node_template;
-    {
-        let language = workspace_command.commit_template_language();
-        let template_string = match &args.template {
-            Some(value) => value.to_string(),
-            None => settings.get_string("templates.log")?,
-        };
-        template = workspace_command
-            .parse_template(
-                ui,
-                &language,
-                &template_string,
-                CommitTemplateLanguage::wrap_commit,
-            )?
-            .labeled("log");
-        node_template = workspace_command
-            .parse_template(
-                ui,
-                &language,
-                &get_node_template(graph_style, settings)?,
-                CommitTemplateLanguage::wrap_commit_opt,
-            )?
-            .labeled("node");
-    }
+    let (template, node_template) = parse_log_templates(ui, &workspace_command, args, graph_style)?;
 
     {
         ui.request_pager();
@@ -195,104 +185,23 @@ pub(crate) fn cmd_log(
         if !args.no_graph {
             let mut raw_output = formatter.raw()?;
             let mut graph = get_graphlog(graph_style, raw_output.as_mut());
-            let iter: Box<dyn Iterator<Item = _>> = {
-                let mut forward_iter = TopoGroupedGraphIterator::new(revset.iter_graph());
-                // Emit the working-copy branch first, which is usually most
-                // interesting. This also helps stabilize output order.
-                if let Some(id) = workspace_command.get_wc_commit_id() {
-                    let has_commit = revset.containing_fn();
-                    if has_commit(id)? {
-                        forward_iter.prioritize_branch(id.clone());
-                    }
-                }
-                // The input to TopoGroupedGraphIterator shouldn't be truncated
-                // because the prioritized commit must exist in the input set.
-                let forward_iter = forward_iter.take(args.limit.unwrap_or(usize::MAX));
-                if args.reversed {
-                    Box::new(reverse_graph(forward_iter, |id| id)?.into_iter().map(Ok))
-                } else {
-                    Box::new(forward_iter)
-                }
-            };
-            for node in iter {
-                let (commit_id, edges) = node?;
-
-                // The graph is keyed by (CommitId, is_synthetic)
-                let mut graphlog_edges = vec![];
-                // TODO: Should we update revset.iter_graph() to yield a `has_missing` flag
-                // instead of all the missing edges since we don't care about
-                // where they point here anyway?
-                let mut missing_edge_id = None;
-                let mut elided_targets = vec![];
-                for edge in edges {
-                    match edge.edge_type {
-                        GraphEdgeType::Missing => {
-                            missing_edge_id = Some(edge.target);
-                        }
-                        GraphEdgeType::Direct => {
-                            graphlog_edges.push(GraphEdge::direct((edge.target, false)));
-                        }
-                        GraphEdgeType::Indirect => {
-                            if use_elided_nodes {
-                                elided_targets.push(edge.target.clone());
-                                graphlog_edges.push(GraphEdge::direct((edge.target, true)));
-                            } else {
-                                graphlog_edges.push(GraphEdge::indirect((edge.target, false)));
-                            }
-                        }
+            write_log_graph(
+                ui,
+                &workspace_command,
+                revset.as_ref(),
+                graph.as_mut(),
+                &template,
+                &node_template,
+                args.limit,
+                args.reversed,
+                |formatter, commit, width| {
+                    if let Some(renderer) = &diff_renderer {
+                        renderer.show_patch(ui, formatter, commit, matcher.as_ref(), width)?;
                     }
-                }
-                if let Some(missing_edge_id) = missing_edge_id {
-                    graphlog_edges.push(GraphEdge::missing((missing_edge_id, false)));
-                }
-                let mut buffer = vec![];
-                let key = (commit_id, false);
-                let commit = store.get_commit(&key.0)?;
-                let within_graph =
-                    with_content_format.sub_width(graph.width(&key, &graphlog_edges));
-                within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
-                    template.format(&commit, formatter)
-                })?;
-                if !buffer.ends_with(b"\n") {
-                    buffer.push(b'\n');
-                }
-                if let Some(renderer) = &diff_renderer {
-                    let mut formatter = ui.new_formatter(&mut buffer);
-                    renderer.show_patch(
-                        ui,
-                        formatter.as_mut(),
-                        &commit,
-                        matcher.as_ref(),
-                        within_graph.width(),
-                    )?;
-                }
-
-                let node_symbol = format_template(ui, &Some(commit), &node_template);
-                graph.add_node(
-                    &key,
-                    &graphlog_edges,
-                    &node_symbol,
-                    &String::from_utf8_lossy(&buffer),
-                )?;
-                for elided_target in elided_targets {
-                    let elided_key = (elided_target, true);
-                    let real_key = (elided_key.0.clone(), false);
-                    let edges = [GraphEdge::direct(real_key)];
-                    let mut buffer = vec![];
-                    let within_graph =
-                        with_content_format.sub_width(graph.width(&elided_key, &edges));
-                    within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
-                        writeln!(formatter.labeled("elided"), "(elided revisions)")
-                    })?;
-                    let node_symbol = format_template(ui, &None, &node_template);
-                    graph.add_node(
-                        &elided_key,
-                        &edges,
-                        &node_symbol,
-                        &String::from_utf8_lossy(&buffer),
-                    )?;
-                }
-            }
+                    Ok(())
+                },
+                |_| {},
+            )?;
         } else {
             let iter: Box<dyn Iterator<Item = Result<CommitId, RevsetEvaluationError>>> = {
                 let forward_iter = revset.iter().take(args.limit.unwrap_or(usize::MAX));
@@ -342,6 +251,232 @@ pub(crate) fn cmd_log(
     Ok(())
 }
 
+fn parse_log_revset<'a>(
+    ui: &Ui,
+    workspace_command: &'a WorkspaceCommandHelper,
+    args: &LogArgs,
+    fileset_expression: &FilesetExpression,
+) -> Result<RevsetExpressionEvaluator<'a>, CommandError> {
+    // only use default revset if neither revset nor path are specified
+    let mut expression = if args.revisions.is_empty() && args.paths.is_empty() {
+        let revset_string = workspace_command.settings().get_string("revsets.log")?;
+        workspace_command.parse_revset(ui, &RevisionArg::from(revset_string))?
+    } else if !args.revisions.is_empty() {
+        workspace_command.parse_union_revsets(ui, &args.revisions)?
+    } else {
+        // a path was specified so we use all() and add path filter later
+        workspace_command.attach_revset_evaluator(RevsetExpression::all())
+    };
+    if !args.paths.is_empty() {
+        // Beware that args.paths = ["root:."] is not identical to []. The
+        // former will filter out empty commits.
+        let predicate = RevsetFilterPredicate::File(fileset_expression.clone());
+        expression.intersect_with(&RevsetExpression::filter(predicate));
+    }
+    Ok(expression)
+}
+
+fn parse_log_templates<'a>(
+    ui: &Ui,
+    workspace_command: &'a WorkspaceCommandHelper,
+    args: &LogArgs,
+    graph_style: GraphStyle,
+) -> Result<
+    (
+        TemplateRenderer<'a, Commit>,
+        TemplateRenderer<'a, Option<Commit>>,
+    ),
+    CommandError,
+> {
+    let settings = workspace_command.settings();
+    let language = workspace_command.commit_template_language();
+    let template_string = match &args.template {
+        Some(value) => value.to_string(),
+        None => settings.get_string("templates.log")?,
+    };
+    let template = workspace_command
+        .parse_template(
+            ui,
+            &language,
+            &template_string,
+            CommitTemplateLanguage::wrap_commit,
+        )?
+        .labeled("log");
+    let node_template = workspace_command
+        .parse_template(
+            ui,
+            &language,
+            &get_node_template(graph_style, settings)?,
+            CommitTemplateLanguage::wrap_commit_opt,
+        )?
+        .labeled("node");
+    Ok((template, node_template))
+}
+
+/// Renders the graph for `jj log --interactive`. The patches of the commits in
+/// `expanded` are included.
+fn render_log_view(
+    ui: &Ui,
+    command: &CommandHelper,
+    args: &LogArgs,
+    expanded: &HashSet<CommitId>,
+) -> Result<LogView, CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    let fileset_expression = workspace_command.parse_file_patterns(ui, &args.paths)?;
+    let revset_expression = parse_log_revset(ui, &workspace_command, args, &fileset_expression)?;
+    let matcher = fileset_expression.to_matcher();
+    let revset = revset_expression.evaluate()?;
+    let diff_renderer = workspace_command.diff_renderer_for_log(&args.diff_format, true)?;
+    let graph_style = GraphStyle::from_settings(workspace_command.settings())?;
+    let (template, node_template) = parse_log_templates(ui, &workspace_command, args, graph_style)?;
+
+    let buffer = SharedBuffer::default();
+    let mut node_starts = vec![];
+    {
+        let mut output = buffer.clone();
+        let mut graph = get_graphlog(graph_style, &mut output);
+        write_log_graph(
+            ui,
+            &workspace_command,
+            revset.as_ref(),
+            graph.as_mut(),
+            &template,
+            &node_template,
+            args.limit,
+            false,
+            |formatter, commit, width| {
+                if let Some(renderer) = &diff_renderer {
+                    if expanded.contains(commit.id()) {
+                        renderer.show_patch(ui, formatter, commit, matcher.as_ref(), width)?;
+                    }
+                }
+                Ok(())
+            },
+            |commit_id| node_starts.push((commit_id.cloned(), buffer.len())),
+        )?;
+    }
+    Ok(LogView::new(&buffer.take(), &node_starts))
+}
+
+/// Writes the graph of the commits in `revset` to `graph`.
+///
+/// The `write_patch` callback can append content such as a diff to the text
+/// of a commit, given the available width. The `on_node` callback is called
+/// before each node is written, with `None` for elided nodes.
+#[expect(clippy::too_many_arguments)]
+pub(crate) fn write_log_graph(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    revset: &dyn Revset,
+    graph: &mut dyn GraphLog<(CommitId, bool)>,
+    template: &TemplateRenderer<Commit>,
+    node_template: &TemplateRenderer<Option<Commit>>,
+    limit: Option<usize>,
+    reversed: bool,
+    mut write_patch: impl FnMut(&mut dyn Formatter, &Commit, usize) -> Result<(), CommandError>,
+    mut on_node: impl FnMut(Option<&CommitId>),
+) -> Result<(), CommandError> {
+    let settings = workspace_command.settings();
+    let store = workspace_command.repo().store();
+    let use_elided_nodes = settings.get_bool("ui.log-synthetic-elided-nodes")?;
+    let with_content_format = LogContentFormat::new(ui, settings)?;
+
+    let iter: Box<dyn Iterator<Item = _>> = {
+        let mut forward_iter = TopoGroupedGraphIterator::new(revset.iter_graph());
+        // Emit the working-copy branch first, which is usually most
+        // interesting. This also helps stabilize output order.
+        if let Some(id) = workspace_command.get_wc_commit_id() {
+            let has_commit = revset.containing_fn();
+            if has_commit(id)? {
+                forward_iter.prioritize_branch(id.clone());
+            }
+        }
+        // The input to TopoGroupedGraphIterator shouldn't be truncated
+        // because the prioritized commit must exist in the input set.
+        let forward_iter = forward_iter.take(limit.unwrap_or(usize::MAX));
+        if reversed {
+            Box::new(reverse_graph(forward_iter, |id| id)?.into_iter().map(Ok))
+        } else {
+            Box::new(forward_iter)
+        }
+    };
+    for node in iter {
+        let (commit_id, edges) = node?;
+
+        // The graph is keyed by (CommitId, is_synthetic)
+        let mut graphlog_edges = vec![];
+        // TODO: Should we update revset.iter_graph() to yield a `has_missing` flag
+        // instead of all the missing edges since we don't care about
+        // where they point here anyway?
+        let mut missing_edge_id = None;
+        let mut elided_targets = vec![];
+        for edge in edges {
+            match edge.edge_type {
+                GraphEdgeType::Missing => {
+                    missing_edge_id = Some(edge.target);
+                }
+                GraphEdgeType::Direct => {
+                    graphlog_edges.push(GraphEdge::direct((edge.target, false)));
+                }
+                GraphEdgeType::Indirect => {
+                    if use_elided_nodes {
+                        elided_targets.push(edge.target.clone());
+                        graphlog_edges.push(GraphEdge::direct((edge.target, true)));
+                    } else {
+                        graphlog_edges.push(GraphEdge::indirect((edge.target, false)));
+                    }
+                }
+            }
+        }
+        if let Some(missing_edge_id) = missing_edge_id {
+            graphlog_edges.push(GraphEdge::missing((missing_edge_id, false)));
+        }
+        let mut buffer = vec![];
+        let key = (commit_id, false);
+        let commit = store.get_commit(&key.0)?;
+        let within_graph = with_content_format.sub_width(graph.width(&key, &graphlog_edges));
+        within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
+            template.format(&commit, formatter)
+        })?;
+        if !buffer.ends_with(b"\n") {
+            buffer.push(b'\n');
+        }
+        write_patch(
+            ui.new_formatter(&mut buffer).as_mut(),
+            &commit,
+            within_graph.width(),
+        )?;
+
+        let node_symbol = format_template(ui, &Some(commit), node_template);
+        on_node(Some(&key.0));
+        graph.add_node(
+            &key,
+            &graphlog_edges,
+            &node_symbol,
+            &String::from_utf8_lossy(&buffer),
+        )?;
+        for elided_target in elided_targets {
+            let elided_key = (elided_target, true);
+            let real_key = (elided_key.0.clone(), false);
+            let edges = [GraphEdge::direct(real_key)];
+            let mut buffer = vec![];
+            let within_graph = with_content_format.sub_width(graph.width(&elided_key, &edges));
+            within_graph.write(ui.new_formatter(&mut buffer).as_mut(), |formatter| {
+                writeln!(formatter.labeled("elided"), "(elided revisions)")
+            })?;
+            let node_symbol = format_template(ui, &None, node_template);
+            on_node(None);
+            graph.add_node(
+                &elided_key,
+                &edges,
+                &node_symbol,
+                &String::from_utf8_lossy(&buffer),
+            )?;
+        }
+    }
+    Ok(())
+}
+
 pub fn get_node_template(
     style: GraphStyle,
     settings: &UserSettings,
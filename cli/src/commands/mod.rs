@@ -59,11 +59,13 @@ mod version;
 mod workspace;
 
 use std::fmt::Debug;
+use std::iter;
 
 use clap::builder::styling::AnsiColor;
 use clap::builder::Styles;
 use clap::CommandFactory as _;
 use clap::FromArgMatches as _;
+use clap::Parser as _;
 use clap::Subcommand as _;
 use clap_complete::engine::SubcommandCandidates;
 use tracing::instrument;
@@ -161,7 +163,28 @@ pub fn default_app() -> clap::Command {
 #[instrument(skip_all)]
 pub fn run_command(ui: &mut Ui, command_helper: &CommandHelper) -> Result<(), CommandError> {
     let subcommand = Command::from_arg_matches(command_helper.matches()).unwrap();
-    match &subcommand {
+    run_parsed_subcommand(ui, command_helper, &subcommand)
+}
+
+/// Runs another subcommand given by `args` (not including the program name)
+/// within the current invocation. The global arguments of the invocation are
+/// forwarded to the subcommand, and can't be specified in `args`.
+pub(crate) fn run_subcommand(
+    ui: &mut Ui,
+    command_helper: &CommandHelper,
+    args: &[&str],
+) -> Result<(), CommandError> {
+    let subcommand = Command::try_parse_from(iter::once("jj").chain(args.iter().copied()))?;
+    let command_helper = command_helper.for_subcommand(args)?;
+    run_parsed_subcommand(ui, &command_helper, &subcommand)
+}
+
+fn run_parsed_subcommand(
+    ui: &mut Ui,
+    command_helper: &CommandHelper,
+    subcommand: &Command,
+) -> Result<(), CommandError> {
+    match subcommand {
         Command::Abandon(args) => abandon::cmd_abandon(ui, command_helper, args),
         Command::Absorb(args) => absorb::cmd_absorb(ui, command_helper, args),
         Command::Backout(args) => backout::cmd_backout(ui, command_helper, args),
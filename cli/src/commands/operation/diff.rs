@@ -14,6 +14,7 @@
 
 use std::collections::HashMap;
 use std::convert::Infallible;
+use std::io;
 use std::sync::Arc;
 
 use clap_complete::ArgValueCandidates;
@@ -29,11 +30,14 @@ use jj_lib::matchers::EverythingMatcher;
 use jj_lib::op_store::RefTarget;
 use jj_lib::op_store::RemoteRef;
 use jj_lib::op_store::RemoteRefState;
+use jj_lib::operation::Operation;
+use jj_lib::ref_name::RefName;
 use jj_lib::refs::diff_named_commit_ids;
 use jj_lib::refs::diff_named_ref_targets;
 use jj_lib::refs::diff_named_remote_refs;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo;
+use jj_lib::repo::RepoLoader;
 use jj_lib::revset;
 use jj_lib::revset::RevsetIteratorExt as _;
 
@@ -417,6 +421,126 @@ pub fn show_op_diff(
     Ok(())
 }
 
+/// Shows what each side of a merge operation did relative to their closest
+/// common ancestor, and which ref conflicts were introduced by the merge.
+///
+/// Does nothing if `op` isn't a merge operation.
+pub fn show_op_merge_summary(
+    formatter: &mut dyn Formatter,
+    repo_loader: &RepoLoader,
+    op: &Operation,
+    op_summary_template: &TemplateRenderer<Operation>,
+) -> Result<(), CommandError> {
+    let parent_ops: Vec<_> = op.parents().try_collect()?;
+    let [first_op, other_ops @ ..] = &*parent_ops else {
+        return Ok(());
+    };
+    if other_ops.is_empty() {
+        return Ok(());
+    }
+    let mut base_op = first_op.clone();
+    for other_op in other_ops {
+        base_op = dag_walk::closest_common_node_ok(
+            [Ok(base_op)],
+            [Ok(other_op.clone())],
+            |op: &Operation| op.id().clone(),
+            |op: &Operation| op.parents().collect_vec(),
+        )?
+        .expect("operations should share the root operation");
+    }
+    let base_repo = repo_loader.load_at(&base_op)?;
+    let parent_repos: Vec<_> = parent_ops
+        .iter()
+        .map(|parent_op| repo_loader.load_at(parent_op))
+        .try_collect()?;
+
+    writeln!(formatter)?;
+    writeln!(formatter, "Merged operations:")?;
+    for (parent_op, parent_repo) in parent_ops.iter().zip(&parent_repos) {
+        let changes =
+            compute_operation_commits_diff(parent_repo.as_ref(), &base_repo, parent_repo)?;
+        let num_bookmarks = diff_named_ref_targets(
+            base_repo.view().local_bookmarks(),
+            parent_repo.view().local_bookmarks(),
+        )
+        .count();
+        let metadata = parent_op.metadata();
+        write!(formatter, "  ")?;
+        op_summary_template.format(parent_op, formatter)?;
+        writeln!(formatter)?;
+        write!(formatter, "    ")?;
+        write!(
+            formatter.labeled("user"),
+            "{}@{}",
+            metadata.username,
+            metadata.hostname
+        )?;
+        let changes_noun = if changes.len() == 1 {
+            "change"
+        } else {
+            "changes"
+        };
+        let bookmarks_noun = if num_bookmarks == 1 {
+            "bookmark"
+        } else {
+            "bookmarks"
+        };
+        writeln!(
+            formatter,
+            ": {} modified {changes_noun}, {num_bookmarks} modified {bookmarks_noun}",
+            changes.len(),
+        )?;
+    }
+
+    let merged_repo = repo_loader.load_at(op)?;
+    let conflicted_bookmarks = merged_repo
+        .view()
+        .local_bookmarks()
+        .filter(|(name, target)| {
+            target.has_conflict()
+                && parent_repos
+                    .iter()
+                    .all(|repo| !repo.view().get_local_bookmark(name).has_conflict())
+        })
+        .map(|(name, _)| name)
+        .collect_vec();
+    if !conflicted_bookmarks.is_empty() {
+        write!(formatter, "Introduced bookmark conflicts: ")?;
+        write_ref_names(formatter, "bookmark", conflicted_bookmarks)?;
+    }
+    let conflicted_tags = merged_repo
+        .view()
+        .tags()
+        .iter()
+        .filter(|(name, target)| {
+            target.has_conflict()
+                && parent_repos
+                    .iter()
+                    .all(|repo| !repo.view().get_tag(name).has_conflict())
+        })
+        .map(|(name, _)| name.as_ref())
+        .collect_vec();
+    if !conflicted_tags.is_empty() {
+        write!(formatter, "Introduced tag conflicts: ")?;
+        write_ref_names(formatter, "tag", conflicted_tags)?;
+    }
+    Ok(())
+}
+
+fn write_ref_names(
+    formatter: &mut dyn Formatter,
+    label: &str,
+    names: Vec<&RefName>,
+) -> io::Result<()> {
+    for (i, name) in names.into_iter().enumerate() {
+        if i > 0 {
+            write!(formatter, ", ")?;
+        }
+        write!(formatter.labeled(label), "{}", name.as_symbol())?;
+    }
+    writeln!(formatter)
+}
+
 /// Writes a summary for the given `ModifiedChange`.
 fn write_modified_change_summary(
     formatter: &mut dyn Formatter,
@@ -27,6 +27,7 @@ use jj_lib::repo::RepoLoader;
 use jj_lib::settings::UserSettings;
 
 use super::diff::show_op_diff;
+use super::diff::show_op_merge_summary;
 use crate::cli_util::format_template;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
@@ -76,6 +77,9 @@ pub struct OperationLogArgs {
     #[arg(long, short = 'T', add = ArgValueCandidates::new(complete::template_aliases))]
     template: Option<String>,
     /// Show changes to the repository at each operation
+    ///
+    /// Merge operations are also summarized by what each merged operation
+    /// changed and which bookmark or tag conflicts the merge introduced.
     #[arg(long, short = 'd')]
     op_diff: bool,
     /// Show patch of modifications to changes (implies --op-diff)
@@ -124,6 +128,7 @@ fn do_op_log(
 
     let template;
     let op_node_template;
+    let op_summary_template;
     {
         let language = OperationTemplateLanguage::new(
             repo_loader,
@@ -151,6 +156,14 @@ fn do_op_log(
                 OperationTemplateLanguage::wrap_operation,
             )?
             .labeled("node");
+        op_summary_template = workspace_env
+            .parse_template(
+                ui,
+                &language,
+                &settings.get_string("templates.op_summary")?,
+                OperationTemplateLanguage::wrap_operation,
+            )?
+            .labeled("operation");
     }
 
     let diff_formats = diff_formats_for_log(settings, &args.diff_format, args.patch)?;
@@ -176,6 +189,7 @@ fn do_op_log(
                     CommitTemplateLanguage::wrap_commit,
                 )?
             };
+            show_op_merge_summary(formatter, repo_loader, op, &op_summary_template)?;
             let path_converter = workspace_env.path_converter();
             let conflict_marker_style = workspace_env.conflict_marker_style();
             let diff_renderer = (!diff_formats.is_empty()).then(|| {
@@ -15,6 +15,7 @@
 mod abandon;
 mod diff;
 mod log;
+mod resolve;
 mod restore;
 mod show;
 pub mod undo;
@@ -26,6 +27,8 @@ use diff::cmd_op_diff;
 use diff::OperationDiffArgs;
 use log::cmd_op_log;
 use log::OperationLogArgs;
+use resolve::cmd_op_resolve;
+use resolve::OperationResolveArgs;
 use restore::cmd_op_restore;
 use restore::OperationRestoreArgs;
 use show::cmd_op_show;
@@ -48,6 +51,7 @@ pub enum OperationCommand {
     Abandon(OperationAbandonArgs),
     Diff(OperationDiffArgs),
     Log(OperationLogArgs),
+    Resolve(OperationResolveArgs),
     Restore(OperationRestoreArgs),
     Show(OperationShowArgs),
     Undo(OperationUndoArgs),
@@ -62,6 +66,7 @@ pub fn cmd_operation(
         OperationCommand::Abandon(args) => cmd_op_abandon(ui, command, args),
         OperationCommand::Diff(args) => cmd_op_diff(ui, command, args),
         OperationCommand::Log(args) => cmd_op_log(ui, command, args),
+        OperationCommand::Resolve(args) => cmd_op_resolve(ui, command, args),
         OperationCommand::Restore(args) => cmd_op_restore(ui, command, args),
         OperationCommand::Show(args) => cmd_op_show(ui, command, args),
         OperationCommand::Undo(args) => cmd_op_undo(ui, command, args),
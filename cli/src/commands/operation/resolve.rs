@@ -0,0 +1,145 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use clap_complete::ArgValueCandidates;
+use itertools::Itertools as _;
+use jj_lib::object_id::ObjectId as _;
+use jj_lib::ref_name::RefNameBuf;
+use jj_lib::str_util::StringPattern;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::complete;
+use crate::ui::Ui;
+
+/// Resolve bookmark conflicts by picking the targets from an operation
+///
+/// Concurrent operations, for example ones run on different hosts sharing the
+/// same repo, are merged automatically. If both sides moved the same bookmark,
+/// the merge leaves the bookmark conflicted. Use `jj op log` to find the merged
+/// operations, then pick one side's view of the conflicted bookmarks with this
+/// command.
+#[derive(clap::Args, Clone, Debug)]
+pub struct OperationResolveArgs {
+    /// The operation to take the bookmark targets from
+    #[arg(add = ArgValueCandidates::new(complete::operations))]
+    operation: String,
+    /// The bookmarks to resolve
+    ///
+    /// If no bookmarks are specified, all conflicted local bookmarks are
+    /// resolved.
+    ///
+    /// By default, the specified name matches exactly. Use `glob:` prefix to
+    /// select bookmarks by [wildcard pattern].
+    ///
+    /// [wildcard pattern]:
+    ///     https://jj-vcs.github.io/jj/latest/revsets/#string-patterns
+    #[arg(
+        value_parser = StringPattern::parse,
+        add = ArgValueCandidates::new(complete::local_bookmarks),
+    )]
+    names: Vec<StringPattern>,
+}
+
+pub fn cmd_op_resolve(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &OperationResolveArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let target_op = workspace_command.resolve_single_op(&args.operation)?;
+    let target_view = target_op.view()?;
+    let current_view = workspace_command.repo().view();
+
+    let names: Vec<RefNameBuf> = if args.names.is_empty() {
+        current_view
+            .local_bookmarks()
+            .filter(|(_, target)| target.has_conflict())
+            .map(|(name, _)| name.to_owned())
+            .collect()
+    } else {
+        let mut names = vec![];
+        let mut unmatched_patterns = vec![];
+        for pattern in &args.names {
+            let matches = itertools::chain(
+                current_view.local_bookmarks_matching(pattern),
+                target_view.local_bookmarks_matching(pattern),
+            )
+            .map(|(name, _)| name.to_owned())
+            .collect_vec();
+            if matches.is_empty() {
+                unmatched_patterns.push(pattern);
+            }
+            names.extend(matches);
+        }
+        match &unmatched_patterns[..] {
+            [] => {}
+            [pattern] if pattern.is_exact() => {
+                return Err(user_error(format!("No such bookmark: {pattern}")));
+            }
+            patterns => {
+                return Err(user_error(format!(
+                    "No matching bookmarks for patterns: {}",
+                    patterns.iter().join(", ")
+                )));
+            }
+        }
+        names.sort_unstable();
+        names.dedup();
+        names
+    };
+
+    let mut tx = workspace_command.start_transaction();
+    let mut resolved_names = vec![];
+    for name in &names {
+        let new_target = target_view.get_local_bookmark(name);
+        if tx.repo().get_local_bookmark(name) != *new_target {
+            tx.repo_mut()
+                .set_local_bookmark_target(name, new_target.clone());
+            resolved_names.push(name);
+        }
+    }
+    if resolved_names.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        let noun = if resolved_names.len() == 1 {
+            "bookmark"
+        } else {
+            "bookmarks"
+        };
+        write!(
+            formatter,
+            "Resolved {} {noun} to operation: ",
+            resolved_names.len()
+        )?;
+        let template = tx.base_workspace_helper().operation_summary_template();
+        template.format(&target_op, formatter.as_mut())?;
+        writeln!(formatter)?;
+    }
+    tx.finish(
+        ui,
+        format!(
+            "resolve bookmark {} to operation {}",
+            resolved_names
+                .iter()
+                .map(|name| name.as_symbol())
+                .join(", "),
+            target_op.id().hex()
+        ),
+    )?;
+    Ok(())
+}
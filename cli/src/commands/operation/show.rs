@@ -16,6 +16,7 @@ use clap_complete::ArgValueCandidates;
 use itertools::Itertools as _;
 
 use super::diff::show_op_diff;
+use super::diff::show_op_merge_summary;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::LogContentFormat;
 use crate::command_error::CommandError;
@@ -96,6 +97,12 @@ pub fn cmd_op_show(
     ui.request_pager();
     let mut formatter = ui.stdout_formatter();
     template.format(&op, formatter.as_mut())?;
+    show_op_merge_summary(
+        formatter.as_mut(),
+        repo_loader,
+        &op,
+        &workspace_command.operation_summary_template(),
+    )?;
 
     show_op_diff(
         ui,
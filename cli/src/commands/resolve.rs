@@ -12,17 +12,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::io::Write as _;
+
 use clap_complete::ArgValueCandidates;
 use clap_complete::ArgValueCompleter;
 use itertools::Itertools as _;
+use jj_lib::backend::BackendError;
+use jj_lib::backend::BackendResult;
+use jj_lib::backend::TreeValue;
+use jj_lib::commit::Commit;
+use jj_lib::merge::Merge;
+use jj_lib::merge::MergedTreeValue;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::object_id::ObjectId as _;
+use jj_lib::repo_path::RepoPathBuf;
 use tracing::instrument;
 
 use crate::cli_util::print_conflicted_paths;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::RevisionArg;
+use crate::cli_util::WorkspaceCommandHelper;
 use crate::command_error::cli_error;
 use crate::command_error::CommandError;
+use crate::commit_templater::CommitTemplateLanguage;
+use crate::commit_templater::ConflictEntry;
 use crate::complete;
 use crate::ui::Ui;
 
@@ -34,6 +48,11 @@ use crate::ui::Ui;
 /// resolved. To stop resolving conflicts, exit the merge tool without making
 /// any changes.
 ///
+/// Conflicts that a merge tool can't handle, such as a file conflicting with a
+/// symlink or a directory, are resolved by choosing one of the sides when run
+/// in a terminal. If the sides of a file conflict disagree on the executable
+/// bit, you're asked whether the resolved file should be executable.
+///
 /// Note that conflicts can also be resolved without using this command. You may
 /// edit the conflict markers in the conflicted file directly with a text
 /// editor.
@@ -58,6 +77,20 @@ pub(crate) struct ResolveArgs {
     // `diff --summary`, but should be more verbose.
     #[arg(long, short)]
     list: bool,
+    /// Render each conflict listed by `--list` using the given template
+    ///
+    /// All 0-argument methods of the [`ConflictEntry` type] are available as
+    /// keywords in the template expression. Use `-T builtin_resolve_list_json`
+    /// to print each conflict as a line of JSON. See [`jj help -k templates`]
+    /// for more information.
+    ///
+    /// [`ConflictEntry` type]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/#conflictentry-type
+    ///
+    /// [`jj help -k templates`]:
+    ///     https://jj-vcs.github.io/jj/latest/templates/
+    #[arg(long, short = 'T', requires = "list")]
+    template: Option<String>,
     /// Specify 3-way merge tool to be used
     #[arg(long, conflicts_with = "list", value_name = "NAME")]
     tool: Option<String>,
@@ -95,6 +128,15 @@ pub(crate) fn cmd_resolve(
         }));
     }
     if args.list {
+        if let Some(template_text) = &args.template {
+            return print_conflict_entries(
+                ui,
+                &workspace_command,
+                &commit,
+                conflicts,
+                template_text,
+            );
+        }
         return print_conflicted_paths(
             conflicts,
             ui.stdout_formatter().as_mut(),
@@ -102,15 +144,23 @@ pub(crate) fn cmd_resolve(
         );
     };
 
-    let repo_paths = conflicts
-        .iter()
-        .map(|(path, _)| path.as_ref())
-        .collect_vec();
     workspace_command.check_rewritable([commit.id()])?;
     let merge_editor = workspace_command.merge_editor(ui, args.tool.as_deref())?;
+    // Conflicts which the merge tool can't handle are resolved by asking the
+    // user. Without a terminal, the merge tool reports them as errors instead.
+    let (tree, file_paths) = if Ui::can_prompt() {
+        choose_non_file_resolutions(ui, &workspace_command, &tree, conflicts)?
+    } else {
+        let paths = conflicts.into_iter().map(|(path, _)| path).collect_vec();
+        (tree, paths)
+    };
+    let repo_paths = file_paths.iter().map(AsRef::as_ref).collect_vec();
     let mut tx = workspace_command.start_transaction();
-    let (new_tree_id, partial_resolution_error) =
-        merge_editor.edit_files(ui, &tree, &repo_paths)?;
+    let (new_tree_id, partial_resolution_error) = if repo_paths.is_empty() {
+        (tree.id(), None)
+    } else {
+        merge_editor.edit_files(ui, &tree, &repo_paths)?
+    };
     let new_commit = tx
         .repo_mut()
         .rewrite_commit(&commit)
@@ -143,3 +193,140 @@ pub(crate) fn cmd_resolve(
     }
     Ok(())
 }
+
+fn print_conflict_entries(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    commit: &Commit,
+    conflicts: Vec<(RepoPathBuf, BackendResult<MergedTreeValue>)>,
+    template_text: &str,
+) -> Result<(), CommandError> {
+    let language = workspace_command.commit_template_language();
+    let template = workspace_command
+        .parse_template(
+            ui,
+            &language,
+            template_text,
+            CommitTemplateLanguage::wrap_conflict_entry,
+        )?
+        .labeled("resolve_list");
+    let parents: Vec<_> = commit
+        .parents()
+        .map(|parent| {
+            let parent = parent?;
+            let tree = parent.tree()?;
+            Ok::<_, BackendError>((parent, tree))
+        })
+        .try_collect()?;
+
+    let mut formatter = ui.stdout_formatter();
+    for (path, value) in conflicts {
+        let entry = ConflictEntry::new(path, value?, &parents)?;
+        template.format(&entry, formatter.as_mut())?;
+    }
+    Ok(())
+}
+
+/// Lets the user resolve the conflicts which can't be passed to a merge tool.
+///
+/// Conflicts involving symlinks, directories, or submodules are resolved by
+/// choosing one of the sides. For file conflicts where the sides disagree on
+/// the executable bit, the user chooses the bit of the resolved file and the
+/// contents are left to the merge tool.
+///
+/// Returns the tree with the choices applied, and the paths which should be
+/// passed to the merge tool.
+fn choose_non_file_resolutions(
+    ui: &Ui,
+    workspace_command: &WorkspaceCommandHelper,
+    tree: &MergedTree,
+    conflicts: Vec<(RepoPathBuf, BackendResult<MergedTreeValue>)>,
+) -> Result<(MergedTree, Vec<RepoPathBuf>), CommandError> {
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut num_chosen = 0;
+    let mut file_paths = vec![];
+    for (path, value) in conflicts {
+        let value = value?;
+        let ui_path = workspace_command.format_file_path(&path);
+        if value.to_file_merge().is_some() {
+            if value.resolve_executable().is_some() {
+                file_paths.push(path);
+                continue;
+            }
+            writeln!(
+                ui.stderr(),
+                "The sides of the conflict in {ui_path} disagree on the executable bit:"
+            )?;
+            writeln!(ui.stderr(), "1: make the resolved file executable")?;
+            writeln!(ui.stderr(), "2: make the resolved file non-executable")?;
+            writeln!(ui.stderr(), "s: skip this file")?;
+            let index =
+                ui.prompt_choice("Enter the index of the resolution", &["1", "2", "s"], None)?;
+            if index == 2 {
+                continue;
+            }
+            let new_value = value.map(|term| match term {
+                Some(TreeValue::File { id, executable: _ }) => Some(TreeValue::File {
+                    id: id.clone(),
+                    executable: index == 0,
+                }),
+                term => term.clone(),
+            });
+            // The conflict is resolved if the contents were already the same.
+            if let Some(resolved) = new_value.resolve_trivial() {
+                tree_builder.set_or_remove(path, Merge::resolved(resolved.clone()));
+            } else {
+                tree_builder.set_or_remove(path.clone(), new_value);
+                file_paths.push(path);
+            }
+        } else {
+            writeln!(
+                ui.stderr(),
+                "The conflict in {ui_path} can't be resolved by a merge tool. Choose a side to \
+                 keep:"
+            )?;
+            let mut choices = vec![];
+            for (i, term) in value.adds().enumerate() {
+                writeln!(
+                    ui.stderr(),
+                    "{}: side #{} ({})",
+                    i + 1,
+                    i + 1,
+                    describe_conflict_side(term.as_ref())
+                )?;
+                choices.push((i + 1).to_string());
+            }
+            writeln!(ui.stderr(), "s: skip this path")?;
+            choices.push("s".to_owned());
+            let index = ui.prompt_choice("Enter the index of the side to keep", &choices, None)?;
+            let Some(term) = value.get_add(index) else {
+                continue;
+            };
+            tree_builder.set_or_remove(path, Merge::resolved(term.clone()));
+        }
+        num_chosen += 1;
+    }
+    let tree = if num_chosen > 0 {
+        let tree_id = tree_builder.write_tree(tree.store())?;
+        tree.store().get_root_tree(&tree_id)?
+    } else {
+        tree.clone()
+    };
+    Ok((tree, file_paths))
+}
+
+fn describe_conflict_side(term: Option<&TreeValue>) -> &'static str {
+    match term {
+        None => "deleted",
+        Some(TreeValue::File {
+            executable: false, ..
+        }) => "file",
+        Some(TreeValue::File {
+            executable: true, ..
+        }) => "executable file",
+        Some(TreeValue::Symlink(_)) => "symlink",
+        Some(TreeValue::Tree(_)) => "directory",
+        Some(TreeValue::GitSubmodule(_)) => "git submodule",
+        Some(TreeValue::Conflict(_)) => "conflict",
+    }
+}
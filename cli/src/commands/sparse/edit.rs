@@ -17,6 +17,7 @@ use std::path::Path;
 
 use itertools::Itertools as _;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePatterns;
 use tracing::instrument;
 
 use super::update_sparse_patterns_with;
@@ -28,6 +29,10 @@ use crate::description_util::TextEditor;
 use crate::ui::Ui;
 
 /// Start an editor to update the patterns that are present in the working copy
+///
+/// If the patterns are a fileset expression, the expression is edited instead
+/// of the list of paths. Editing the patterns of a sparse profile stops using
+/// the profile.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseEditArgs {}
 
@@ -39,12 +44,34 @@ pub fn cmd_sparse_edit(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     let editor = workspace_command.text_editor()?;
-    update_sparse_patterns_with(ui, &mut workspace_command, |_ui, old_patterns| {
-        let mut new_patterns = edit_sparse(&editor, old_patterns)?;
-        new_patterns.sort_unstable();
-        new_patterns.dedup();
-        Ok(new_patterns)
-    })
+    update_sparse_patterns_with(
+        ui,
+        &mut workspace_command,
+        |_ui, old_patterns| match old_patterns {
+            SparsePatterns::Prefixes(old_prefixes) => {
+                let mut new_prefixes = edit_sparse(&editor, old_prefixes)?;
+                new_prefixes.sort_unstable();
+                new_prefixes.dedup();
+                Ok(SparsePatterns::Prefixes(new_prefixes))
+            }
+            SparsePatterns::Fileset { text, .. } | SparsePatterns::Profile { text, .. } => {
+                let text = edit_sparse_fileset(&editor, text)?;
+                Ok(SparsePatterns::parse_fileset(&text)?)
+            }
+        },
+    )
+}
+
+fn edit_sparse_fileset(editor: &TextEditor, text: &str) -> Result<String, CommandError> {
+    let content = editor
+        .edit_str(format!("{text}\n"), Some(".jjsparse"))
+        .map_err(|err| err.with_name("sparse patterns"))?;
+    Ok(content
+        .lines()
+        .filter(|line| !line.starts_with("JJ:"))
+        .join("\n")
+        .trim()
+        .to_owned())
 }
 
 fn edit_sparse(
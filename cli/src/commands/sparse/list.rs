@@ -15,6 +15,7 @@
 use std::io::Write as _;
 use std::path::Path;
 
+use jj_lib::working_copy::SparsePatterns;
 use tracing::instrument;
 
 use crate::cli_util::CommandHelper;
@@ -25,7 +26,9 @@ use crate::ui::Ui;
 ///
 /// By default, a newly cloned or initialized repo will have have a pattern
 /// matching all files from the repo root. That pattern is rendered as `.` (a
-/// single period).
+/// single period). If the patterns are a fileset expression, the expression is
+/// printed as is. If a sparse profile is in use, its name is printed before
+/// the expression read from the profile.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseListArgs {}
 
@@ -36,12 +39,21 @@ pub fn cmd_sparse_list(
     _args: &SparseListArgs,
 ) -> Result<(), CommandError> {
     let workspace_command = command.workspace_helper(ui)?;
-    for path in workspace_command.working_copy().sparse_patterns()? {
-        writeln!(
-            ui.stdout(),
-            "{}",
-            path.to_fs_path_unchecked(Path::new("")).display()
-        )?;
+    match workspace_command.working_copy().sparse_patterns()? {
+        SparsePatterns::Prefixes(prefixes) => {
+            for path in prefixes {
+                writeln!(
+                    ui.stdout(),
+                    "{}",
+                    path.to_fs_path_unchecked(Path::new("")).display()
+                )?;
+            }
+        }
+        SparsePatterns::Fileset { text, .. } => writeln!(ui.stdout(), "{text}")?,
+        SparsePatterns::Profile { name, text, .. } => {
+            writeln!(ui.status(), "Using sparse profile {name}")?;
+            writeln!(ui.stdout(), "{text}")?;
+        }
     }
     Ok(())
 }
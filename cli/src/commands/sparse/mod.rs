@@ -16,9 +16,10 @@ mod edit;
 mod list;
 mod reset;
 mod set;
+mod use_profile;
 
 use clap::Subcommand;
-use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePatterns;
 use tracing::instrument;
 
 use self::edit::cmd_sparse_edit;
@@ -29,6 +30,8 @@ use self::reset::cmd_sparse_reset;
 use self::reset::SparseResetArgs;
 use self::set::cmd_sparse_set;
 use self::set::SparseSetArgs;
+use self::use_profile::cmd_sparse_use;
+use self::use_profile::SparseUseArgs;
 use crate::cli_util::print_checkout_stats;
 use crate::cli_util::CommandHelper;
 use crate::cli_util::WorkspaceCommandHelper;
@@ -38,12 +41,25 @@ use crate::ui::Ui;
 
 /// Manage which paths from the working-copy commit are present in the working
 /// copy
+///
+/// The paths can be specified as a list of path prefixes, or as a [fileset]
+/// expression with `jj sparse set --fileset`. File patterns in the expression
+/// are relative to the workspace root.
+///
+/// A fileset expression can also be stored in the repo as a sparse profile
+/// `.jj-sparse/<name>`, and applied with `jj sparse use <name>`. Lines
+/// starting with `#` in the profile are ignored. The profile is read again
+/// whenever another commit is checked out, so changes to the profile take
+/// effect automatically.
+///
+/// [fileset]: https://jj-vcs.github.io/jj/latest/filesets/
 #[derive(Subcommand, Clone, Debug)]
 pub(crate) enum SparseCommand {
     Edit(SparseEditArgs),
     List(SparseListArgs),
     Reset(SparseResetArgs),
     Set(SparseSetArgs),
+    Use(SparseUseArgs),
 }
 
 #[instrument(skip_all)]
@@ -57,13 +73,14 @@ pub(crate) fn cmd_sparse(
         SparseCommand::List(args) => cmd_sparse_list(ui, command, args),
         SparseCommand::Reset(args) => cmd_sparse_reset(ui, command, args),
         SparseCommand::Set(args) => cmd_sparse_set(ui, command, args),
+        SparseCommand::Use(args) => cmd_sparse_use(ui, command, args),
     }
 }
 
 fn update_sparse_patterns_with(
     ui: &mut Ui,
     workspace_command: &mut WorkspaceCommandHelper,
-    f: impl FnOnce(&mut Ui, &[RepoPathBuf]) -> Result<Vec<RepoPathBuf>, CommandError>,
+    f: impl FnOnce(&mut Ui, &SparsePatterns) -> Result<SparsePatterns, CommandError>,
 ) -> Result<(), CommandError> {
     let checkout_options = workspace_command.checkout_options();
     let (mut locked_ws, wc_commit) = workspace_command.start_working_copy_mutation()?;
@@ -16,6 +16,7 @@ use std::collections::HashSet;
 
 use itertools::Itertools as _;
 use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::working_copy::SparsePatterns;
 use tracing::instrument;
 
 use super::update_sparse_patterns_with;
@@ -28,6 +29,11 @@ use crate::ui::Ui;
 /// For example, if all you need is the `README.md` and the `lib/`
 /// directory, use `jj sparse set --clear --add README.md --add lib`.
 /// If you no longer need the `lib` directory, use `jj sparse set --remove lib`.
+///
+/// For more complex patterns, use a fileset expression, e.g. `jj sparse set
+/// --fileset 'src ~ glob:"src/**/testdata/**"'`. If the patterns are a fileset
+/// expression, `--add` and `--remove` extend the expression. Updating the
+/// patterns of a sparse profile stops using the profile.
 #[derive(clap::Args, Clone, Debug)]
 pub struct SparseSetArgs {
     /// Patterns to add to the working copy
@@ -48,6 +54,11 @@ pub struct SparseSetArgs {
     /// Include no files in the working copy (combine with --add)
     #[arg(long)]
     clear: bool,
+    /// Replace the patterns with a fileset expression
+    ///
+    /// File patterns in the expression are relative to the workspace root.
+    #[arg(long, conflicts_with_all = ["add", "remove", "clear"])]
+    fileset: Option<String>,
 }
 
 #[instrument(skip_all)]
@@ -58,16 +69,40 @@ pub fn cmd_sparse_set(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
     update_sparse_patterns_with(ui, &mut workspace_command, |_ui, old_patterns| {
-        let mut new_patterns = HashSet::new();
-        if !args.clear {
-            new_patterns.extend(old_patterns.iter().cloned());
-            for path in &args.remove {
-                new_patterns.remove(path);
-            }
+        if let Some(text) = &args.fileset {
+            return Ok(SparsePatterns::parse_fileset(text)?);
         }
-        for path in &args.add {
-            new_patterns.insert(path.to_owned());
+        match old_patterns {
+            SparsePatterns::Prefixes(old_prefixes) => {
+                let mut new_patterns = HashSet::new();
+                if !args.clear {
+                    new_patterns.extend(old_prefixes.iter().cloned());
+                    for path in &args.remove {
+                        new_patterns.remove(path);
+                    }
+                }
+                for path in &args.add {
+                    new_patterns.insert(path.to_owned());
+                }
+                Ok(SparsePatterns::Prefixes(
+                    new_patterns.into_iter().sorted_unstable().collect(),
+                ))
+            }
+            SparsePatterns::Fileset { .. } | SparsePatterns::Profile { .. } if args.clear => {
+                Ok(SparsePatterns::Prefixes(args.add.clone()))
+            }
+            SparsePatterns::Fileset { text, .. } | SparsePatterns::Profile { text, .. } => {
+                let mut text = text.clone();
+                if !args.remove.is_empty() {
+                    let removed = SparsePatterns::Prefixes(args.remove.clone());
+                    text = format!("({text}) ~ ({})", removed.to_fileset_text());
+                }
+                if !args.add.is_empty() {
+                    let added = SparsePatterns::Prefixes(args.add.clone());
+                    text = format!("({text}) | {}", added.to_fileset_text());
+                }
+                Ok(SparsePatterns::parse_fileset(&text)?)
+            }
         }
-        Ok(new_patterns.into_iter().sorted_unstable().collect())
     })
 }
@@ -0,0 +1,58 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use jj_lib::repo::Repo as _;
+use jj_lib::working_copy::SparsePatterns;
+use tracing::instrument;
+
+use super::update_sparse_patterns_with;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Use a sparse profile stored in the repo
+///
+/// The profile is read from the file `.jj-sparse/<PROFILE>` in the
+/// working-copy commit. It contains a fileset expression with paths relative
+/// to the workspace root. Lines starting with `#` are ignored.
+///
+/// The profile is read again whenever another commit is checked out. Use `jj
+/// sparse set` or `jj sparse reset` to stop using the profile.
+#[derive(clap::Args, Clone, Debug)]
+pub struct SparseUseArgs {
+    /// The name of the profile
+    profile: String,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_sparse_use(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &SparseUseArgs,
+) -> Result<(), CommandError> {
+    let mut workspace_command = command.workspace_helper(ui)?;
+    let wc_commit_id = workspace_command
+        .get_wc_commit_id()
+        .ok_or_else(|| user_error("This command requires a working copy"))?;
+    let tree = workspace_command
+        .repo()
+        .store()
+        .get_commit(wc_commit_id)?
+        .tree()?;
+    let new_patterns = SparsePatterns::load_profile(&tree, &args.profile).map_err(user_error)?;
+    update_sparse_patterns_with(ui, &mut workspace_command, |_ui, _old_patterns| {
+        Ok(new_patterns)
+    })
+}
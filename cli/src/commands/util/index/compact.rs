@@ -0,0 +1,66 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::io::Write as _;
+
+use jj_lib::default_index::AsCompositeIndex as _;
+use jj_lib::default_index::DefaultIndexStore;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Merge all index segment files into one
+///
+/// The commit index is stored as a stack of segment files, which grows as new
+/// operations are indexed. This command merges the segments of the current
+/// operation into a single file, and removes the files no longer in use.
+/// Indexes of other operations will be rebuilt when needed.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilIndexCompactArgs {}
+
+pub fn cmd_util_index_compact(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilIndexCompactArgs,
+) -> Result<(), CommandError> {
+    if !command.is_at_head_operation() {
+        return Err(user_error(
+            "Cannot compact the index from a non-head operation",
+        ));
+    }
+    // Resolve the operation without loading the repo. The index might have to
+    // be rebuilt while loading the repo.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.as_any().downcast_ref::<DefaultIndexStore>() else {
+        return Err(user_error(format!(
+            "Cannot compact indexes of type '{}'",
+            index_store.name()
+        )));
+    };
+    let default_index = default_index_store
+        .compact_index_at_operation(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    writeln!(
+        ui.status(),
+        "Compacted index of {} commits into one segment file.",
+        default_index.as_composite().stats().num_commits
+    )?;
+    Ok(())
+}
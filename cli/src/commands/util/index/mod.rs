@@ -0,0 +1,44 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+mod compact;
+mod verify;
+
+use clap::Subcommand;
+
+use self::compact::cmd_util_index_compact;
+use self::compact::UtilIndexCompactArgs;
+use self::verify::cmd_util_index_verify;
+use self::verify::UtilIndexVerifyArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage the commit index
+#[derive(Subcommand, Clone, Debug)]
+pub enum UtilIndexCommand {
+    Compact(UtilIndexCompactArgs),
+    Verify(UtilIndexVerifyArgs),
+}
+
+pub fn cmd_util_index(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &UtilIndexCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        UtilIndexCommand::Compact(args) => cmd_util_index_compact(ui, command, args),
+        UtilIndexCommand::Verify(args) => cmd_util_index_verify(ui, command, args),
+    }
+}
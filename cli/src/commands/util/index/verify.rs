@@ -0,0 +1,70 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error;
+use std::io::Write as _;
+use std::iter;
+
+use itertools::Itertools as _;
+use jj_lib::default_index::DefaultIndexStore;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::internal_error;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Check the integrity of the commit index
+///
+/// Checks that the index files of all operations can be loaded, and that the
+/// index of the current operation matches the commits in the backend. This
+/// includes the parents, change ids, and generation numbers of all indexed
+/// commits, and the commit id and change id lookup tables.
+///
+/// If problems are found, run `jj debug reindex` to rebuild the index.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilIndexVerifyArgs {}
+
+pub fn cmd_util_index_verify(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilIndexVerifyArgs,
+) -> Result<(), CommandError> {
+    // Resolve the operation without loading the repo, so this command won't
+    // update the index.
+    let workspace = command.load_workspace()?;
+    let repo_loader = workspace.repo_loader();
+    let op = command.resolve_operation(ui, repo_loader)?;
+    let index_store = repo_loader.index_store();
+    let Some(default_index_store) = index_store.as_any().downcast_ref::<DefaultIndexStore>() else {
+        return Err(user_error(format!(
+            "Cannot verify indexes of type '{}'",
+            index_store.name()
+        )));
+    };
+    let problems = default_index_store
+        .verify_index_at_operation(&op, repo_loader.store())
+        .map_err(internal_error)?;
+    if problems.is_empty() {
+        writeln!(ui.status(), "No problems found in the index.")?;
+        return Ok(());
+    }
+    for problem in &problems {
+        let err: &dyn error::Error = problem;
+        let message = iter::successors(Some(err), |&err| err.source()).format(": ");
+        writeln!(ui.stdout(), "{message}")?;
+    }
+    Err(user_error("Index verification failed")
+        .hinted("Run `jj debug reindex` to rebuild the index."))
+}
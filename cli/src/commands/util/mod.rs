@@ -16,8 +16,10 @@ mod completion;
 mod config_schema;
 mod exec;
 mod gc;
+mod index;
 mod install_man_pages;
 mod markdown_help;
+mod resolutions;
 
 use clap::Subcommand;
 use tracing::instrument;
@@ -30,10 +32,14 @@ use self::exec::cmd_util_exec;
 use self::exec::UtilExecArgs;
 use self::gc::cmd_util_gc;
 use self::gc::UtilGcArgs;
+use self::index::cmd_util_index;
+use self::index::UtilIndexCommand;
 use self::install_man_pages::cmd_util_install_man_pages;
 use self::install_man_pages::UtilInstallManPagesArgs;
 use self::markdown_help::cmd_util_markdown_help;
 use self::markdown_help::UtilMarkdownHelp;
+use self::resolutions::cmd_util_resolutions;
+use self::resolutions::UtilResolutionsCommand;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
@@ -45,8 +51,12 @@ pub(crate) enum UtilCommand {
     ConfigSchema(UtilConfigSchemaArgs),
     Exec(UtilExecArgs),
     Gc(UtilGcArgs),
+    #[command(subcommand)]
+    Index(UtilIndexCommand),
     InstallManPages(UtilInstallManPagesArgs),
     MarkdownHelp(UtilMarkdownHelp),
+    #[command(subcommand)]
+    Resolutions(UtilResolutionsCommand),
 }
 
 #[instrument(skip_all)]
@@ -60,7 +70,9 @@ pub(crate) fn cmd_util(
         UtilCommand::ConfigSchema(args) => cmd_util_config_schema(ui, command, args),
         UtilCommand::Exec(args) => cmd_util_exec(ui, command, args),
         UtilCommand::Gc(args) => cmd_util_gc(ui, command, args),
+        UtilCommand::Index(args) => cmd_util_index(ui, command, args),
         UtilCommand::InstallManPages(args) => cmd_util_install_man_pages(ui, command, args),
         UtilCommand::MarkdownHelp(args) => cmd_util_markdown_help(ui, command, args),
+        UtilCommand::Resolutions(args) => cmd_util_resolutions(ui, command, args),
     }
 }
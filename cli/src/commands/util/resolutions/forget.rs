@@ -0,0 +1,83 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::io::Write as _;
+
+use itertools::Itertools as _;
+use jj_lib::resolution_cache::ResolutionCache;
+
+use super::list::short_key;
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Forget recorded conflict resolutions
+#[derive(clap::Args, Clone, Debug)]
+#[command(group(clap::ArgGroup::new("target").args(&["keys", "all"]).required(true)))]
+pub struct UtilResolutionsForgetArgs {
+    /// Keys (or unique key prefixes) of the resolutions to forget
+    keys: Vec<String>,
+    /// Forget all recorded resolutions
+    #[arg(long)]
+    all: bool,
+}
+
+pub fn cmd_util_resolutions_forget(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &UtilResolutionsForgetArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let cache = ResolutionCache::new(ResolutionCache::dir_in_repo(workspace_command.repo_path()));
+    let resolutions = cache
+        .list()
+        .map_err(|err| user_error_with_message("Failed to read recorded resolutions", err))?;
+    let keys_to_forget = if args.all {
+        resolutions
+            .iter()
+            .map(|resolution| &resolution.key)
+            .collect_vec()
+    } else {
+        let mut keys = vec![];
+        for prefix in &args.keys {
+            let matches = resolutions
+                .iter()
+                .filter(|resolution| resolution.key.starts_with(prefix.as_str()))
+                .collect_vec();
+            match matches.as_slice() {
+                [] => {
+                    return Err(user_error(format!(
+                        "No recorded resolution matches key {prefix}"
+                    )));
+                }
+                [resolution] => keys.push(&resolution.key),
+                _ => {
+                    return Err(user_error(format!("Key prefix {prefix} is ambiguous")));
+                }
+            }
+        }
+        keys.into_iter().unique().collect()
+    };
+    for key in &keys_to_forget {
+        cache
+            .remove(key)
+            .map_err(|err| user_error_with_message("Failed to forget recorded resolution", err))?;
+        writeln!(ui.status(), "Forgot resolution {}", short_key(key))?;
+    }
+    if keys_to_forget.is_empty() {
+        writeln!(ui.status(), "No recorded resolutions to forget.")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::io::Write as _;
+
+use jj_lib::resolution_cache::ResolutionCache;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// List recorded conflict resolutions
+///
+/// Each line shows the key of the recorded conflict hunk and the path of the
+/// file where it was resolved.
+#[derive(clap::Args, Clone, Debug)]
+pub struct UtilResolutionsListArgs {}
+
+pub fn cmd_util_resolutions_list(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    _args: &UtilResolutionsListArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper_no_snapshot(ui)?;
+    let cache = ResolutionCache::new(ResolutionCache::dir_in_repo(workspace_command.repo_path()));
+    let resolutions = cache
+        .list()
+        .map_err(|err| user_error_with_message("Failed to read recorded resolutions", err))?;
+    let mut formatter = ui.stdout_formatter();
+    for resolution in &resolutions {
+        writeln!(
+            formatter,
+            "{} {}",
+            short_key(&resolution.key),
+            workspace_command.format_file_path(&resolution.path)
+        )?;
+    }
+    Ok(())
+}
+
+pub(super) fn short_key(key: &str) -> &str {
+    &key[..key.len().min(12)]
+}
@@ -0,0 +1,47 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+mod forget;
+mod list;
+
+use clap::Subcommand;
+
+use self::forget::cmd_util_resolutions_forget;
+use self::forget::UtilResolutionsForgetArgs;
+use self::list::cmd_util_resolutions_list;
+use self::list::UtilResolutionsListArgs;
+use crate::cli_util::CommandHelper;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Manage recorded conflict resolutions
+///
+/// When `merge.reuse-resolutions` is enabled, resolving a conflicted commit
+/// records how each conflicted hunk was resolved. The recorded resolutions are
+/// re-applied automatically when the same conflict appears again.
+#[derive(Subcommand, Clone, Debug)]
+pub enum UtilResolutionsCommand {
+    Forget(UtilResolutionsForgetArgs),
+    List(UtilResolutionsListArgs),
+}
+
+pub fn cmd_util_resolutions(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    subcommand: &UtilResolutionsCommand,
+) -> Result<(), CommandError> {
+    match subcommand {
+        UtilResolutionsCommand::Forget(args) => cmd_util_resolutions_forget(ui, command, args),
+        UtilResolutionsCommand::List(args) => cmd_util_resolutions_list(ui, command, args),
+    }
+}
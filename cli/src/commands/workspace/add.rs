@@ -21,6 +21,7 @@ use jj_lib::file_util::IoResultExt as _;
 use jj_lib::op_store::WorkspaceId;
 use jj_lib::repo::Repo as _;
 use jj_lib::rewrite::merge_commit_trees;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::workspace::Workspace;
 use tracing::instrument;
 
@@ -82,6 +83,9 @@ pub fn cmd_workspace_add(
     args: &WorkspaceAddArgs,
 ) -> Result<(), CommandError> {
     let old_workspace_command = command.workspace_helper(ui)?;
+    // Record where this workspace lives, too, in case it was moved or added
+    // before locations were recorded.
+    old_workspace_command.workspace().update_recorded_path()?;
     let destination_path = command.cwd().join(&args.destination);
     if destination_path.exists() {
         return Err(user_error("Workspace already exists"));
@@ -136,12 +140,12 @@ pub fn cmd_workspace_add(
 
     let sparsity = match args.sparse_patterns {
         SparseInheritance::Full => None,
-        SparseInheritance::Empty => Some(vec![]),
+        SparseInheritance::Empty => Some(SparsePatterns::Prefixes(vec![])),
         SparseInheritance::Copy => {
             let sparse_patterns = old_workspace_command
                 .working_copy()
                 .sparse_patterns()?
-                .to_vec();
+                .clone();
             Some(sparse_patterns)
         }
     };
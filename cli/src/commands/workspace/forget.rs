@@ -17,6 +17,7 @@ use itertools::Itertools as _;
 use jj_lib::op_store::WorkspaceId;
 use tracing::instrument;
 
+use super::find_missing_workspace_path;
 use crate::cli_util::CommandHelper;
 use crate::command_error::user_error;
 use crate::command_error::CommandError;
@@ -33,6 +34,9 @@ pub struct WorkspaceForgetArgs {
     /// workspace.
     #[arg(add = ArgValueCandidates::new(complete::workspaces))]
     workspaces: Vec<String>,
+    /// Forget all workspaces whose directories no longer exist
+    #[arg(long, conflicts_with = "workspaces")]
+    missing: bool,
 }
 
 #[instrument(skip_all)]
@@ -43,7 +47,22 @@ pub fn cmd_workspace_forget(
 ) -> Result<(), CommandError> {
     let mut workspace_command = command.workspace_helper(ui)?;
 
-    let wss: Vec<WorkspaceId> = if args.workspaces.is_empty() {
+    let wss: Vec<WorkspaceId> = if args.missing {
+        let workspace_store = workspace_command.workspace().workspace_store();
+        let mut wss = vec![];
+        for ws in workspace_command.repo().view().wc_commit_ids().keys() {
+            if ws != workspace_command.workspace_id()
+                && find_missing_workspace_path(&workspace_store, ws)?.is_some()
+            {
+                wss.push(ws.clone());
+            }
+        }
+        if wss.is_empty() {
+            writeln!(ui.status(), "Nothing changed.")?;
+            return Ok(());
+        }
+        wss
+    } else if args.workspaces.is_empty() {
         vec![workspace_command.workspace_id().clone()]
     } else {
         args.workspaces
@@ -78,5 +97,9 @@ pub fn cmd_workspace_forget(
     };
 
     tx.finish(ui, description)?;
+    workspace_command
+        .workspace()
+        .workspace_store()
+        .forget(&wss.iter().collect_vec())?;
     Ok(())
 }
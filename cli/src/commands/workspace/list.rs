@@ -15,11 +15,14 @@
 use jj_lib::repo::Repo as _;
 use tracing::instrument;
 
+use super::find_missing_workspace_path;
 use crate::cli_util::CommandHelper;
 use crate::command_error::CommandError;
 use crate::ui::Ui;
 
 /// List workspaces
+///
+/// Workspaces whose directories no longer exist are reported as missing.
 #[derive(clap::Args, Clone, Debug)]
 pub struct WorkspaceListArgs {}
 
@@ -33,10 +36,25 @@ pub fn cmd_workspace_list(
     let repo = workspace_command.repo();
     let mut formatter = ui.stdout_formatter();
     let template = workspace_command.commit_summary_template();
+    let workspace_store = workspace_command.workspace().workspace_store();
     for (workspace_id, wc_commit_id) in repo.view().wc_commit_ids() {
         write!(formatter, "{}: ", workspace_id.as_str())?;
         let commit = repo.store().get_commit(wc_commit_id)?;
         template.format(&commit, formatter.as_mut())?;
+        // The current workspace exists even if it was moved after its location
+        // was recorded.
+        let missing_path = if workspace_id == workspace_command.workspace_id() {
+            None
+        } else {
+            find_missing_workspace_path(&workspace_store, workspace_id)?
+        };
+        if let Some(path) = missing_path {
+            write!(
+                formatter.labeled("warning"),
+                " (missing: {})",
+                path.display()
+            )?;
+        }
         writeln!(formatter)?;
     }
     Ok(())
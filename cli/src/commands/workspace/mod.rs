@@ -16,10 +16,16 @@ mod add;
 mod forget;
 mod list;
 mod rename;
+mod repair;
 mod root;
 mod update_stale;
 
+use std::path::Path;
+use std::path::PathBuf;
+
 use clap::Subcommand;
+use jj_lib::op_store::WorkspaceId;
+use jj_lib::workspace_store::WorkspaceStore;
 use tracing::instrument;
 
 use self::add::cmd_workspace_add;
@@ -30,6 +36,8 @@ use self::list::cmd_workspace_list;
 use self::list::WorkspaceListArgs;
 use self::rename::cmd_workspace_rename;
 use self::rename::WorkspaceRenameArgs;
+use self::repair::cmd_workspace_repair;
+use self::repair::WorkspaceRepairArgs;
 use self::root::cmd_workspace_root;
 use self::root::WorkspaceRootArgs;
 use self::update_stale::cmd_workspace_update_stale;
@@ -49,12 +57,18 @@ use crate::ui::Ui;
 /// `jj log`.
 ///
 /// Each workspace also has own sparse patterns.
+///
+/// The location of each workspace is recorded in the repo. If a workspace
+/// directory is moved or deleted, `jj workspace list` reports it as missing.
+/// Use `jj workspace repair` to reattach moved workspaces, or `jj workspace
+/// forget --missing` to forget the deleted ones.
 #[derive(Subcommand, Clone, Debug)]
 pub(crate) enum WorkspaceCommand {
     Add(WorkspaceAddArgs),
     Forget(WorkspaceForgetArgs),
     List(WorkspaceListArgs),
     Rename(WorkspaceRenameArgs),
+    Repair(WorkspaceRepairArgs),
     Root(WorkspaceRootArgs),
     UpdateStale(WorkspaceUpdateStaleArgs),
 }
@@ -70,7 +84,22 @@ pub(crate) fn cmd_workspace(
         WorkspaceCommand::Forget(args) => cmd_workspace_forget(ui, command, args),
         WorkspaceCommand::List(args) => cmd_workspace_list(ui, command, args),
         WorkspaceCommand::Rename(args) => cmd_workspace_rename(ui, command, args),
+        WorkspaceCommand::Repair(args) => cmd_workspace_repair(ui, command, args),
         WorkspaceCommand::Root(args) => cmd_workspace_root(ui, command, args),
         WorkspaceCommand::UpdateStale(args) => cmd_workspace_update_stale(ui, command, args),
     }
 }
+
+/// Returns the recorded root of the workspace if the directory no longer
+/// contains a workspace.
+fn find_missing_workspace_path(
+    workspace_store: &WorkspaceStore,
+    workspace_id: &WorkspaceId,
+) -> Result<Option<PathBuf>, CommandError> {
+    let path = workspace_store.get_workspace_path(workspace_id)?;
+    Ok(path.filter(|path| !is_workspace_dir(path)))
+}
+
+fn is_workspace_dir(path: &Path) -> bool {
+    path.join(".jj").is_dir()
+}
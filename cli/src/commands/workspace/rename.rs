@@ -40,6 +40,7 @@ pub fn cmd_workspace_rename(
     let mut workspace_command = command.workspace_helper(ui)?;
 
     let old_workspace_id = workspace_command.working_copy().workspace_id().clone();
+    let workspace_store = workspace_command.workspace().workspace_store();
     let new_workspace_id = WorkspaceId::new(args.new_workspace_name.clone());
     if new_workspace_id == old_workspace_id {
         writeln!(ui.status(), "Nothing changed.")?;
@@ -66,13 +67,14 @@ pub fn cmd_workspace_rename(
         .rename_workspace(new_workspace_id.clone());
 
     tx.repo_mut()
-        .rename_workspace(&old_workspace_id, new_workspace_id)?;
+        .rename_workspace(&old_workspace_id, new_workspace_id.clone())?;
     let repo = tx.commit(format!(
         "Renamed workspace '{}' to '{}'",
         old_workspace_id.as_str(),
         args.new_workspace_name
     ))?;
     locked_ws.finish(repo.op_id().clone())?;
+    workspace_store.rename(&old_workspace_id, &new_workspace_id)?;
 
     Ok(())
 }
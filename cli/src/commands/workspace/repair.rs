@@ -0,0 +1,135 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::path::PathBuf;
+
+use jj_lib::op_store::WorkspaceId;
+use jj_lib::workspace::Workspace;
+use tracing::instrument;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::user_error_with_message;
+use crate::command_error::CommandError;
+use crate::ui::Ui;
+
+/// Reattach workspaces after they or the repo were moved
+///
+/// The location of each workspace is recorded in the repo when the workspace
+/// is added, and is updated when `jj workspace add` or `jj workspace repair`
+/// runs in it. The other workspaces find the repo through the path stored in
+/// their `.jj/repo` file, so they break when the workspace containing the repo
+/// is moved.
+///
+/// Run this command in the workspace containing the repo to point the other
+/// workspaces at their recorded locations back to the repo. Workspaces that
+/// were moved too can be reattached by passing their new paths.
+#[derive(clap::Args, Clone, Debug)]
+pub struct WorkspaceRepairArgs {
+    /// Paths to workspaces to reattach to this repo
+    #[arg(value_hint = clap::ValueHint::DirPath)]
+    paths: Vec<String>,
+}
+
+#[instrument(skip_all)]
+pub fn cmd_workspace_repair(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    args: &WorkspaceRepairArgs,
+) -> Result<(), CommandError> {
+    let workspace_command = command.workspace_helper(ui)?;
+    // This workspace may have been moved, too.
+    workspace_command.workspace().update_recorded_path()?;
+    let repo_path = workspace_command.repo_path();
+    let repo_dir = dunce::canonicalize(repo_path).ok();
+    let workspace_store = workspace_command.workspace().workspace_store();
+    let view = workspace_command.repo().view();
+
+    let mut repaired: Vec<(WorkspaceId, PathBuf)> = vec![];
+    for path in &args.paths {
+        let workspace_root = dunce::canonicalize(command.cwd().join(path)).map_err(|err| {
+            user_error_with_message(format!("Failed to access workspace at {path}"), err)
+        })?;
+        if !workspace_root.join(".jj").join("repo").is_file() {
+            return Err(user_error(format!(
+                "No workspace that can be reattached at {path}"
+            )));
+        }
+        // Check that the workspace belongs to this repo before repointing it
+        let Some(workspace_id) = Workspace::read_workspace_id(&workspace_root) else {
+            return Err(user_error(format!(
+                "Failed to read the workspace name at {path}"
+            )));
+        };
+        if workspace_id == *workspace_command.workspace_id()
+            || view.get_wc_commit_id(&workspace_id).is_none()
+        {
+            writeln!(
+                ui.warning_default(),
+                "Workspace {} at {path} is not tracked in the repo",
+                workspace_id.as_str()
+            )?;
+            continue;
+        }
+        if let Ok(loader) = command.new_workspace_loader_at(&workspace_root) {
+            if Some(loader.repo_path()) != repo_dir.as_deref() {
+                writeln!(
+                    ui.warning_default(),
+                    "Workspace {} at {path} belongs to another repo",
+                    workspace_id.as_str()
+                )?;
+                continue;
+            }
+        }
+        Workspace::repoint_workspace(&workspace_root, repo_path)?;
+        workspace_store.add(&workspace_id, &workspace_root)?;
+        repaired.push((workspace_id, workspace_root));
+    }
+
+    for workspace_id in view.wc_commit_ids().keys() {
+        if workspace_id == workspace_command.workspace_id()
+            || repaired.iter().any(|(id, _)| id == workspace_id)
+        {
+            continue;
+        }
+        let Some(workspace_root) = workspace_store.get_workspace_path(workspace_id)? else {
+            continue;
+        };
+        // Leave workspaces that can still find a repo alone. They may have
+        // been replaced by workspaces of another repo.
+        if !workspace_root.join(".jj").join("repo").is_file()
+            || command.new_workspace_loader_at(&workspace_root).is_ok()
+        {
+            continue;
+        }
+        Workspace::repoint_workspace(&workspace_root, repo_path)?;
+        repaired.push((workspace_id.clone(), workspace_root));
+    }
+
+    if repaired.is_empty() {
+        writeln!(ui.status(), "Nothing changed.")?;
+        return Ok(());
+    }
+    if let Some(mut formatter) = ui.status_formatter() {
+        for (workspace_id, workspace_root) in &repaired {
+            writeln!(
+                formatter,
+                "Reattached workspace {} at {}",
+                workspace_id.as_str(),
+                workspace_root.display()
+            )?;
+        }
+    }
+    Ok(())
+}
@@ -29,17 +29,21 @@ use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::TreeValue;
 use jj_lib::commit::Commit;
+use jj_lib::conflicts::extract_as_single_hunk;
 use jj_lib::conflicts::ConflictMarkerStyle;
 use jj_lib::copies::CopiesTreeDiffEntry;
 use jj_lib::copies::CopiesTreeDiffEntryPath;
 use jj_lib::copies::CopyRecords;
 use jj_lib::extensions_map::ExtensionsMap;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
 use jj_lib::fileset;
 use jj_lib::fileset::FilesetDiagnostics;
 use jj_lib::fileset::FilesetExpression;
 use jj_lib::id_prefix::IdPrefixContext;
 use jj_lib::id_prefix::IdPrefixIndex;
 use jj_lib::matchers::Matcher;
+use jj_lib::merge::Merge;
 use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::object_id::ObjectId as _;
@@ -303,6 +307,27 @@ impl<'repo> TemplateLanguage<'repo> for CommitTemplateLanguage<'repo> {
                 let build = template_parser::lookup_method(type_name, table, function)?;
                 build(self, diagnostics, build_ctx, property, function)
             }
+            CommitTemplatePropertyKind::ConflictEntry(property) => {
+                let table = &self.build_fn_table.conflict_entry_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::ConflictSide(property) => {
+                let table = &self.build_fn_table.conflict_side_methods;
+                let build = template_parser::lookup_method(type_name, table, function)?;
+                build(self, diagnostics, build_ctx, property, function)
+            }
+            CommitTemplatePropertyKind::ConflictSideList(property) => {
+                template_builder::build_unformattable_list_method(
+                    self,
+                    diagnostics,
+                    build_ctx,
+                    property,
+                    function,
+                    Self::wrap_conflict_side,
+                    Self::wrap_conflict_side_list,
+                )
+            }
             CommitTemplatePropertyKind::DiffStats(property) => {
                 let table = &self.build_fn_table.diff_stats_methods;
                 let build = template_parser::lookup_method(type_name, table, function)?;
@@ -437,6 +462,24 @@ impl<'repo> CommitTemplateLanguage<'repo> {
         CommitTemplatePropertyKind::TreeEntry(Box::new(property))
     }
 
+    pub fn wrap_conflict_entry(
+        property: impl TemplateProperty<Output = ConflictEntry> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::ConflictEntry(Box::new(property))
+    }
+
+    pub fn wrap_conflict_side(
+        property: impl TemplateProperty<Output = ConflictSide> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::ConflictSide(Box::new(property))
+    }
+
+    pub fn wrap_conflict_side_list(
+        property: impl TemplateProperty<Output = Vec<ConflictSide>> + 'repo,
+    ) -> CommitTemplatePropertyKind<'repo> {
+        CommitTemplatePropertyKind::ConflictSideList(Box::new(property))
+    }
+
     pub fn wrap_diff_stats(
         property: impl TemplateProperty<Output = DiffStatsFormatted<'repo>> + 'repo,
     ) -> CommitTemplatePropertyKind<'repo> {
@@ -472,6 +515,9 @@ pub enum CommitTemplatePropertyKind<'repo> {
     TreeDiffEntry(Box<dyn TemplateProperty<Output = TreeDiffEntry> + 'repo>),
     TreeDiffEntryList(Box<dyn TemplateProperty<Output = Vec<TreeDiffEntry>> + 'repo>),
     TreeEntry(Box<dyn TemplateProperty<Output = TreeEntry> + 'repo>),
+    ConflictEntry(Box<dyn TemplateProperty<Output = ConflictEntry> + 'repo>),
+    ConflictSide(Box<dyn TemplateProperty<Output = ConflictSide> + 'repo>),
+    ConflictSideList(Box<dyn TemplateProperty<Output = Vec<ConflictSide>> + 'repo>),
     DiffStats(Box<dyn TemplateProperty<Output = DiffStatsFormatted<'repo>> + 'repo>),
     CryptographicSignatureOpt(
         Box<dyn TemplateProperty<Output = Option<CryptographicSignature>> + 'repo>,
@@ -497,6 +543,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::TreeDiffEntry(_) => "TreeDiffEntry",
             CommitTemplatePropertyKind::TreeDiffEntryList(_) => "List<TreeDiffEntry>",
             CommitTemplatePropertyKind::TreeEntry(_) => "TreeEntry",
+            CommitTemplatePropertyKind::ConflictEntry(_) => "ConflictEntry",
+            CommitTemplatePropertyKind::ConflictSide(_) => "ConflictSide",
+            CommitTemplatePropertyKind::ConflictSideList(_) => "List<ConflictSide>",
             CommitTemplatePropertyKind::DiffStats(_) => "DiffStats",
             CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => {
                 "Option<CryptographicSignature>"
@@ -536,6 +585,11 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
                 Some(Box::new(property.map(|l| !l.is_empty())))
             }
             CommitTemplatePropertyKind::TreeEntry(_) => None,
+            CommitTemplatePropertyKind::ConflictEntry(_) => None,
+            CommitTemplatePropertyKind::ConflictSide(_) => None,
+            CommitTemplatePropertyKind::ConflictSideList(property) => {
+                Some(Box::new(property.map(|l| !l.is_empty())))
+            }
             CommitTemplatePropertyKind::DiffStats(_) => None,
             CommitTemplatePropertyKind::CryptographicSignatureOpt(property) => {
                 Some(Box::new(property.map(|sig| sig.is_some())))
@@ -582,6 +636,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             CommitTemplatePropertyKind::TreeDiffEntry(_) => None,
             CommitTemplatePropertyKind::TreeDiffEntryList(_) => None,
             CommitTemplatePropertyKind::TreeEntry(_) => None,
+            CommitTemplatePropertyKind::ConflictEntry(_) => None,
+            CommitTemplatePropertyKind::ConflictSide(_) => None,
+            CommitTemplatePropertyKind::ConflictSideList(_) => None,
             CommitTemplatePropertyKind::DiffStats(property) => Some(property.into_template()),
             CommitTemplatePropertyKind::CryptographicSignatureOpt(_) => None,
             CommitTemplatePropertyKind::AnnotationLine(_) => None,
@@ -608,6 +665,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::TreeDiffEntry(_), _) => None,
             (CommitTemplatePropertyKind::TreeDiffEntryList(_), _) => None,
             (CommitTemplatePropertyKind::TreeEntry(_), _) => None,
+            (CommitTemplatePropertyKind::ConflictEntry(_), _) => None,
+            (CommitTemplatePropertyKind::ConflictSide(_), _) => None,
+            (CommitTemplatePropertyKind::ConflictSideList(_), _) => None,
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
@@ -637,6 +697,9 @@ impl<'repo> IntoTemplateProperty<'repo> for CommitTemplatePropertyKind<'repo> {
             (CommitTemplatePropertyKind::TreeDiffEntry(_), _) => None,
             (CommitTemplatePropertyKind::TreeDiffEntryList(_), _) => None,
             (CommitTemplatePropertyKind::TreeEntry(_), _) => None,
+            (CommitTemplatePropertyKind::ConflictEntry(_), _) => None,
+            (CommitTemplatePropertyKind::ConflictSide(_), _) => None,
+            (CommitTemplatePropertyKind::ConflictSideList(_), _) => None,
             (CommitTemplatePropertyKind::DiffStats(_), _) => None,
             (CommitTemplatePropertyKind::CryptographicSignatureOpt(_), _) => None,
             (CommitTemplatePropertyKind::AnnotationLine(_), _) => None,
@@ -659,6 +722,8 @@ pub struct CommitTemplateBuildFnTable<'repo> {
     pub tree_diff_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiff>,
     pub tree_diff_entry_methods: CommitTemplateBuildMethodFnMap<'repo, TreeDiffEntry>,
     pub tree_entry_methods: CommitTemplateBuildMethodFnMap<'repo, TreeEntry>,
+    pub conflict_entry_methods: CommitTemplateBuildMethodFnMap<'repo, ConflictEntry>,
+    pub conflict_side_methods: CommitTemplateBuildMethodFnMap<'repo, ConflictSide>,
     pub diff_stats_methods: CommitTemplateBuildMethodFnMap<'repo, DiffStats>,
     pub cryptographic_signature_methods:
         CommitTemplateBuildMethodFnMap<'repo, CryptographicSignature>,
@@ -678,6 +743,8 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             tree_diff_methods: builtin_tree_diff_methods(),
             tree_diff_entry_methods: builtin_tree_diff_entry_methods(),
             tree_entry_methods: builtin_tree_entry_methods(),
+            conflict_entry_methods: builtin_conflict_entry_methods(),
+            conflict_side_methods: builtin_conflict_side_methods(),
             diff_stats_methods: builtin_diff_stats_methods(),
             cryptographic_signature_methods: builtin_cryptographic_signature_methods(),
             annotation_line_methods: builtin_annotation_line_methods(),
@@ -695,6 +762,8 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             tree_diff_methods: HashMap::new(),
             tree_diff_entry_methods: HashMap::new(),
             tree_entry_methods: HashMap::new(),
+            conflict_entry_methods: HashMap::new(),
+            conflict_side_methods: HashMap::new(),
             diff_stats_methods: HashMap::new(),
             cryptographic_signature_methods: HashMap::new(),
             annotation_line_methods: HashMap::new(),
@@ -712,6 +781,8 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
             tree_diff_methods,
             tree_diff_entry_methods,
             tree_entry_methods,
+            conflict_entry_methods,
+            conflict_side_methods,
             diff_stats_methods,
             cryptographic_signature_methods,
             annotation_line_methods,
@@ -732,6 +803,8 @@ impl<'repo> CommitTemplateBuildFnTable<'repo> {
         merge_fn_map(&mut self.tree_diff_methods, tree_diff_methods);
         merge_fn_map(&mut self.tree_diff_entry_methods, tree_diff_entry_methods);
         merge_fn_map(&mut self.tree_entry_methods, tree_entry_methods);
+        merge_fn_map(&mut self.conflict_entry_methods, conflict_entry_methods);
+        merge_fn_map(&mut self.conflict_side_methods, conflict_side_methods);
         merge_fn_map(&mut self.diff_stats_methods, diff_stats_methods);
         merge_fn_map(
             &mut self.cryptographic_signature_methods,
@@ -2083,6 +2156,165 @@ fn builtin_tree_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo,
     map
 }
 
+/// Conflicted [`MergedTree`] entry.
+#[derive(Clone, Debug)]
+pub struct ConflictEntry {
+    pub path: RepoPathBuf,
+    pub value: MergedTreeValue,
+    pub sides: Vec<ConflictSide>,
+}
+
+impl ConflictEntry {
+    /// Creates an entry for the conflicted `value`, attributing each side to
+    /// the first of the `parents` which has the same value at the path.
+    pub fn new(
+        path: RepoPathBuf,
+        value: MergedTreeValue,
+        parents: &[(Commit, MergedTree)],
+    ) -> BackendResult<Self> {
+        let value = value.simplify();
+        let parent_values: Vec<_> = parents
+            .iter()
+            .map(|(commit, tree)| tree.path_value(&path).map(|value| (commit, value)))
+            .try_collect()?;
+        let sides = value
+            .adds()
+            .map(|term| {
+                let commit = parent_values
+                    .iter()
+                    .find(|(_, parent_value)| parent_value.as_resolved() == Some(term))
+                    .map(|(commit, _)| (*commit).clone());
+                ConflictSide {
+                    value: term.clone(),
+                    commit,
+                }
+            })
+            .collect();
+        Ok(ConflictEntry { path, value, sides })
+    }
+
+    fn kind(&self) -> &'static str {
+        match self.value.to_file_merge() {
+            None => "type",
+            Some(file_merge) if file_merge.clone().simplify().is_resolved() => "executable",
+            Some(_) => "content",
+        }
+    }
+
+    fn num_hunks(&self, store: &Store) -> BackendResult<usize> {
+        let Some(file_merge) = self.value.to_file_merge() else {
+            return Ok(0);
+        };
+        let content =
+            extract_as_single_hunk(&file_merge.simplify(), store, &self.path).block_on()?;
+        let num_hunks = match files::merge(&content) {
+            MergeResult::Resolved(_) => 0,
+            MergeResult::Conflict(hunks) => hunks.iter().filter(|hunk| !hunk.is_resolved()).count(),
+        };
+        Ok(num_hunks)
+    }
+}
+
+fn builtin_conflict_entry_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, ConflictEntry> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<ConflictEntry>::new();
+    map.insert(
+        "path",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|entry| entry.path);
+            Ok(L::wrap_repo_path(out_property))
+        },
+    );
+    map.insert(
+        "num_sides",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property =
+                self_property.and_then(|entry| Ok(i64::try_from(entry.value.num_sides())?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "kind",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|entry| entry.kind().to_owned());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "num_hunks",
+        |language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let store = language.repo.store().clone();
+            let out_property =
+                self_property.and_then(move |entry| Ok(i64::try_from(entry.num_hunks(&store)?)?));
+            Ok(L::wrap_integer(out_property))
+        },
+    );
+    map.insert(
+        "sides",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|entry| entry.sides);
+            Ok(L::wrap_conflict_side_list(out_property))
+        },
+    );
+    map
+}
+
+/// Side of a [`ConflictEntry`].
+#[derive(Clone, Debug)]
+pub struct ConflictSide {
+    pub value: Option<TreeValue>,
+    /// Parent commit which has this side at the conflicted path, if any.
+    pub commit: Option<Commit>,
+}
+
+fn builtin_conflict_side_methods<'repo>() -> CommitTemplateBuildMethodFnMap<'repo, ConflictSide> {
+    type L<'repo> = CommitTemplateLanguage<'repo>;
+    // Not using maplit::hashmap!{} or custom declarative macro here because
+    // code completion inside macro is quite restricted.
+    let mut map = CommitTemplateBuildMethodFnMap::<ConflictSide>::new();
+    map.insert(
+        "file_type",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property
+                .map(|side| describe_file_type(&Merge::resolved(side.value)).to_owned());
+            Ok(L::wrap_string(out_property))
+        },
+    );
+    map.insert(
+        "executable",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|side| {
+                matches!(
+                    side.value,
+                    Some(TreeValue::File {
+                        executable: true,
+                        ..
+                    })
+                )
+            });
+            Ok(L::wrap_boolean(out_property))
+        },
+    );
+    map.insert(
+        "commit",
+        |_language, _diagnostics, _build_ctx, self_property, function| {
+            function.expect_no_arguments()?;
+            let out_property = self_property.map(|side| side.commit);
+            Ok(L::wrap_commit_opt(out_property))
+        },
+    );
+    map
+}
+
 fn describe_file_type(value: &MergedTreeValue) -> &'static str {
     match value.as_resolved() {
         Some(Some(TreeValue::File { .. })) => "file",
@@ -823,6 +823,7 @@ fn get_jj_command() -> Result<(JjBuilder, UserSettings), CommandError> {
     let _ = config_env.reload_user_config(&mut raw_config);
     if let Ok(loader) = &maybe_cwd_workspace_loader {
         config_env.reset_repo_path(loader.repo_path());
+        config_env.reset_workspace_id(loader.workspace_id().as_ref());
         let _ = config_env.reload_repo_config(&mut raw_config);
     }
     let mut config = config_env.resolve_config(&raw_config)?;
@@ -841,6 +842,7 @@ fn get_jj_command() -> Result<(JjBuilder, UserSettings), CommandError> {
         // Try to update repo-specific config on a best-effort basis.
         if let Ok(loader) = DefaultWorkspaceLoaderFactory.create(&cwd.join(&repository)) {
             config_env.reset_repo_path(loader.repo_path());
+            config_env.reset_workspace_id(loader.workspace_id().as_ref());
             let _ = config_env.reload_repo_config(&mut raw_config);
             if let Ok(new_config) = config_env.resolve_config(&raw_config) {
                 config = new_config;
@@ -33,6 +33,7 @@ use jj_lib::config::ConfigResolutionContext;
 use jj_lib::config::ConfigSource;
 use jj_lib::config::ConfigValue;
 use jj_lib::config::StackedConfig;
+use jj_lib::op_store::WorkspaceId;
 use regex::Captures;
 use regex::Regex;
 use tracing::instrument;
@@ -251,9 +252,12 @@ impl UnresolvedConfigEnv {
 pub struct ConfigEnv {
     home_dir: Option<PathBuf>,
     repo_path: Option<PathBuf>,
+    workspace_name: Option<String>,
     user_config_paths: Vec<ConfigPath>,
     repo_config_path: Option<ConfigPath>,
     command: Option<String>,
+    hostname: String,
+    environment: HashMap<String, String>,
 }
 
 impl ConfigEnv {
@@ -267,12 +271,22 @@ impl ConfigEnv {
             home_dir: home_dir.clone(),
             jj_config: env::var("JJ_CONFIG").ok(),
         };
+        let hostname = whoami::fallible::hostname()
+            .inspect_err(|err| tracing::warn!(?err, "failed to get hostname"))
+            .unwrap_or_default();
         ConfigEnv {
             home_dir,
             repo_path: None,
+            workspace_name: None,
             user_config_paths: env.resolve(),
             repo_config_path: None,
             command: None,
+            hostname,
+            environment: env::vars_os()
+                .filter_map(|(name, value)| {
+                    Some((name.into_string().ok()?, value.into_string().ok()?))
+                })
+                .collect(),
         }
     }
 
@@ -345,6 +359,12 @@ impl ConfigEnv {
         self.repo_config_path = Some(ConfigPath::new(path.join("config.toml")));
     }
 
+    /// Sets the workspace name, which is used to resolve `--when.workspaces`
+    /// conditions.
+    pub fn reset_workspace_id(&mut self, workspace_id: Option<&WorkspaceId>) {
+        self.workspace_name = workspace_id.map(|id| id.as_str().to_owned());
+    }
+
     /// Returns a path to the repo-specific config file.
     pub fn repo_config_path(&self) -> Option<&Path> {
         self.repo_config_path.as_ref().map(ConfigPath::as_path)
@@ -396,7 +416,10 @@ impl ConfigEnv {
         let context = ConfigResolutionContext {
             home_dir: self.home_dir.as_deref(),
             repo_path: self.repo_path.as_deref(),
+            workspace_name: self.workspace_name.as_deref(),
             command: self.command.as_deref(),
+            hostname: &self.hostname,
+            environment: &self.environment,
         };
         jj_lib::config::resolve(config.as_ref(), &context)
     }
@@ -1559,9 +1582,12 @@ mod tests {
         ConfigEnv {
             home_dir,
             repo_path: None,
+            workspace_name: None,
             user_config_paths: env.resolve(),
             repo_config_path: None,
             command: None,
+            hostname: String::new(),
+            environment: HashMap::new(),
         }
     }
 }
@@ -0,0 +1,512 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Full-screen log browser for `jj log --interactive`.
+//!
+//! The graph is rendered by the regular `jj log` code and displayed as is.
+//! Actions are run as ordinary subcommands, after which the graph is rendered
+//! again.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::io;
+use std::io::IsTerminal as _;
+use std::io::Write;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crossterm::cursor;
+use crossterm::event;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use crossterm::execute;
+use crossterm::queue;
+use crossterm::style::Attribute;
+use crossterm::style::SetAttribute;
+use crossterm::terminal;
+use crossterm::terminal::Clear;
+use crossterm::terminal::ClearType;
+use itertools::Itertools as _;
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId as _;
+
+use crate::cli_util::CommandHelper;
+use crate::command_error::user_error;
+use crate::command_error::CommandError;
+use crate::commands::run_subcommand;
+use crate::ui::Ui;
+
+const KEY_HINTS: &str = "j/k: move  enter: diff  e: edit  n: new  d: describe  s: squash  a: \
+                         abandon  r: rebase  u: undo  ?: help  q: quit";
+
+const HELP: &str = "e: jj edit  n: jj new  d: jj describe  s: jj squash -r  a: jj abandon  r: \
+                    mark revision, then r on the destination: jj rebase -s  u: jj undo";
+
+/// Writer which can be inspected while it's borrowed by the graph renderer.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl SharedBuffer {
+    pub fn len(&self) -> usize {
+        self.0.borrow().len()
+    }
+
+    pub fn take(&self) -> Vec<u8> {
+        self.0.take()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Rendered log graph split into lines.
+#[derive(Clone, Debug)]
+pub(crate) struct LogView {
+    lines: Vec<String>,
+    /// Commits in graph order, with the lines rendered for them.
+    nodes: Vec<(CommitId, Range<usize>)>,
+}
+
+impl LogView {
+    /// Creates view of the graph `output`. The `node_starts` are the byte
+    /// offsets in the `output` where the nodes start, with `None` for elided
+    /// nodes.
+    pub fn new(output: &[u8], node_starts: &[(Option<CommitId>, usize)]) -> Self {
+        let text = String::from_utf8_lossy(output);
+        let lines = text.lines().map(ToOwned::to_owned).collect_vec();
+        let line_at = |offset: usize| output[..offset].iter().filter(|&&b| b == b'\n').count();
+        let starts = node_starts
+            .iter()
+            .map(|(commit_id, offset)| (commit_id, line_at(*offset)))
+            .collect_vec();
+        let nodes = starts
+            .iter()
+            .enumerate()
+            .filter_map(|(i, (commit_id, start))| {
+                let end = starts.get(i + 1).map_or(lines.len(), |(_, end)| *end);
+                Some(((*commit_id).clone()?, *start..end))
+            })
+            .collect();
+        LogView { lines, nodes }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum LogUiAction {
+    /// Draw the screen again.
+    Redraw,
+    /// Render the graph again.
+    Refresh,
+    /// Run the subcommand with the given arguments.
+    Run(Vec<String>),
+    Quit,
+}
+
+#[derive(Debug)]
+struct LogUiState {
+    view: LogView,
+    /// Index of the selected node.
+    selected: usize,
+    /// First visible line.
+    scroll: usize,
+    /// Commits whose diffs are shown.
+    expanded: HashSet<CommitId>,
+    /// Commit to be rebased onto the next selected commit.
+    rebase_source: Option<CommitId>,
+    message: Option<String>,
+}
+
+impl LogUiState {
+    fn new(view: LogView) -> Self {
+        LogUiState {
+            view,
+            selected: 0,
+            scroll: 0,
+            expanded: HashSet::new(),
+            rebase_source: None,
+            message: None,
+        }
+    }
+
+    fn selected_id(&self) -> Option<&CommitId> {
+        self.view.nodes.get(self.selected).map(|(id, _)| id)
+    }
+
+    /// Replaces the view, keeping the selected commit if it's still there.
+    fn set_view(&mut self, view: LogView) {
+        let selected_id = self.selected_id().cloned();
+        self.view = view;
+        self.selected = selected_id
+            .and_then(|id| self.view.nodes.iter().position(|(other, _)| *other == id))
+            .unwrap_or_else(|| self.selected.min(self.view.nodes.len().saturating_sub(1)));
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, page_height: usize) -> LogUiAction {
+        self.message = None;
+        let last = self.view.nodes.len().saturating_sub(1);
+        let selected_hex = || self.selected_id().map(|id| id.hex());
+        let run = |args: &[&str], id: Option<String>| match id {
+            Some(id) => {
+                LogUiAction::Run(args.iter().map(|&arg| arg.to_owned()).chain([id]).collect())
+            }
+            None => LogUiAction::Redraw,
+        };
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                LogUiAction::Quit
+            }
+            KeyCode::Char('q') => LogUiAction::Quit,
+            KeyCode::Esc => {
+                if self.rebase_source.take().is_some() {
+                    LogUiAction::Redraw
+                } else {
+                    LogUiAction::Quit
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.selected = (self.selected + 1).min(last);
+                LogUiAction::Redraw
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+                LogUiAction::Redraw
+            }
+            KeyCode::PageDown => {
+                self.select_line(self.scroll + page_height, page_height);
+                LogUiAction::Redraw
+            }
+            KeyCode::PageUp => {
+                self.select_line(self.scroll.saturating_sub(page_height), page_height);
+                LogUiAction::Redraw
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.selected = 0;
+                LogUiAction::Redraw
+            }
+            KeyCode::Char('G') | KeyCode::End => {
+                self.selected = last;
+                LogUiAction::Redraw
+            }
+            KeyCode::Char('?') => {
+                self.message = Some(HELP.to_owned());
+                LogUiAction::Redraw
+            }
+            KeyCode::Enter | KeyCode::Char('r') if self.rebase_source.is_some() => {
+                let source = self.rebase_source.take().unwrap();
+                match self.selected_id() {
+                    Some(destination) if *destination != source => LogUiAction::Run(vec![
+                        "rebase".to_owned(),
+                        "-s".to_owned(),
+                        source.hex(),
+                        "-d".to_owned(),
+                        destination.hex(),
+                    ]),
+                    _ => LogUiAction::Redraw,
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                let Some(id) = self.selected_id().cloned() else {
+                    return LogUiAction::Redraw;
+                };
+                if !self.expanded.remove(&id) {
+                    self.expanded.insert(id);
+                }
+                LogUiAction::Refresh
+            }
+            KeyCode::Char('r') => {
+                self.rebase_source = self.selected_id().cloned();
+                if self.rebase_source.is_some() {
+                    self.message = Some(
+                        "Select the destination and press r or enter to rebase, or esc to cancel"
+                            .to_owned(),
+                    );
+                }
+                LogUiAction::Redraw
+            }
+            KeyCode::Char('e') => run(&["edit"], selected_hex()),
+            KeyCode::Char('n') => run(&["new"], selected_hex()),
+            KeyCode::Char('d') => run(&["describe"], selected_hex()),
+            KeyCode::Char('s') => run(&["squash", "-r"], selected_hex()),
+            KeyCode::Char('a') => run(&["abandon"], selected_hex()),
+            KeyCode::Char('u') => LogUiAction::Run(vec!["undo".to_owned()]),
+            _ => LogUiAction::Redraw,
+        }
+    }
+
+    /// Selects the node at or after the `line`.
+    fn select_line(&mut self, line: usize, page_height: usize) {
+        let last = self.view.nodes.len().saturating_sub(1);
+        self.selected = self
+            .view
+            .nodes
+            .iter()
+            .position(|(_, lines)| lines.end > line)
+            .unwrap_or(last);
+        self.scroll = line.min(self.view.lines.len().saturating_sub(page_height));
+    }
+
+    /// Scrolls so that as much as possible of the selected node is visible.
+    fn scroll_to_selected(&mut self, page_height: usize) {
+        let Some((_, lines)) = self.view.nodes.get(self.selected) else {
+            return;
+        };
+        if lines.start < self.scroll {
+            self.scroll = lines.start;
+        } else if lines.end > self.scroll + page_height {
+            self.scroll = lines.start.min(lines.end.saturating_sub(page_height));
+        }
+    }
+
+    fn draw(&self, output: &mut impl Write, height: usize) -> io::Result<()> {
+        let page_height = height.saturating_sub(1);
+        let selected_line = self.view.nodes.get(self.selected).map(|(_, l)| l.start);
+        let source_line = self.rebase_source.as_ref().and_then(|source| {
+            let (_, lines) = self.view.nodes.iter().find(|(id, _)| id == source)?;
+            Some(lines.start)
+        });
+        queue!(output, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        for line_index in self.scroll..self.scroll + page_height {
+            let Some(line) = self.view.lines.get(line_index) else {
+                write!(output, "\r\n")?;
+                continue;
+            };
+            let gutter = if Some(line_index) == selected_line {
+                '>'
+            } else if Some(line_index) == source_line {
+                '*'
+            } else {
+                ' '
+            };
+            write!(output, "{gutter} {line}")?;
+            queue!(output, SetAttribute(Attribute::Reset))?;
+            write!(output, "\r\n")?;
+        }
+        queue!(output, SetAttribute(Attribute::Reverse))?;
+        write!(output, "{}", self.message.as_deref().unwrap_or(KEY_HINTS))?;
+        queue!(output, Clear(ClearType::UntilNewLine))?;
+        queue!(output, SetAttribute(Attribute::Reset))?;
+        output.flush()
+    }
+}
+
+/// Puts the terminal into full-screen mode, and restores it when dropped.
+struct TerminalGuard {
+    active: bool,
+}
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        let mut guard = TerminalGuard { active: false };
+        guard.resume()?;
+        Ok(guard)
+    }
+
+    fn resume(&mut self) -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        self.active = true;
+        execute!(
+            io::stdout(),
+            terminal::EnterAlternateScreen,
+            terminal::DisableLineWrap,
+            cursor::Hide
+        )
+    }
+
+    fn suspend(&mut self) -> io::Result<()> {
+        self.active = false;
+        execute!(
+            io::stdout(),
+            cursor::Show,
+            terminal::EnableLineWrap,
+            terminal::LeaveAlternateScreen
+        )?;
+        terminal::disable_raw_mode()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.active {
+            self.suspend().ok();
+        }
+    }
+}
+
+/// Runs the log browser until the user quits. The `render` function is called
+/// to render the graph with the diffs of the given commits.
+pub(crate) fn run_log_ui(
+    ui: &mut Ui,
+    command: &CommandHelper,
+    mut render: impl FnMut(&Ui, &HashSet<CommitId>) -> Result<LogView, CommandError>,
+) -> Result<(), CommandError> {
+    if !io::stdout().is_terminal() {
+        return Err(user_error("Interactive log requires a terminal"));
+    }
+    let mut state = LogUiState::new(render(ui, &HashSet::new())?);
+    let mut terminal = TerminalGuard::enter()?;
+    loop {
+        let (_, height) = terminal::size()?;
+        let page_height = usize::from(height.saturating_sub(1)).max(1);
+        state.scroll_to_selected(page_height);
+        state.draw(&mut io::stdout().lock(), page_height + 1)?;
+        let action = match event::read()? {
+            Event::Key(key) if key.kind != KeyEventKind::Release => {
+                state.handle_key(key, page_height)
+            }
+            _ => continue,
+        };
+        match action {
+            LogUiAction::Redraw => {}
+            LogUiAction::Refresh => state.set_view(render(ui, &state.expanded)?),
+            LogUiAction::Run(args) => {
+                terminal.suspend()?;
+                let result =
+                    run_subcommand(ui, command, &args.iter().map(String::as_str).collect_vec());
+                terminal.resume()?;
+                state.message = Some(match result {
+                    Ok(()) => format!("Ran `jj {}`", args.join(" ")),
+                    Err(err) => format!("Error: {}", err.error),
+                });
+                state.set_view(render(ui, &state.expanded)?);
+            }
+            LogUiAction::Quit => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn test_view() -> LogView {
+        let output = b"@  a\n|  desc\n~  (elided revisions)\no  b\n|\no  c\n";
+        LogView::new(
+            output,
+            &[
+                (Some(CommitId::from_hex("aa")), 0),
+                (None, 13),
+                (Some(CommitId::from_hex("bb")), 35),
+                (Some(CommitId::from_hex("cc")), 42),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_log_view() {
+        let view = test_view();
+        assert_eq!(view.lines.len(), 6);
+        assert_eq!(
+            view.nodes,
+            vec![
+                (CommitId::from_hex("aa"), 0..2),
+                (CommitId::from_hex("bb"), 3..5),
+                (CommitId::from_hex("cc"), 5..6),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_navigate() {
+        let mut state = LogUiState::new(test_view());
+        assert_eq!(state.handle_key(key(KeyCode::Down), 3), LogUiAction::Redraw);
+        assert_eq!(state.selected_id(), Some(&CommitId::from_hex("bb")));
+        state.handle_key(key(KeyCode::Char('G')), 3);
+        state.handle_key(key(KeyCode::Char('j')), 3);
+        assert_eq!(state.selected_id(), Some(&CommitId::from_hex("cc")));
+        state.scroll_to_selected(3);
+        assert_eq!(state.scroll, 3);
+        state.handle_key(key(KeyCode::Char('g')), 3);
+        state.scroll_to_selected(3);
+        assert_eq!((state.selected, state.scroll), (0, 0));
+
+        // Selection follows the commit when the graph changes
+        state.handle_key(key(KeyCode::Char('j')), 3);
+        let view = LogView::new(
+            b"o  c\no  b\n",
+            &[
+                (Some(CommitId::from_hex("cc")), 0),
+                (Some(CommitId::from_hex("bb")), 5),
+            ],
+        );
+        state.set_view(view);
+        assert_eq!(state.selected, 1);
+
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('q')), 3),
+            LogUiAction::Quit
+        );
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut state = LogUiState::new(test_view());
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('e')), 3),
+            LogUiAction::Run(vec!["edit".to_owned(), "aa".to_owned()])
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('s')), 3),
+            LogUiAction::Run(vec!["squash".to_owned(), "-r".to_owned(), "aa".to_owned()])
+        );
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('u')), 3),
+            LogUiAction::Run(vec!["undo".to_owned()])
+        );
+
+        // Expand and collapse the diff
+        assert_eq!(
+            state.handle_key(key(KeyCode::Enter), 3),
+            LogUiAction::Refresh
+        );
+        assert!(state.expanded.contains(&CommitId::from_hex("aa")));
+        assert_eq!(
+            state.handle_key(key(KeyCode::Enter), 3),
+            LogUiAction::Refresh
+        );
+        assert!(state.expanded.is_empty());
+
+        // Rebase onto another commit
+        state.handle_key(key(KeyCode::Char('r')), 3);
+        state.handle_key(key(KeyCode::Down), 3);
+        assert_eq!(
+            state.handle_key(key(KeyCode::Char('r')), 3),
+            LogUiAction::Run(
+                ["rebase", "-s", "aa", "-d", "bb"]
+                    .map(ToOwned::to_owned)
+                    .to_vec()
+            )
+        );
+        assert_eq!(state.rebase_source, None);
+
+        // Rebase can be cancelled
+        state.handle_key(key(KeyCode::Char('r')), 3);
+        assert_eq!(state.handle_key(key(KeyCode::Esc), 3), LogUiAction::Redraw);
+        assert_eq!(state.rebase_source, None);
+        assert_eq!(state.handle_key(key(KeyCode::Esc), 3), LogUiAction::Quit);
+    }
+}
@@ -44,6 +44,10 @@ pub enum BuiltinToolError {
     Unimplemented { item: &'static str, id: String },
     #[error("Backend error")]
     BackendError(#[from] jj_lib::backend::BackendError),
+    #[error("Failed to set up the terminal")]
+    Terminal(#[source] std::io::Error),
+    #[error("Canceled by user")]
+    Canceled,
 }
 
 #[derive(Clone, Debug)]
@@ -572,6 +576,10 @@ fn make_merge_file(
 ) -> Result<scm_record::File<'static>, BuiltinToolError> {
     let merge_result = files::merge(&merge_tool_file.content);
     let sections = make_merge_sections(merge_result)?;
+    let executable = merge_tool_file
+        .conflict
+        .resolve_executable()
+        .unwrap_or(false);
     Ok(scm_record::File {
         old_path: None,
         // Path for displaying purposes, not for file access.
@@ -580,7 +588,11 @@ fn make_merge_file(
                 .repo_path
                 .to_fs_path_unchecked(Path::new("")),
         ),
-        file_mode: mode::NORMAL,
+        file_mode: if executable {
+            mode::EXECUTABLE
+        } else {
+            mode::NORMAL
+        },
         sections,
     })
 }
@@ -22,6 +22,7 @@ use jj_lib::store::Store;
 use jj_lib::working_copy::CheckoutError;
 use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::SnapshotOptions;
+use jj_lib::working_copy::SparsePatterns;
 use pollster::FutureExt as _;
 use tempfile::TempDir;
 use thiserror::Error;
@@ -91,7 +92,7 @@ fn check_out(
     std::fs::create_dir(&wc_dir).map_err(DiffCheckoutError::SetUpDir)?;
     std::fs::create_dir(&state_dir).map_err(DiffCheckoutError::SetUpDir)?;
     let mut tree_state = TreeState::init(store, wc_dir, state_dir)?;
-    tree_state.set_sparse_patterns(sparse_patterns, options)?;
+    tree_state.set_sparse_patterns(SparsePatterns::Prefixes(sparse_patterns), options)?;
     tree_state.check_out(tree, options)?;
     Ok(tree_state)
 }
@@ -17,6 +17,7 @@ use jj_lib::conflicts::MIN_CONFLICT_MARKER_LEN;
 use jj_lib::gitignore::GitIgnoreFile;
 use jj_lib::matchers::Matcher;
 use jj_lib::merge::Merge;
+use jj_lib::merge::MergedTreeValue;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::repo_path::RepoPathUiConverter;
@@ -62,6 +63,9 @@ pub struct ExternalMergeTool {
     /// `$left`, `$right`, `$base`, and `$output` are replaced with
     /// paths to the corresponding files.
     pub merge_args: Vec<String>,
+    /// Whether to execute the tool with directories containing all conflicted
+    /// files or with individual files when resolving conflicts.
+    pub merge_invocation_mode: DiffToolMode,
     /// By default, if a merge tool exits with a non-zero exit code, then the
     /// merge will be cancelled. Some merge tools allow leaving some conflicts
     /// unresolved, in which case they will be left as conflict markers in the
@@ -87,9 +91,11 @@ pub struct ExternalMergeTool {
 #[derive(serde::Deserialize, Copy, Clone, Debug, Eq, PartialEq)]
 #[serde(rename_all = "kebab-case")]
 pub enum DiffToolMode {
-    /// Invoke the diff tool on a temp directory of the modified files.
+    /// Invoke the tool on temp directories of the modified or conflicted
+    /// files.
     Dir,
-    /// Invoke the diff tool on each of the modified files individually.
+    /// Invoke the tool on each of the modified or conflicted files
+    /// individually.
     FileByFile,
 }
 
@@ -106,6 +112,7 @@ impl Default for ExternalMergeTool {
             diff_expected_exit_codes: vec![0],
             edit_args: ["$left", "$right"].map(ToOwned::to_owned).to_vec(),
             merge_args: vec![],
+            merge_invocation_mode: DiffToolMode::FileByFile,
             merge_conflict_exit_codes: vec![],
             merge_tool_edits_conflict_markers: false,
             conflict_marker_style: None,
@@ -181,10 +188,7 @@ fn run_mergetool_external_single_file(
     tree_builder: &mut MergedTreeBuilder,
 ) -> Result<(), ConflictResolveError> {
     let MergeToolFile {
-        repo_path,
-        conflict,
-        file_merge,
-        content,
+        repo_path, content, ..
     } = merge_tool_file;
 
     let conflict_marker_style = editor
@@ -249,8 +253,36 @@ fn run_mergetool_external_single_file(
         .try_collect()?;
     variables.insert("marker_length", conflict_marker_len.to_string());
 
+    let exit_status = run_merge_tool_command(editor, &variables)?;
+
+    let output_file_contents: Vec<u8> =
+        std::fs::read(variables.get("output").unwrap()).map_err(ExternalToolError::Io)?;
+    if output_file_contents.is_empty() || output_file_contents == initial_output_content {
+        return Err(ConflictResolveError::EmptyOrUnchanged);
+    }
+
+    let new_tree_value = read_merge_tool_output(
+        editor,
+        store,
+        merge_tool_file,
+        &output_file_contents,
+        exit_status,
+        conflict_marker_style,
+        conflict_marker_len,
+    )?;
+    tree_builder.set_or_remove(repo_path.to_owned(), new_tree_value);
+    Ok(())
+}
+
+/// Runs the merge tool with the `merge_args` interpolated with the
+/// `variables`. Fails unless the exit status indicates success or remaining
+/// conflicts.
+fn run_merge_tool_command(
+    editor: &ExternalMergeTool,
+    variables: &HashMap<&str, String>,
+) -> Result<ExitStatus, ConflictResolveError> {
     let mut cmd = Command::new(&editor.program);
-    cmd.args(interpolate_variables(&editor.merge_args, &variables));
+    cmd.args(interpolate_variables(&editor.merge_args, variables));
     tracing::info!(?cmd, "Invoking the external merge tool:");
     let exit_status = cmd
         .status()
@@ -269,26 +301,41 @@ fn run_mergetool_external_single_file(
             exit_status,
         }));
     }
+    Ok(exit_status)
+}
 
-    let output_file_contents: Vec<u8> =
-        std::fs::read(variables.get("output").unwrap()).map_err(ExternalToolError::Io)?;
-    if output_file_contents.is_empty() || output_file_contents == initial_output_content {
-        return Err(ConflictResolveError::EmptyOrUnchanged);
-    }
+/// Computes the new value of a conflicted file from the merge tool output.
+fn read_merge_tool_output(
+    editor: &ExternalMergeTool,
+    store: &Store,
+    merge_tool_file: &MergeToolFile,
+    output_file_contents: &[u8],
+    exit_status: ExitStatus,
+    conflict_marker_style: ConflictMarkerStyle,
+    conflict_marker_len: usize,
+) -> Result<MergedTreeValue, ConflictResolveError> {
+    let MergeToolFile {
+        repo_path,
+        file_merge,
+        ..
+    } = merge_tool_file;
+    let exit_status_implies_conflict = exit_status
+        .code()
+        .is_some_and(|code| editor.merge_conflict_exit_codes.contains(&code));
 
     let new_file_ids = if editor.merge_tool_edits_conflict_markers || exit_status_implies_conflict {
         conflicts::update_from_content(
             file_merge,
             store,
             repo_path,
-            output_file_contents.as_slice(),
+            output_file_contents,
             conflict_marker_style,
             conflict_marker_len,
         )
         .block_on()?
     } else {
         let new_file_id = store
-            .write_file(repo_path, &mut output_file_contents.as_slice())
+            .write_file(repo_path, &mut &*output_file_contents)
             .block_on()?;
         Merge::normal(new_file_id)
     };
@@ -302,15 +349,136 @@ fn run_mergetool_external_single_file(
             ExternalToolError::InvalidConflictMarkers { exit_status },
         ));
     }
-    // Update the file ids only, leaving the executable flags unchanged
-    let new_file_ids = if let Some(resolved) = new_file_ids.as_resolved() {
-        Merge::from_vec(vec![resolved.clone(); conflict.as_slice().len()])
+    Ok(merge_tool_file.new_tree_value(&new_file_ids))
+}
+
+/// Resolves all files with a single invocation of the merge tool, which gets
+/// directories containing the conflicted files. Files which the tool leaves
+/// empty or unchanged stay conflicted.
+fn run_mergetool_external_dir(
+    ui: &Ui,
+    path_converter: &RepoPathUiConverter,
+    editor: &ExternalMergeTool,
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+    default_conflict_marker_style: ConflictMarkerStyle,
+) -> Result<(MergedTreeId, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
+    let store = tree.store();
+    let conflict_marker_style = editor
+        .conflict_marker_style
+        .unwrap_or(default_conflict_marker_style);
+    let uses_marker_length = find_all_variables(&editor.merge_args).contains(&"marker_length");
+    // All files share the same "$marker_length", so use the longest markers
+    // needed by any of them.
+    let conflict_marker_len = if editor.merge_tool_edits_conflict_markers || uses_marker_length {
+        merge_tool_files
+            .iter()
+            .map(|file| choose_materialized_conflict_marker_len(&file.content))
+            .max()
+            .unwrap_or(MIN_CONFLICT_MARKER_LEN)
     } else {
-        new_file_ids
+        MIN_CONFLICT_MARKER_LEN
     };
-    let new_tree_value = conflict.with_new_file_ids(&new_file_ids);
-    tree_builder.set_or_remove(repo_path.to_owned(), new_tree_value);
-    Ok(())
+
+    let temp_dir = new_utf8_temp_dir("jj-resolve-").map_err(ExternalToolError::SetUpDir)?;
+    let roles = ["base", "left", "right", "output"];
+    for role in roles {
+        std::fs::create_dir(temp_dir.path().join(role)).map_err(ExternalToolError::SetUpDir)?;
+    }
+    let mut initial_output_contents = Vec::with_capacity(merge_tool_files.len());
+    for merge_tool_file in merge_tool_files {
+        let MergeToolFile {
+            repo_path, content, ..
+        } = merge_tool_file;
+        writeln!(
+            ui.status(),
+            "Resolving conflicts in: {}",
+            path_converter.format_file_path(repo_path)
+        )?;
+        let initial_output_content = if editor.merge_tool_edits_conflict_markers {
+            materialize_merge_result_to_bytes_with_marker_len(
+                content,
+                conflict_marker_style,
+                conflict_marker_len,
+            )
+        } else {
+            BString::default()
+        };
+        assert_eq!(content.num_sides(), 2);
+        let contents: [&[u8]; 4] = [
+            content.get_remove(0).unwrap(),
+            content.get_add(0).unwrap(),
+            content.get_add(1).unwrap(),
+            &initial_output_content,
+        ];
+        for (role, contents) in roles.iter().zip(contents) {
+            let path = repo_path.to_fs_path(&temp_dir.path().join(role))?;
+            std::fs::create_dir_all(path.parent().unwrap()).map_err(ExternalToolError::SetUpDir)?;
+            std::fs::write(&path, contents).map_err(ExternalToolError::SetUpDir)?;
+        }
+        initial_output_contents.push(initial_output_content);
+    }
+    for role in ["base", "left", "right"] {
+        // TODO: Should actually ignore the error here, or have a warning.
+        set_readonly_recursively(&temp_dir.path().join(role))
+            .map_err(ExternalToolError::SetUpDir)?;
+    }
+    let mut variables: HashMap<&str, String> = roles
+        .iter()
+        .map(|&role| {
+            let path = temp_dir.path().join(role);
+            let path = path.into_os_string().into_string();
+            (role, path.expect("temp_dir should be valid utf-8"))
+        })
+        .collect();
+    variables.insert("marker_length", conflict_marker_len.to_string());
+
+    let exit_status = run_merge_tool_command(editor, &variables)?;
+
+    let output_dir = temp_dir.path().join("output");
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    let mut resolved_count = 0;
+    let mut partial_resolution_error = None;
+    for (merge_tool_file, initial_output_content) in
+        merge_tool_files.iter().zip(&initial_output_contents)
+    {
+        let output_path = merge_tool_file.repo_path.to_fs_path(&output_dir)?;
+        let output_file_contents = match std::fs::read(&output_path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(ExternalToolError::Io(err).into()),
+        };
+        if output_file_contents.is_empty() || output_file_contents == *initial_output_content {
+            continue;
+        }
+        match read_merge_tool_output(
+            editor,
+            store,
+            merge_tool_file,
+            &output_file_contents,
+            exit_status,
+            conflict_marker_style,
+            conflict_marker_len,
+        ) {
+            Ok(new_tree_value) => {
+                tree_builder.set_or_remove(merge_tool_file.repo_path.clone(), new_tree_value);
+                resolved_count += 1;
+            }
+            Err(err) if resolved_count == 0 => return Err(err),
+            Err(err) => {
+                partial_resolution_error = Some(MergeToolPartialResolutionError {
+                    source: err,
+                    resolved_count,
+                });
+                break;
+            }
+        }
+    }
+    if resolved_count == 0 {
+        return Err(ConflictResolveError::EmptyOrUnchanged);
+    }
+    let new_tree = tree_builder.write_tree(store)?;
+    Ok((new_tree, partial_resolution_error))
 }
 
 pub fn run_mergetool_external(
@@ -321,8 +489,16 @@ pub fn run_mergetool_external(
     merge_tool_files: &[MergeToolFile],
     default_conflict_marker_style: ConflictMarkerStyle,
 ) -> Result<(MergedTreeId, Option<MergeToolPartialResolutionError>), ConflictResolveError> {
-    // TODO: add support for "dir" invocation mode, similar to the
-    // "diff-invocation-mode" config option for diffs
+    if editor.merge_invocation_mode == DiffToolMode::Dir {
+        return run_mergetool_external_dir(
+            ui,
+            path_converter,
+            editor,
+            tree,
+            merge_tool_files,
+            default_conflict_marker_style,
+        );
+    }
     let mut tree_builder = MergedTreeBuilder::new(tree.id());
     let mut partial_resolution_error = None;
     for (i, merge_tool_file) in merge_tool_files.iter().enumerate() {
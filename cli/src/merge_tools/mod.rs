@@ -15,6 +15,7 @@
 mod builtin;
 mod diff_working_copies;
 mod external;
+mod pane_editor;
 
 use std::sync::Arc;
 
@@ -52,6 +53,7 @@ pub use self::external::invoke_external_diff;
 pub use self::external::DiffToolMode;
 pub use self::external::ExternalMergeTool;
 use self::external::ExternalToolError;
+use self::pane_editor::edit_merge_panes;
 use crate::config::CommandNameAndArgs;
 use crate::ui::Ui;
 
@@ -302,13 +304,6 @@ impl MergeToolFile {
             ConflictResolveError::NotNormalFiles(repo_path.to_owned(), summary)
         })?;
         let simplified_file_merge = file_merge.clone().simplify();
-        // We only support conflicts with 2 sides (3-way conflicts)
-        if simplified_file_merge.num_sides() > 2 {
-            return Err(ConflictResolveError::ConflictTooComplicated {
-                path: repo_path.to_owned(),
-                sides: simplified_file_merge.num_sides(),
-            });
-        };
         let content =
             extract_as_single_hunk(&simplified_file_merge, tree.store(), repo_path).block_on()?;
         Ok(MergeToolFile {
@@ -318,12 +313,35 @@ impl MergeToolFile {
             content,
         })
     }
+
+    /// Returns the conflict with the file ids updated to the merge tool
+    /// output, leaving the executable flags unchanged.
+    fn new_tree_value(&self, new_file_ids: &Merge<Option<FileId>>) -> MergedTreeValue {
+        let new_file_ids = if let Some(resolved) = new_file_ids.as_resolved() {
+            Merge::from_vec(vec![resolved.clone(); self.conflict.as_slice().len()])
+        } else {
+            new_file_ids.clone()
+        };
+        self.conflict.with_new_file_ids(&new_file_ids)
+    }
+}
+
+/// How the builtin merge tool presents conflicts.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinMergeEditorMode {
+    /// Choose sides per hunk, like in the builtin diff editor.
+    #[default]
+    Hunks,
+    /// Show each side and base in its own pane, with an editable result.
+    Panes,
 }
 
 /// Configured 3-way merge editor.
 #[derive(Clone, Debug)]
 pub struct MergeEditor {
     tool: MergeTool,
+    builtin_mode: BuiltinMergeEditorMode,
     path_converter: RepoPathUiConverter,
     conflict_marker_style: ConflictMarkerStyle,
 }
@@ -339,7 +357,7 @@ impl MergeEditor {
     ) -> Result<Self, MergeToolConfigError> {
         let tool = get_tool_config(settings, name)?
             .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_program(name)));
-        Self::new_inner(name, tool, path_converter, conflict_marker_style)
+        Self::new_inner(name, tool, settings, path_converter, conflict_marker_style)
     }
 
     /// Loads the default 3-way merge editor from the settings.
@@ -356,12 +374,13 @@ impl MergeEditor {
             None
         }
         .unwrap_or_else(|| MergeTool::external(ExternalMergeTool::with_merge_args(&args)));
-        Self::new_inner(&args, tool, path_converter, conflict_marker_style)
+        Self::new_inner(&args, tool, settings, path_converter, conflict_marker_style)
     }
 
     fn new_inner(
         name: impl ToString,
         tool: MergeTool,
+        settings: &UserSettings,
         path_converter: RepoPathUiConverter,
         conflict_marker_style: ConflictMarkerStyle,
     ) -> Result<Self, MergeToolConfigError> {
@@ -372,6 +391,7 @@ impl MergeEditor {
         }
         Ok(MergeEditor {
             tool,
+            builtin_mode: settings.get("ui.builtin-merge-editor.mode")?,
             path_converter,
             conflict_marker_style,
         })
@@ -389,9 +409,31 @@ impl MergeEditor {
             .map(|&repo_path| MergeToolFile::from_tree_and_path(tree, repo_path))
             .try_collect()?;
 
+        if !matches!(
+            (&self.tool, self.builtin_mode),
+            (MergeTool::Builtin, BuiltinMergeEditorMode::Panes)
+        ) {
+            // Other tools only support conflicts with 2 sides (3-way conflicts)
+            if let Some(file) = merge_tool_files
+                .iter()
+                .find(|file| file.content.num_sides() > 2)
+            {
+                return Err(ConflictResolveError::ConflictTooComplicated {
+                    path: file.repo_path.clone(),
+                    sides: file.content.num_sides(),
+                });
+            }
+        }
+
         match &self.tool {
             MergeTool::Builtin => {
-                let tree_id = edit_merge_builtin(tree, &merge_tool_files).map_err(Box::new)?;
+                let tree_id = match self.builtin_mode {
+                    BuiltinMergeEditorMode::Hunks => edit_merge_builtin(tree, &merge_tool_files),
+                    BuiltinMergeEditorMode::Panes => {
+                        edit_merge_panes(tree, &merge_tool_files, self.conflict_marker_style)
+                    }
+                }
+                .map_err(Box::new)?;
                 Ok((tree_id, None))
             }
             MergeTool::External(editor) => external::run_mergetool_external(
@@ -456,6 +498,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -488,6 +531,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -532,6 +576,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -560,6 +605,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -587,6 +633,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -620,6 +667,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -651,6 +699,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -676,6 +725,7 @@ mod tests {
                     "$right",
                 ],
                 merge_args: [],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -737,6 +787,7 @@ mod tests {
                     "$right",
                     "$output",
                 ],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -793,6 +844,7 @@ mod tests {
                     "$right",
                     "$output",
                 ],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -826,6 +878,7 @@ mod tests {
                     "$right",
                     "$output",
                 ],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
@@ -862,6 +915,7 @@ mod tests {
                     "$right",
                     "$output",
                 ],
+                merge_invocation_mode: FileByFile,
                 merge_conflict_exit_codes: [],
                 merge_tool_edits_conflict_markers: false,
                 conflict_marker_style: None,
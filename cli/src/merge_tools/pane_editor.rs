@@ -0,0 +1,941 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merge editor of the builtin tool showing each term of a conflict in its own
+//! pane, with the result below them.
+//!
+//! Conflicts with any number of sides are supported. The result of a conflict
+//! hunk can be taken from any side or base, assembled line by line, and edited.
+//! Hunks which are left unresolved stay conflicted.
+
+use std::io;
+use std::io::Write;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use crossterm::cursor;
+use crossterm::event;
+use crossterm::event::Event;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use crossterm::execute;
+use crossterm::queue;
+use crossterm::style::Attribute;
+use crossterm::style::SetAttribute;
+use crossterm::terminal;
+use crossterm::terminal::Clear;
+use crossterm::terminal::ClearType;
+use itertools::Itertools as _;
+use jj_lib::backend::BackendResult;
+use jj_lib::backend::MergedTreeId;
+use jj_lib::conflicts;
+use jj_lib::conflicts::choose_materialized_conflict_marker_len;
+use jj_lib::conflicts::materialize_merge_result_to_bytes_with_marker_len;
+use jj_lib::conflicts::ConflictMarkerStyle;
+use jj_lib::files;
+use jj_lib::files::MergeResult;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTree;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use pollster::FutureExt as _;
+
+use super::builtin::BuiltinToolError;
+use super::MergeToolFile;
+use crate::text_util;
+
+const KEY_HINTS: &str = "n/p: next/prev conflict  tab: switch pane  1-9: take side  b: take \
+                         base  a: take all sides  enter: copy line  e/o/d: edit/add/delete line  \
+                         u: reset  w: save  q: quit";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Hunk {
+    Resolved(BString),
+    Conflict(ConflictHunk),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct ConflictHunk {
+    /// Lines of each term, ordered as in [`Merge`]: side, base, side, ...
+    terms: Merge<Vec<BString>>,
+    /// Lines of the result, or `None` if the hunk is unresolved.
+    result: Option<Vec<BString>>,
+}
+
+/// Conflict hunks of a file and their resolutions.
+#[derive(Clone, Debug)]
+struct FileState {
+    path: String,
+    hunks: Vec<Hunk>,
+    changed: bool,
+}
+
+impl FileState {
+    fn new(path: String, content: &Merge<BString>) -> Self {
+        let hunks = match files::merge(content) {
+            MergeResult::Resolved(text) => vec![Hunk::Resolved(text)],
+            MergeResult::Conflict(hunks) => hunks
+                .into_iter()
+                .map(|hunk| match hunk.into_resolved() {
+                    Ok(text) => Hunk::Resolved(text),
+                    Err(terms) => Hunk::Conflict(ConflictHunk {
+                        terms: terms.map(|text| split_lines(text)),
+                        result: None,
+                    }),
+                })
+                .collect(),
+        };
+        FileState {
+            path,
+            hunks,
+            changed: false,
+        }
+    }
+
+    /// Returns the content with the resolved hunks applied. The result is
+    /// resolved if all hunks are.
+    fn to_merge(&self, num_terms: usize) -> Merge<BString> {
+        let mut terms = vec![BString::default(); num_terms];
+        for hunk in &self.hunks {
+            match hunk {
+                Hunk::Resolved(text) => {
+                    for term in &mut terms {
+                        term.extend_from_slice(text);
+                    }
+                }
+                Hunk::Conflict(ConflictHunk {
+                    result: Some(lines),
+                    ..
+                }) => {
+                    for term in &mut terms {
+                        term.extend(lines.iter().flat_map(|line| line.iter()));
+                    }
+                }
+                Hunk::Conflict(ConflictHunk {
+                    terms: hunk_terms,
+                    result: None,
+                }) => {
+                    for (term, lines) in terms.iter_mut().zip(hunk_terms.iter()) {
+                        term.extend(lines.iter().flat_map(|line| line.iter()));
+                    }
+                }
+            }
+        }
+        Merge::from_vec(terms)
+    }
+}
+
+fn split_lines(text: &[u8]) -> Vec<BString> {
+    text.lines_with_terminator().map(BString::from).collect()
+}
+
+/// Which pane has the focus.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Focus {
+    Term(usize),
+    Result,
+}
+
+/// Line of the result being edited.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct LineEdit {
+    index: usize,
+    text: Vec<char>,
+    cursor: usize,
+    /// If the line was inserted for this edit, the cursor to restore when the
+    /// edit is cancelled.
+    inserted_at: Option<usize>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum PaneEditorAction {
+    Continue,
+    Save,
+    Quit,
+}
+
+#[derive(Debug)]
+struct PaneEditor {
+    files: Vec<FileState>,
+    /// File and hunk indices of the conflict hunks.
+    conflicts: Vec<(usize, usize)>,
+    current: usize,
+    focus: Focus,
+    /// Cursor line in each term pane, followed by the result pane.
+    cursors: Vec<usize>,
+    editing: Option<LineEdit>,
+    message: Option<String>,
+    confirm_quit: bool,
+}
+
+impl PaneEditor {
+    fn new(files: Vec<FileState>) -> Self {
+        let conflicts = files
+            .iter()
+            .enumerate()
+            .flat_map(|(file_index, file)| {
+                file.hunks
+                    .iter()
+                    .positions(|hunk| matches!(hunk, Hunk::Conflict(_)))
+                    .map(move |hunk_index| (file_index, hunk_index))
+            })
+            .collect();
+        let mut editor = PaneEditor {
+            files,
+            conflicts,
+            current: 0,
+            focus: Focus::Result,
+            cursors: vec![],
+            editing: None,
+            message: None,
+            confirm_quit: false,
+        };
+        editor.select(0);
+        editor
+    }
+
+    fn current_hunk(&self) -> Option<&ConflictHunk> {
+        let &(file_index, hunk_index) = self.conflicts.get(self.current)?;
+        match &self.files[file_index].hunks[hunk_index] {
+            Hunk::Conflict(hunk) => Some(hunk),
+            Hunk::Resolved(_) => None,
+        }
+    }
+
+    fn current_hunk_mut(&mut self) -> Option<&mut ConflictHunk> {
+        let &(file_index, hunk_index) = self.conflicts.get(self.current)?;
+        match &mut self.files[file_index].hunks[hunk_index] {
+            Hunk::Conflict(hunk) => Some(hunk),
+            Hunk::Resolved(_) => None,
+        }
+    }
+
+    fn num_terms(&self) -> usize {
+        self.current_hunk()
+            .map_or(0, |hunk| hunk.terms.as_slice().len())
+    }
+
+    fn result_len(&self) -> usize {
+        self.current_hunk()
+            .and_then(|hunk| hunk.result.as_ref())
+            .map_or(0, |lines| lines.len())
+    }
+
+    fn select(&mut self, index: usize) {
+        self.current = index;
+        self.cursors = vec![0; self.num_terms() + 1];
+        if let Focus::Term(i) = self.focus {
+            if i >= self.num_terms() {
+                self.focus = Focus::Result;
+            }
+        }
+    }
+
+    fn cursor(&self, focus: Focus) -> usize {
+        let index = match focus {
+            Focus::Term(i) => i,
+            Focus::Result => self.num_terms(),
+        };
+        self.cursors.get(index).copied().unwrap_or(0)
+    }
+
+    fn set_cursor(&mut self, focus: Focus, line: usize) {
+        let (index, len) = match focus {
+            Focus::Term(i) => (
+                i,
+                self.current_hunk()
+                    .map_or(0, |hunk| hunk.terms.as_slice()[i].len()),
+            ),
+            Focus::Result => (self.num_terms(), self.result_len()),
+        };
+        if let Some(cursor) = self.cursors.get_mut(index) {
+            *cursor = line.min(len.saturating_sub(1));
+        }
+    }
+
+    fn take_term(&mut self, index: usize) {
+        let Some(hunk) = self.current_hunk_mut() else {
+            return;
+        };
+        if let Some(lines) = hunk.terms.as_slice().get(index) {
+            hunk.result = Some(lines.clone());
+            self.set_cursor(Focus::Result, 0);
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) -> PaneEditorAction {
+        let position = self.conflicts.get(self.current).copied();
+        let old_result = self.current_hunk().map(|hunk| hunk.result.clone());
+        let action = if self.editing.is_some() {
+            self.handle_edit_key(key);
+            PaneEditorAction::Continue
+        } else {
+            self.handle_command_key(key)
+        };
+        // Only mark the file as changed if the key actually modified the hunk
+        if let Some((file_index, hunk_index)) = position {
+            let file = &mut self.files[file_index];
+            if let Hunk::Conflict(hunk) = &file.hunks[hunk_index] {
+                if old_result.as_ref() != Some(&hunk.result) {
+                    file.changed = true;
+                }
+            }
+        }
+        action
+    }
+
+    fn handle_command_key(&mut self, key: KeyEvent) -> PaneEditorAction {
+        self.message = None;
+        let confirm_quit = std::mem::take(&mut self.confirm_quit);
+        let num_terms = self.num_terms();
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return PaneEditorAction::Quit;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                if confirm_quit || !self.files.iter().any(|file| file.changed) {
+                    return PaneEditorAction::Quit;
+                }
+                self.confirm_quit = true;
+                self.message = Some("Press q again to discard the resolutions".to_owned());
+            }
+            KeyCode::Char('w') => return PaneEditorAction::Save,
+            KeyCode::Char('n') if self.current + 1 < self.conflicts.len() => {
+                self.select(self.current + 1);
+            }
+            KeyCode::Char('p') if self.current > 0 => {
+                self.select(self.current - 1);
+            }
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Term(i) if i + 1 < num_terms => Focus::Term(i + 1),
+                    Focus::Term(_) => Focus::Result,
+                    Focus::Result if num_terms > 0 => Focus::Term(0),
+                    Focus::Result => Focus::Result,
+                };
+            }
+            KeyCode::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Term(0) => Focus::Result,
+                    Focus::Term(i) => Focus::Term(i - 1),
+                    Focus::Result if num_terms > 0 => Focus::Term(num_terms - 1),
+                    Focus::Result => Focus::Result,
+                };
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                self.set_cursor(self.focus, self.cursor(self.focus) + 1);
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                self.set_cursor(self.focus, self.cursor(self.focus).saturating_sub(1));
+            }
+            KeyCode::Char(c @ '1'..='9') => {
+                // Sides are at the even indices
+                let side = c.to_digit(10).unwrap() as usize - 1;
+                self.take_term(side * 2);
+            }
+            KeyCode::Char('b') => {
+                let index = match self.focus {
+                    Focus::Term(i) if i % 2 == 1 => i,
+                    _ => 1,
+                };
+                self.take_term(index);
+            }
+            KeyCode::Char('a') => {
+                if let Some(hunk) = self.current_hunk_mut() {
+                    let lines = hunk.terms.adds().flatten().cloned().collect();
+                    hunk.result = Some(lines);
+                }
+            }
+            KeyCode::Char('u') => {
+                if let Some(hunk) = self.current_hunk_mut() {
+                    hunk.result = None;
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Focus::Term(i) = self.focus {
+                    let cursor = self.cursor(self.focus);
+                    let result_cursor = self.cursor(Focus::Result);
+                    if let Some(hunk) = self.current_hunk_mut() {
+                        if let Some(line) = hunk.terms.as_slice()[i].get(cursor).cloned() {
+                            let result = hunk.result.get_or_insert_with(Vec::new);
+                            let index = (result_cursor + 1).min(result.len());
+                            result.insert(index, line);
+                            self.set_cursor(Focus::Result, index);
+                            self.set_cursor(self.focus, cursor + 1);
+                        }
+                    }
+                }
+            }
+            KeyCode::Char('d') if self.focus == Focus::Result => {
+                let cursor = self.cursor(Focus::Result);
+                if let Some(result) = self.current_hunk_mut().and_then(|h| h.result.as_mut()) {
+                    if cursor < result.len() {
+                        result.remove(cursor);
+                    }
+                }
+                self.set_cursor(Focus::Result, cursor);
+            }
+            KeyCode::Char('e') if self.focus == Focus::Result => {
+                let index = self.cursor(Focus::Result);
+                let line = self
+                    .current_hunk()
+                    .and_then(|hunk| hunk.result.as_ref()?.get(index));
+                if let Some(line) = line {
+                    let text = line
+                        .trim_end_with(|c| c == '\n')
+                        .to_str_lossy()
+                        .chars()
+                        .collect_vec();
+                    self.editing = Some(LineEdit {
+                        index,
+                        cursor: text.len(),
+                        text,
+                        inserted_at: None,
+                    });
+                }
+            }
+            KeyCode::Char(c @ ('o' | 'O')) if self.focus == Focus::Result => {
+                let cursor = self.cursor(Focus::Result);
+                if let Some(hunk) = self.current_hunk_mut() {
+                    let result = hunk.result.get_or_insert_with(Vec::new);
+                    let index = if c == 'o' && !result.is_empty() {
+                        cursor + 1
+                    } else {
+                        cursor.min(result.len())
+                    };
+                    result.insert(index, BString::from("\n"));
+                    self.set_cursor(Focus::Result, index);
+                    self.editing = Some(LineEdit {
+                        index,
+                        text: vec![],
+                        cursor: 0,
+                        inserted_at: Some(cursor),
+                    });
+                }
+            }
+            _ => {}
+        }
+        PaneEditorAction::Continue
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) {
+        let Some(edit) = &mut self.editing else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                let edit = self.editing.take().unwrap();
+                let mut line: String = edit.text.into_iter().collect();
+                line.push('\n');
+                if let Some(result) = self.current_hunk_mut().and_then(|h| h.result.as_mut()) {
+                    result[edit.index] = line.into();
+                }
+            }
+            KeyCode::Esc => {
+                let edit = self.editing.take().unwrap();
+                if let Some(cursor) = edit.inserted_at {
+                    if let Some(result) = self.current_hunk_mut().and_then(|h| h.result.as_mut()) {
+                        result.remove(edit.index);
+                    }
+                    self.set_cursor(Focus::Result, cursor);
+                }
+            }
+            KeyCode::Backspace if edit.cursor > 0 => {
+                edit.cursor -= 1;
+                edit.text.remove(edit.cursor);
+            }
+            KeyCode::Delete if edit.cursor < edit.text.len() => {
+                edit.text.remove(edit.cursor);
+            }
+            KeyCode::Left => edit.cursor = edit.cursor.saturating_sub(1),
+            KeyCode::Right => edit.cursor = (edit.cursor + 1).min(edit.text.len()),
+            KeyCode::Home => edit.cursor = 0,
+            KeyCode::End => edit.cursor = edit.text.len(),
+            KeyCode::Char(c) => {
+                edit.text.insert(edit.cursor, c);
+                edit.cursor += 1;
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(&self, output: &mut impl Write, width: usize, height: usize) -> io::Result<()> {
+        queue!(output, Clear(ClearType::All), cursor::MoveTo(0, 0))?;
+        let Some(hunk) = self.current_hunk() else {
+            write!(output, "No conflicts to resolve")?;
+            return output.flush();
+        };
+        let (file_index, _) = self.conflicts[self.current];
+        let file = &self.files[file_index];
+        let num_resolved = self
+            .conflicts
+            .iter()
+            .filter(|&&(f, h)| {
+                matches!(
+                    &self.files[f].hunks[h],
+                    Hunk::Conflict(hunk) if hunk.result.is_some()
+                )
+            })
+            .count();
+        write!(
+            output,
+            "{} - conflict {} of {} ({} resolved)\r\n",
+            file.path,
+            self.current + 1,
+            self.conflicts.len(),
+            num_resolved
+        )?;
+
+        // The terms take the upper half of the screen, the result the lower.
+        let body_height = height.saturating_sub(2);
+        let terms_height = body_height / 2;
+        let result_height = body_height - terms_height;
+        let num_terms = hunk.terms.as_slice().len();
+        let pane_width = (width + 1) / num_terms.max(1);
+        let num_sides = hunk.terms.num_sides();
+        let titles = (0..num_terms)
+            .map(|i| match (i % 2, num_sides) {
+                (0, _) => format!("Side #{}", i / 2 + 1),
+                (_, 2) => "Base".to_owned(),
+                _ => format!("Base #{}", i / 2 + 1),
+            })
+            .collect_vec();
+        for row in 0..terms_height {
+            for (i, lines) in hunk.terms.iter().enumerate() {
+                if i > 0 {
+                    write!(output, "|")?;
+                }
+                let focus = Focus::Term(i);
+                let text = if row == 0 {
+                    Some(titles[i].as_str().into())
+                } else {
+                    let scroll = scroll_offset(self.cursor(focus), terms_height.saturating_sub(1));
+                    lines.get(scroll + row - 1).map(|line| display_line(line))
+                };
+                let selected = row > 0
+                    && self.focus == focus
+                    && scroll_offset(self.cursor(focus), terms_height.saturating_sub(1)) + row - 1
+                        == self.cursor(focus);
+                write_cell(
+                    output,
+                    text.as_deref(),
+                    pane_width.saturating_sub(1),
+                    row == 0 || selected,
+                )?;
+            }
+            write!(output, "\r\n")?;
+        }
+
+        let result_title = match &hunk.result {
+            Some(_) => "Result",
+            None => "Result (unresolved)",
+        };
+        write_cell(output, Some(result_title), width, true)?;
+        write!(output, "\r\n")?;
+        let result_lines = hunk.result.as_deref().unwrap_or_default();
+        let result_cursor = self.cursor(Focus::Result);
+        let scroll = scroll_offset(result_cursor, result_height.saturating_sub(1));
+        for row in 0..result_height.saturating_sub(1) {
+            let index = scroll + row;
+            let text = match &self.editing {
+                Some(edit) if edit.index == index => {
+                    let mut text: String = edit.text.iter().collect();
+                    text.insert(
+                        text.char_indices()
+                            .nth(edit.cursor)
+                            .map_or(text.len(), |(i, _)| i),
+                        '_',
+                    );
+                    Some(text)
+                }
+                _ => result_lines.get(index).map(|line| display_line(line)),
+            };
+            let selected = self.focus == Focus::Result && index == result_cursor;
+            write_cell(output, text.as_deref(), width, selected)?;
+            write!(output, "\r\n")?;
+        }
+        queue!(output, SetAttribute(Attribute::Reverse))?;
+        write!(output, "{}", self.message.as_deref().unwrap_or(KEY_HINTS))?;
+        queue!(
+            output,
+            Clear(ClearType::UntilNewLine),
+            SetAttribute(Attribute::Reset)
+        )?;
+        output.flush()
+    }
+}
+
+/// Returns the first visible line such that the `cursor` is visible.
+fn scroll_offset(cursor: usize, height: usize) -> usize {
+    (cursor + 1).saturating_sub(height.max(1))
+}
+
+fn display_line(line: &[u8]) -> String {
+    line.trim_end_with(|c| c == '\n')
+        .to_str_lossy()
+        .replace('\t', "    ")
+}
+
+fn write_cell(
+    output: &mut impl Write,
+    text: Option<&str>,
+    width: usize,
+    highlight: bool,
+) -> io::Result<()> {
+    let (text, text_width) = text_util::elide_end(text.unwrap_or(""), "", width);
+    if highlight {
+        queue!(output, SetAttribute(Attribute::Reverse))?;
+    }
+    write!(output, "{text}{:pad$}", "", pad = width - text_width)?;
+    queue!(output, SetAttribute(Attribute::Reset))
+}
+
+/// Writes the resolutions of the changed files to the tree.
+fn apply_resolutions(
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+    files: &[FileState],
+    conflict_marker_style: ConflictMarkerStyle,
+) -> BackendResult<MergedTreeId> {
+    let store = tree.store();
+    let mut tree_builder = MergedTreeBuilder::new(tree.id());
+    for (merge_tool_file, file) in merge_tool_files.iter().zip_eq(files) {
+        if !file.changed {
+            continue;
+        }
+        let content = file.to_merge(merge_tool_file.content.as_slice().len());
+        let conflict_marker_len = choose_materialized_conflict_marker_len(&content);
+        let new_content = materialize_merge_result_to_bytes_with_marker_len(
+            &content,
+            conflict_marker_style,
+            conflict_marker_len,
+        );
+        let new_file_ids = conflicts::update_from_content(
+            &merge_tool_file.file_merge,
+            store,
+            &merge_tool_file.repo_path,
+            &new_content,
+            conflict_marker_style,
+            conflict_marker_len,
+        )
+        .block_on()?;
+        tree_builder.set_or_remove(
+            merge_tool_file.repo_path.clone(),
+            merge_tool_file.new_tree_value(&new_file_ids),
+        );
+    }
+    tree_builder.write_tree(store)
+}
+
+/// Restores the terminal when dropped.
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let guard = TerminalGuard;
+        execute!(
+            io::stdout(),
+            terminal::EnterAlternateScreen,
+            terminal::DisableLineWrap,
+            cursor::Hide
+        )?;
+        Ok(guard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        execute!(
+            io::stdout(),
+            cursor::Show,
+            terminal::EnableLineWrap,
+            terminal::LeaveAlternateScreen
+        )
+        .ok();
+        terminal::disable_raw_mode().ok();
+    }
+}
+
+pub fn edit_merge_panes(
+    tree: &MergedTree,
+    merge_tool_files: &[MergeToolFile],
+    conflict_marker_style: ConflictMarkerStyle,
+) -> Result<MergedTreeId, BuiltinToolError> {
+    let files = merge_tool_files
+        .iter()
+        .map(|file| {
+            let path = file.repo_path.as_internal_file_string().to_owned();
+            FileState::new(path, &file.content)
+        })
+        .collect();
+    let mut editor = PaneEditor::new(files);
+    {
+        let _guard = TerminalGuard::enter().map_err(BuiltinToolError::Terminal)?;
+        loop {
+            let (width, height) = terminal::size().map_err(BuiltinToolError::Terminal)?;
+            editor
+                .draw(&mut io::stdout().lock(), width.into(), height.into())
+                .map_err(BuiltinToolError::Terminal)?;
+            let action = match event::read().map_err(BuiltinToolError::Terminal)? {
+                Event::Key(key) if key.kind != KeyEventKind::Release => editor.handle_key(key),
+                _ => continue,
+            };
+            match action {
+                PaneEditorAction::Continue => {}
+                PaneEditorAction::Save => break,
+                PaneEditorAction::Quit => return Err(BuiltinToolError::Canceled),
+            }
+        }
+    }
+    apply_resolutions(tree, merge_tool_files, &editor.files, conflict_marker_style)
+        .map_err(BuiltinToolError::BackendError)
+}
+
+#[cfg(test)]
+mod tests {
+    use jj_lib::repo::Repo as _;
+    use jj_lib::repo_path::RepoPath;
+    use testutils::TestRepo;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_keys(editor: &mut PaneEditor, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\n' => KeyCode::Enter,
+                '\t' => KeyCode::Tab,
+                c => KeyCode::Char(c),
+            };
+            editor.handle_key(key(code));
+        }
+    }
+
+    fn to_string(merge: &Merge<BString>) -> Vec<String> {
+        merge.iter().map(|term| term.to_string()).collect()
+    }
+
+    fn three_way() -> Merge<BString> {
+        Merge::from_vec(vec![
+            BString::from("a\nleft\nb\nleft 2\n"),
+            BString::from("a\nbase\nb\nbase 2\n"),
+            BString::from("a\nright\nb\nright 2\n"),
+        ])
+    }
+
+    #[test]
+    fn test_file_state() {
+        let file = FileState::new("file".to_owned(), &three_way());
+        assert_eq!(file.hunks.len(), 4);
+        assert_eq!(file.hunks[0], Hunk::Resolved("a\n".into()));
+        let Hunk::Conflict(hunk) = &file.hunks[1] else {
+            panic!();
+        };
+        assert_eq!(
+            hunk.terms,
+            Merge::from_vec(vec![
+                vec![BString::from("left\n")],
+                vec![BString::from("base\n")],
+                vec![BString::from("right\n")],
+            ])
+        );
+        assert_eq!(file.to_merge(3), three_way());
+    }
+
+    #[test]
+    fn test_take_sides() {
+        let mut editor = PaneEditor::new(vec![FileState::new("file".to_owned(), &three_way())]);
+        assert_eq!(editor.conflicts, vec![(0, 1), (0, 3)]);
+        type_keys(&mut editor, "2n");
+        assert_eq!(
+            to_string(&editor.files[0].to_merge(3)),
+            [
+                "a\nright\nb\nleft 2\n",
+                "a\nright\nb\nbase 2\n",
+                "a\nright\nb\nright 2\n"
+            ]
+        );
+        type_keys(&mut editor, "a");
+        assert_eq!(
+            to_string(&editor.files[0].to_merge(3)),
+            ["a\nright\nb\nleft 2\nright 2\n"; 3]
+        );
+        type_keys(&mut editor, "b");
+        assert_eq!(
+            to_string(&editor.files[0].to_merge(3)),
+            ["a\nright\nb\nbase 2\n"; 3]
+        );
+        // Reset the hunk to the conflict
+        type_keys(&mut editor, "u");
+        assert_eq!(
+            to_string(&editor.files[0].to_merge(3)),
+            [
+                "a\nright\nb\nleft 2\n",
+                "a\nright\nb\nbase 2\n",
+                "a\nright\nb\nright 2\n"
+            ]
+        );
+        assert!(editor.files[0].changed);
+    }
+
+    #[test]
+    fn test_no_op_keys_do_not_change_file() {
+        let mut editor = PaneEditor::new(vec![FileState::new("file".to_owned(), &three_way())]);
+        // Resetting an unresolved hunk and deleting from an empty result
+        type_keys(&mut editor, "udjk");
+        assert!(!editor.files[0].changed);
+        // Editing a line without modifying it
+        type_keys(&mut editor, "1");
+        editor.files[0].changed = false;
+        type_keys(&mut editor, "e\n");
+        assert!(!editor.files[0].changed);
+        type_keys(&mut editor, "d");
+        assert!(editor.files[0].changed);
+    }
+
+    #[test]
+    fn test_n_way() {
+        let content = Merge::from_vec(vec![
+            BString::from("one\n"),
+            BString::from("base\n"),
+            BString::from("two\n"),
+            BString::from("base\n"),
+            BString::from("three\n"),
+        ]);
+        let mut editor = PaneEditor::new(vec![FileState::new("file".to_owned(), &content)]);
+        assert_eq!(editor.num_terms(), 5);
+        type_keys(&mut editor, "3");
+        assert_eq!(to_string(&editor.files[0].to_merge(5)), ["three\n"; 5]);
+        // Copy lines from the first and second side
+        type_keys(&mut editor, "u\t\n\t\t\n");
+        assert_eq!(to_string(&editor.files[0].to_merge(5)), ["one\ntwo\n"; 5]);
+    }
+
+    #[test]
+    fn test_edit_lines() {
+        let mut editor = PaneEditor::new(vec![FileState::new("file".to_owned(), &three_way())]);
+        // Edit the line taken from the left side
+        type_keys(&mut editor, "1e!\n");
+        type_keys(&mut editor, "onew\n");
+        type_keys(&mut editor, "Oabove");
+        editor.handle_key(key(KeyCode::Backspace));
+        editor.handle_key(key(KeyCode::Enter));
+        assert_eq!(
+            editor.current_hunk().unwrap().result,
+            Some(vec!["left!\n".into(), "abov\n".into(), "new\n".into()])
+        );
+        // Cancelling the insertion removes the line again, deleting removes
+        // the line at the cursor.
+        type_keys(&mut editor, "oxyz");
+        editor.handle_key(key(KeyCode::Esc));
+        type_keys(&mut editor, "kd");
+        assert_eq!(
+            editor.current_hunk().unwrap().result,
+            Some(vec!["abov\n".into(), "new\n".into()])
+        );
+    }
+
+    #[test]
+    fn test_draw() {
+        let mut editor = PaneEditor::new(vec![FileState::new("file".to_owned(), &three_way())]);
+        type_keys(&mut editor, "\t");
+        for (width, height) in [(60, 10), (3, 2), (0, 0)] {
+            let mut output = vec![];
+            editor.draw(&mut output, width, height).unwrap();
+        }
+        let mut output = vec![];
+        editor.draw(&mut output, 60, 10).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("file - conflict 1 of 2 (0 resolved)"));
+        assert!(output.contains("Side #1"));
+        assert!(output.contains("Base"));
+        assert!(output.contains("right"));
+        assert!(output.contains("Result (unresolved)"));
+    }
+
+    #[test]
+    fn test_quit() {
+        let mut editor = PaneEditor::new(vec![FileState::new("file".to_owned(), &three_way())]);
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Char('q'))),
+            PaneEditorAction::Quit
+        );
+        type_keys(&mut editor, "1");
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Char('q'))),
+            PaneEditorAction::Continue
+        );
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Char('q'))),
+            PaneEditorAction::Quit
+        );
+        assert_eq!(
+            editor.handle_key(key(KeyCode::Char('w'))),
+            PaneEditorAction::Save
+        );
+    }
+
+    #[test]
+    fn test_apply_resolutions() {
+        let test_repo = TestRepo::init();
+        let path = RepoPath::from_internal_string("file");
+        let base_tree = testutils::create_tree(&test_repo.repo, &[(path, "a\nbase\nb\nbase 2\n")]);
+        let left_tree = testutils::create_tree(&test_repo.repo, &[(path, "a\nleft\nb\nleft 2\n")]);
+        let right_tree =
+            testutils::create_tree(&test_repo.repo, &[(path, "a\nright\nb\nright 2\n")]);
+        let tree = left_tree.merge(&base_tree, &right_tree).unwrap();
+        let merge_tool_file = MergeToolFile::from_tree_and_path(&tree, path).unwrap();
+        let mut editor = PaneEditor::new(vec![FileState::new(
+            "file".to_owned(),
+            &merge_tool_file.content,
+        )]);
+        let style = ConflictMarkerStyle::Diff;
+
+        // Resolving one of the conflicts leaves the file conflicted
+        type_keys(&mut editor, "1");
+        let tree_id = apply_resolutions(&tree, &[merge_tool_file], &editor.files, style).unwrap();
+        let new_tree = test_repo.repo.store().get_root_tree(&tree_id).unwrap();
+        let merge_tool_file = MergeToolFile::from_tree_and_path(&new_tree, path).unwrap();
+        assert_eq!(
+            to_string(&merge_tool_file.content),
+            [
+                "a\nleft\nb\nleft 2\n",
+                "a\nleft\nb\nbase 2\n",
+                "a\nleft\nb\nright 2\n"
+            ]
+        );
+
+        // Resolving all of them resolves the file
+        type_keys(&mut editor, "n2");
+        let tree_id = apply_resolutions(
+            &tree,
+            &[MergeToolFile::from_tree_and_path(&tree, path).unwrap()],
+            &editor.files,
+            style,
+        )
+        .unwrap();
+        let new_tree = test_repo.repo.store().get_root_tree(&tree_id).unwrap();
+        assert_eq!(
+            new_tree.path_value(path).unwrap(),
+            testutils::create_tree(&test_repo.repo, &[(path, "a\nleft\nb\nright 2\n")])
+                .path_value(path)
+                .unwrap()
+        );
+    }
+}
@@ -49,9 +49,9 @@ struct Args {
     #[arg(long, default_value_t = false)]
     lowercase: bool,
 
-    /// Adds a line to the end of the file
+    /// Adds a line to the end of the file. Can be repeated.
     #[arg(long)]
-    append: Option<String>,
+    append: Vec<String>,
 
     /// Write this string to stdout, and ignore stdin.
     #[arg(long)]
@@ -77,7 +77,7 @@ fn main() -> ExitCode {
         assert!(!args.reverse);
         assert!(!args.uppercase);
         assert!(!args.lowercase);
-        assert!(args.append.is_none());
+        assert!(args.append.is_empty());
         data
     } else {
         let mut stdout = std::io::stdin()
@@ -101,7 +101,7 @@ fn main() -> ExitCode {
                 })
             })
             .join("");
-        if let Some(line) = args.append {
+        for line in args.append {
             stdout.push_str(&line);
         }
         stdout
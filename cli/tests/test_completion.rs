@@ -682,6 +682,7 @@ fn test_config() {
     ui.conflict-marker-style=diff
     ui.conflict-marker-style=snapshot
     ui.conflict-marker-style=git
+    ui.conflict-marker-style=zdiff3
     [EOF]
     ");
     let output = test_env.run_jj_in(
@@ -733,6 +734,7 @@ fn test_template_alias() {
     builtin_op_log_node
     builtin_op_log_node_ascii
     builtin_op_log_oneline
+    builtin_resolve_list_json
     commit_summary_separator
     description_placeholder
     email_placeholder
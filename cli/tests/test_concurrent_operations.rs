@@ -244,8 +244,111 @@ fn test_concurrent_snapshot_wc_reloadable() {
     ");
 }
 
+#[test]
+fn test_concurrent_operations_bookmark_conflict() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    work_dir.run_jj(["new", "-m", "A"]).success();
+    work_dir.run_jj(["new", "root()", "-m", "B"]).success();
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "main"])
+        .success();
+    let base_op_id = get_current_op_id(&work_dir);
+
+    work_dir
+        .run_jj([
+            "bookmark",
+            "set",
+            "-r=description(A)",
+            "--allow-backwards",
+            "main",
+        ])
+        .success();
+    let side_op_id = get_current_op_id(&work_dir);
+    work_dir
+        .run_jj([
+            "bookmark",
+            "set",
+            "--at-op",
+            &base_op_id,
+            "-r=root()",
+            "--allow-backwards",
+            "main",
+        ])
+        .success();
+
+    // The merge operation should summarize both sides and the conflict
+    let output = work_dir.run_jj(["op", "show", "--no-graph"]);
+    insta::assert_snapshot!(output, @r"
+    38ad3758a2bb test-username@host.example.com 2001-02-03 04:05:15.000 +07:00 - 2001-02-03 04:05:15.000 +07:00
+    reconcile divergent operations
+    args: jj op show --no-graph
+
+    Merged operations:
+      26080e944cf3 (2001-02-03 08:05:12) point bookmark main to commit 9489917e45396967361c0f00da2b990b93722840
+        test-username@host.example.com: 0 modified changes, 1 modified bookmark
+      22df523a4a2e (2001-02-03 08:05:14) point bookmark main to commit 0000000000000000000000000000000000000000
+        test-username@host.example.com: 0 modified changes, 1 modified bookmark
+    Introduced bookmark conflicts: main
+    [EOF]
+    ------- stderr -------
+    Concurrent modification detected, resolving automatically.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bookmark", "list"]);
+    insta::assert_snapshot!(output, @r"
+    main (conflicted):
+      - kkmpptxz 66f4d180 (empty) B
+      + rlvkpnrz 9489917e (empty) A
+      + zzzzzzzz 00000000 (empty) (no description set)
+    [EOF]
+    ");
+
+    // Pick one side's target for the conflicted bookmark
+    let output = work_dir.run_jj(["op", "resolve", &side_op_id]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Resolved 1 bookmark to operation: 26080e944cf3 (2001-02-03 08:05:12) point bookmark main to commit 9489917e45396967361c0f00da2b990b93722840
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["bookmark", "list"]);
+    insta::assert_snapshot!(output, @r"
+    main: rlvkpnrz 9489917e (empty) A
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["op", "resolve", &side_op_id, "main"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["op", "resolve", &side_op_id, "unknown"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such bookmark: unknown
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[must_use]
 fn get_log_output(work_dir: &TestWorkDir) -> CommandOutput {
     let template = r#"commit_id ++ " " ++ description"#;
     work_dir.run_jj(["log", "-T", template])
 }
+
+fn get_current_op_id(work_dir: &TestWorkDir) -> String {
+    let output = work_dir
+        .run_jj([
+            "op",
+            "log",
+            "--ignore-working-copy",
+            "--no-graph",
+            "-n1",
+            "-Tid.short()",
+        ])
+        .success();
+    output.stdout.into_raw()
+}
@@ -1410,6 +1410,59 @@ fn test_config_conditional_without_home_dir() {
     ");
 }
 
+#[test]
+fn test_config_conditional_workspace_and_environment() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    test_env
+        .work_dir("main")
+        .run_jj(["workspace", "add", "../secondary"])
+        .success();
+    // Test with fresh new config file
+    let user_config_path = test_env.env_root().join("config.toml");
+    test_env.set_config_path(&user_config_path);
+    let main_dir = test_env.work_dir("main");
+    let secondary_dir = test_env.work_dir("secondary");
+    std::fs::write(
+        &user_config_path,
+        indoc! {"
+            foo = 'global'
+            bar = 'global'
+            [[--scope]]
+            --when.workspaces = ['secondary']
+            foo = 'secondary'
+            [[--scope]]
+            --when.environments = ['JJ_TEST_RELEASE=1']
+            bar = 'release'
+        "},
+    )
+    .unwrap();
+
+    let output = main_dir.run_jj(["config", "get", "foo"]);
+    insta::assert_snapshot!(output, @r"
+    global
+    [EOF]
+    ");
+    let output = secondary_dir.run_jj(["config", "get", "foo"]);
+    insta::assert_snapshot!(output, @r"
+    secondary
+    [EOF]
+    ");
+
+    let output = main_dir.run_jj(["config", "get", "bar"]);
+    insta::assert_snapshot!(output, @r"
+    global
+    [EOF]
+    ");
+    test_env.add_env_var("JJ_TEST_RELEASE", "1");
+    let main_dir = test_env.work_dir("main");
+    let output = main_dir.run_jj(["config", "get", "bar"]);
+    insta::assert_snapshot!(output, @r"
+    release
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_config_show_paths() {
     let test_env = TestEnvironment::default();
@@ -358,6 +358,7 @@ fn test_evolog_with_no_template() {
     - builtin_op_log_node
     - builtin_op_log_node_ascii
     - builtin_op_log_oneline
+    - builtin_resolve_list_json
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
@@ -953,6 +953,80 @@ fn sorted_lines(path: PathBuf) -> String {
     log.join("\n")
 }
 
+#[test]
+fn test_fix_line_range_variables() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    set_up_fake_formatter(&test_env, &["--append=[$first_line:$last_line]\n"]);
+    work_dir.write_file("file", "a\nb\nc\nd\n");
+    work_dir.write_file("old", "1\n2\n3\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file", "a\nB\nc\nd\ne\n");
+    work_dir.write_file("new", "x\ny\n");
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file", "a\nB\nd\ne\n");
+    work_dir.write_file("old", "1\n3\n");
+
+    // The argument is repeated for each range of lines changed relative to the
+    // parent revision
+    let output = work_dir.run_jj(["fix", "-s", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Fixed 2 commits of 2 checked.
+    Working copy now at: kkmpptxz f964b313 (no description set)
+    Parent commit      : rlvkpnrz 378ea160 (no description set)
+    Added 0 files, modified 2 files, removed 0 files
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "file", "-r", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    B
+    c
+    d
+    e
+    [2:2]
+    [5:5]
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "new", "-r", "@-"]);
+    insta::assert_snapshot!(output, @r"
+    x
+    y
+    [1:2]
+    [EOF]
+    ");
+    // The file fixed in the parent and modified again in the child is fixed
+    // with the lines changed relative to the grandparent, so the lines fixed in
+    // the parent are fixed again. The unchanged file is fixed the same way as
+    // in the parent.
+    let output = work_dir.run_jj(["file", "show", "file", "-r", "@"]);
+    insta::assert_snapshot!(output, @r"
+    a
+    B
+    d
+    e
+    [2:2]
+    [4:4]
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "new", "-r", "@"]);
+    insta::assert_snapshot!(output, @r"
+    x
+    y
+    [1:2]
+    [EOF]
+    ");
+    // Lines were only removed from the other file, so the tool isn't run on it
+    let output = work_dir.run_jj(["file", "show", "old", "-r", "@"]);
+    insta::assert_snapshot!(output, @r"
+    1
+    3
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_executed_but_nothing_changed() {
     // Show that the tool ran by causing a side effect with --tee, and test that we
@@ -1324,6 +1398,62 @@ fn test_fix_resolve_conflict() {
     ");
 }
 
+#[test]
+fn test_fix_resolve_conflict_from_reformatting() {
+    // A conflict caused by one side reformatting the file goes away once every
+    // side of the conflict is formatted the same way.
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+    set_up_fake_formatter(&test_env, &["--uppercase"]);
+    work_dir.write_file("file", "foo\nbar\n");
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "base"])
+        .success();
+    work_dir.run_jj(["new"]).success();
+    work_dir.write_file("file", "FOO\nBAR\n");
+    work_dir
+        .run_jj(["bookmark", "create", "-r@", "formatted"])
+        .success();
+    work_dir.run_jj(["new", "base"]).success();
+    work_dir.write_file("file", "foo\nbaz\n");
+    work_dir
+        .run_jj(["rebase", "-r@", "-d", "formatted"])
+        .success();
+    let output = work_dir.run_jj(["file", "show", "file", "-r", "@"]);
+    insta::assert_snapshot!(output, @r"
+    <<<<<<< Conflict 1 of 1
+    +++++++ Contents of side #1
+    FOO
+    BAR
+    %%%%%%% Changes from base to side #2
+     foo
+    -bar
+    +baz
+    >>>>>>> Conflict 1 of 1 ends
+    [EOF]
+    ");
+
+    // Only the conflicted commit needs to be fixed. The base of the conflict is
+    // formatted too.
+    let output = work_dir.run_jj(["fix", "-s", "@"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Fixed 1 commits of 1 checked.
+    Working copy now at: mzvwutvl d1b60721 (no description set)
+    Parent commit      : kkmpptxz 85e63d77 formatted | (no description set)
+    Added 0 files, modified 1 files, removed 0 files
+    Existing conflicts were resolved or abandoned from 1 commits.
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["file", "show", "file", "-r", "@"]);
+    insta::assert_snapshot!(output, @r"
+    FOO
+    BAZ
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_all_files() {
     let test_env = TestEnvironment::default();
@@ -2172,6 +2172,126 @@ fn test_git_push_sign_on_push() {
     ");
 }
 
+#[test]
+fn test_git_push_sign_behavior_on_push() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    test_env.add_config(
+        r#"
+    signing.backend = "test"
+    signing.key = "impeccable"
+    signing.behavior = "on-push"
+    "#,
+    );
+    let template = r#"
+    separate(" ",
+      description.first_line(),
+      if(signature, "Status: " ++ signature.status()),
+    ) ++ "\n"
+    "#;
+    work_dir
+        .run_jj(["new", "bookmark2", "-m", "commit to be signed"])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark2", "-r@"])
+        .success();
+    work_dir
+        .run_jj(["new", "-m", "commit which should not be signed"])
+        .success();
+    // Commits aren't signed when they are created
+    let output = work_dir.run_jj(["log", "-T", template, "-r", "bookmark2::"]);
+    insta::assert_snapshot!(output, @r"
+    @  commit which should not be signed
+    ○  commit to be signed
+    │
+    ~
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["git", "push"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Updated signatures of 1 commits
+    Rebased 1 descendant commits
+    Changes to push to origin:
+      Move forward bookmark bookmark2 from 8476341eb395 to 9535c75b0a46
+    Working copy now at: znkkpsqq 4412ff7f (empty) commit which should not be signed
+    Parent commit      : vruxwmqv 9535c75b bookmark2 | (empty) commit to be signed
+    [EOF]
+    ");
+    let output = work_dir.run_jj(["log", "-T", template, "-r", "bookmark2::"]);
+    insta::assert_snapshot!(output, @r"
+    @  commit which should not be signed
+    ○  commit to be signed Status: good
+    │
+    ~
+    [EOF]
+    ");
+
+    // Rewriting a pushed commit drops the signature
+    work_dir
+        .run_jj(["describe", "bookmark2", "-m", "rewritten commit"])
+        .success();
+    let output = work_dir.run_jj(["log", "-T", template, "-r", "bookmark2::"]);
+    insta::assert_snapshot!(output, @r"
+    @  commit which should not be signed
+    ○  rewritten commit
+    │
+    ~
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_git_push_require_signed_commits() {
+    let test_env = TestEnvironment::default();
+    set_up(&test_env);
+    let work_dir = test_env.work_dir("local");
+    test_env.add_config(
+        r#"
+    signing.backend = "test"
+    signing.key = "impeccable"
+    git.require-signed-commits = "mine()"
+    "#,
+    );
+    work_dir
+        .run_jj(["new", "bookmark2", "-m", "unsigned commit"])
+        .success();
+    work_dir
+        .run_jj(["bookmark", "set", "bookmark2", "-r@"])
+        .success();
+
+    let output = work_dir.run_jj(["git", "push", "--dry-run"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Won't push commit e4c5c4fc1737 since it is not signed
+    Hint: Rejected commit: vruxwmqv e4c5c4fc bookmark2* | (empty) unsigned commit
+    Hint: Configured git.require-signed-commits: 'mine()'
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Commits signed on push satisfy the policy
+    let output = work_dir.run_jj(["git", "push", "--dry-run", "--config=git.sign-on-push=true"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark2 from 8476341eb395 to e4c5c4fc1737
+    Dry-run requested, not pushing.
+    [EOF]
+    ");
+
+    work_dir.run_jj(["sign", "-r@"]).success();
+    let output = work_dir.run_jj(["git", "push"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Changes to push to origin:
+      Move forward bookmark bookmark2 from 8476341eb395 to d8fcf3f11a9f
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_git_push_rejected_by_remote() {
     let test_env = TestEnvironment::default();
@@ -60,6 +60,7 @@ fn test_log_with_no_template() {
     - builtin_op_log_node
     - builtin_op_log_node_ascii
     - builtin_op_log_oneline
+    - builtin_resolve_list_json
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
@@ -368,6 +369,33 @@ fn test_log_with_or_without_diff() {
     ");
 }
 
+#[test]
+fn test_log_interactive_without_terminal() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    let output = test_env.run_jj_in(&repo_path, ["log", "--interactive"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Interactive log requires a terminal
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["log", "--interactive", "--no-graph"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the argument '--interactive' cannot be used with '--no-graph'
+
+    Usage: jj log --interactive [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_log_null_terminate_multiline_descriptions() {
     let test_env = TestEnvironment::default();
@@ -185,6 +185,7 @@ fn test_op_log_with_no_template() {
     - builtin_op_log_node
     - builtin_op_log_node_ascii
     - builtin_op_log_oneline
+    - builtin_resolve_list_json
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
@@ -2136,12 +2137,19 @@ fn test_op_show() {
     Concurrent modification detected, resolving automatically.
     [EOF]
     ");
-    // Showing a merge operation is empty.
+    // Showing a merge operation only summarizes the merged operations.
     let output = test_env.run_jj_in(&repo_path, ["op", "show"]);
     insta::assert_snapshot!(output, @r"
     774687cc6e4e test-username@host.example.com 2001-02-03 04:05:14.000 +07:00 - 2001-02-03 04:05:14.000 +07:00
     reconcile divergent operations
     args: jj log
+
+    Merged operations:
+      364d0a677b0c (2001-02-03 08:05:07) check out git remote's default branch
+        test-username@host.example.com: 2 modified changes, 1 modified bookmark
+      4fe4acac8c5f (2001-02-03 08:05:13) point bookmark bookmark-1 to commit 4ff6253913375c6ebdddd8423c11df3b3f17e331
+        test-username@host.example.com: 0 modified changes, 1 modified bookmark
+    Introduced bookmark conflicts: bookmark-1
     [EOF]
     ");
 
@@ -14,7 +14,12 @@
 
 use std::path::Path;
 
+use indoc::formatdoc;
+
 use crate::common::create_commit;
+use crate::common::create_commit_with_files;
+use crate::common::fake_editor_path;
+use crate::common::to_toml_value;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -3158,6 +3163,155 @@ fn test_rebase_skip_if_on_destination() {
     ");
 }
 
+#[test]
+fn test_rebase_with_merge_driver() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+    test_env.add_config(formatdoc! {r#"
+        [merge.drivers.lockfiles]
+        patterns = ["glob:'*.lock'"]
+        command = [{editor}, "$output"]
+
+        [merge.drivers.changelog]
+        patterns = ["CHANGELOG"]
+        strategy = "union"
+        "#,
+        editor = to_toml_value(fake_editor_path()),
+    });
+    let work_dir = test_env.work_dir(&repo_path);
+
+    create_commit_with_files(
+        &work_dir,
+        "a",
+        &[],
+        &[("Cargo.lock", "a\n"), ("CHANGELOG", "a\n"), ("file", "a\n")],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "b",
+        &["a"],
+        &[
+            ("Cargo.lock", "b\n"),
+            ("CHANGELOG", "a\nb\n"),
+            ("file", "b\n"),
+        ],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "c",
+        &["a"],
+        &[
+            ("Cargo.lock", "c\n"),
+            ("CHANGELOG", "a\nc\n"),
+            ("file", "c\n"),
+        ],
+    );
+
+    // The lockfile is resolved by the command, the changelog by taking both
+    // sides, and the other file remains conflicted.
+    std::fs::write(&edit_script, "write\nmerged\n").unwrap();
+    let output = work_dir.run_jj(["rebase", "-r", "c", "-d", "b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 commits onto destination
+    Working copy now at: royxmykx ec3c6d3e c | (conflict) c
+    Parent commit      : zsuskuln 30787b89 b | b
+    Added 0 files, modified 3 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict
+    New conflicts appeared in 1 commits:
+      royxmykx ec3c6d3e c | (conflict) c
+    Hint: To resolve the conflicts, start by updating to it:
+      jj new royxmykx
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "-r", "c", "Cargo.lock"]), @r"
+    merged
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "-r", "c", "CHANGELOG"]), @r"
+    a
+    b
+    c
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list", "-r", "c"]), @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+
+    // If the command fails, the file remains conflicted.
+    work_dir.run_jj(["undo"]).success();
+    std::fs::write(&edit_script, "fail").unwrap();
+    work_dir.run_jj(["rebase", "-r", "c", "-d", "b"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list", "-r", "c"]), @r"
+    Cargo.lock    2-sided conflict
+    file          2-sided conflict
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_rebase_reuses_recorded_resolution() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    test_env.add_config("merge.reuse-resolutions = true");
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(&work_dir, "a", &[], &[("file", "a\n")]);
+    create_commit_with_files(&work_dir, "b", &["a"], &[("file", "b\n")]);
+    create_commit_with_files(&work_dir, "d", &["a"], &[("file", "c\n")]);
+    create_commit_with_files(&work_dir, "c", &["a"], &[("file", "c\n")]);
+
+    // Resolving the conflict in the working copy records the resolution
+    work_dir.run_jj(["rebase", "-r", "c", "-d", "b"]).success();
+    work_dir.write_file("file", "resolved\n");
+    work_dir.run_jj(["status"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["util", "resolutions", "list"]), @r"
+    c2f63d18f4a4 file
+    [EOF]
+    ");
+
+    // The same conflict is resolved automatically
+    let output = work_dir.run_jj(["rebase", "-r", "d", "-d", "b"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Rebased 1 commits onto destination
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "show", "-r", "d", "file"]), @r"
+    resolved
+    [EOF]
+    ");
+
+    // Forgotten resolutions are no longer applied
+    let output = work_dir.run_jj(["util", "resolutions", "forget", "0"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No recorded resolution matches key 0
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = work_dir.run_jj(["util", "resolutions", "forget", "--all"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Forgot resolution c2f63d18f4a4
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["util", "resolutions", "list"]), @"");
+    work_dir.run_jj(["undo"]).success();
+    work_dir.run_jj(["rebase", "-r", "d", "-d", "b"]).success();
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list", "-r", "d"]), @r"
+    file    2-sided conflict
+    [EOF]
+    ");
+}
+
 #[must_use]
 fn get_log_output(test_env: &TestEnvironment, repo_path: &Path) -> CommandOutput {
     let template = "bookmarks ++ surround(': ', '', parents.map(|c| c.bookmarks()))";
@@ -17,6 +17,7 @@ use std::path::Path;
 use indoc::indoc;
 
 use crate::common::create_commit_with_files;
+use crate::common::force_interactive;
 use crate::common::CommandOutput;
 use crate::common::TestEnvironment;
 
@@ -683,6 +684,67 @@ fn test_baseless_conflict_input_files() {
     check_resolve_produces_input_file(&mut test_env, &repo_path, "file", "right", "b\n");
 }
 
+#[test]
+fn test_resolve_list_template() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    create_commit_with_files(
+        &work_dir,
+        "base",
+        &[],
+        &[("file", "1\n2\n3\n4\n5\n"), ("other", "base\n")],
+    );
+    create_commit_with_files(
+        &work_dir,
+        "a",
+        &["base"],
+        &[("file", "a\n2\n3\n4\na\n"), ("other", "a\n")],
+    );
+    create_commit_with_files(&work_dir, "b", &["base"], &[("file", "b\n2\n3\n4\nb\n")]);
+    std::fs::remove_file(work_dir.root().join("other")).unwrap();
+    work_dir.write_file("other/placeholder", "");
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+
+    let template = r#"
+    separate(" ",
+      path,
+      num_sides ++ "-sided",
+      kind,
+      num_hunks ++ " hunks",
+      sides.map(|s| s.file_type() ++ "@" ++ if(s.commit(), s.commit().bookmarks())),
+    ) ++ "\n"
+    "#;
+    let output = work_dir.run_jj(["resolve", "--list", "-T", template]);
+    insta::assert_snapshot!(output, @r"
+    file 2-sided content 2 hunks file@a file@b
+    other 2-sided type 0 hunks file@a tree@b
+    [EOF]
+    ");
+
+    let output = work_dir.run_jj(["resolve", "--list", "-T", "builtin_resolve_list_json"]);
+    insta::assert_snapshot!(output, @r#"
+    {"path":"file","num_sides":2,"kind":"content","num_hunks":2,"sides":[{"file_type":"file","executable":false,"commit":"93dd15dd5b158b69f5d0b68e84130ba72657e8f1"},{"file_type":"file","executable":false,"commit":"b27211d0f2fccf506ab55b0dea705e470e3e226f"}]}
+    {"path":"other","num_sides":2,"kind":"type","num_hunks":0,"sides":[{"file_type":"file","executable":false,"commit":"93dd15dd5b158b69f5d0b68e84130ba72657e8f1"},{"file_type":"tree","executable":false,"commit":"b27211d0f2fccf506ab55b0dea705e470e3e226f"}]}
+    [EOF]
+    "#);
+
+    // A template can't be used when resolving conflicts
+    let output = work_dir.run_jj(["resolve", "-T", "path"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    error: the following required arguments were not provided:
+      --list
+
+    Usage: jj resolve --list --template <TEMPLATE> [FILESETS]...
+
+    For more information, try '--help'.
+    [EOF]
+    [exit status: 2]
+    ");
+}
+
 #[test]
 fn test_too_many_parents() {
     let test_env = TestEnvironment::default();
@@ -1007,6 +1069,104 @@ fn test_file_vs_dir() {
     "#);
 }
 
+#[test]
+fn test_resolve_non_file_conflicts_interactively() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let work_dir = test_env.work_dir("repo");
+
+    // "file" is a file on one side and a directory on the other, and "script"
+    // has the same contents on both sides but only one side is executable.
+    create_commit_with_files(&work_dir, "base", &[], &[]);
+    create_commit_with_files(
+        &work_dir,
+        "a",
+        &["base"],
+        &[("file", "a\n"), ("script", "same\n")],
+    );
+    work_dir.run_jj(["file", "chmod", "x", "script"]).success();
+    create_commit_with_files(
+        &work_dir,
+        "b",
+        &["base"],
+        &[("file/placeholder", ""), ("script", "same\n")],
+    );
+    create_commit_with_files(&work_dir, "conflict", &["a", "b"], &[]);
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @r"
+    file      2-sided conflict including a directory
+    script    2-sided conflict including an executable
+    [EOF]
+    ");
+
+    // Keep the directory and make the script executable
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["resolve"])
+            .write_stdin("2\n1\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Hint: Using default editor ':builtin'; run `jj config set --user ui.merge-editor :builtin` to disable this message.
+    The conflict in file can't be resolved by a merge tool. Choose a side to keep:
+    1: side #1 (file)
+    2: side #2 (directory)
+    s: skip this path
+    Enter the index of the side to keep: The sides of the conflict in script disagree on the executable bit:
+    1: make the resolved file executable
+    2: make the resolved file non-executable
+    s: skip this file
+    Enter the index of the resolution: Working copy now at: yostqsxw 060f19e0 conflict | conflict
+    Parent commit      : zsuskuln bc125e54 a | a
+    Parent commit      : yqosqzyt 30a17aef b | b
+    Added 1 files, modified 1 files, removed 1 files
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["file", "list"]), @r"
+    file/placeholder
+    script
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["diff", "--git", "--from", "a", "script"]), @"");
+
+    // Skipped paths remain conflicted
+    work_dir.run_jj(["undo"]).success();
+    let output = work_dir.run_jj_with(|cmd| {
+        force_interactive(cmd)
+            .args(["resolve"])
+            .write_stdin("s\n2\n")
+    });
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Hint: Using default editor ':builtin'; run `jj config set --user ui.merge-editor :builtin` to disable this message.
+    The conflict in file can't be resolved by a merge tool. Choose a side to keep:
+    1: side #1 (file)
+    2: side #2 (directory)
+    s: skip this path
+    Enter the index of the side to keep: The sides of the conflict in script disagree on the executable bit:
+    1: make the resolved file executable
+    2: make the resolved file non-executable
+    s: skip this file
+    Enter the index of the resolution: Working copy now at: yostqsxw 1bda44dd conflict | (conflict) conflict
+    Parent commit      : zsuskuln bc125e54 a | a
+    Parent commit      : yqosqzyt 30a17aef b | b
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file    2-sided conflict including a directory
+    New conflicts appeared in 1 commits:
+      yostqsxw 1bda44dd conflict | (conflict) conflict
+    Hint: To resolve the conflicts, start by updating to it:
+      jj new yostqsxw
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    insta::assert_snapshot!(work_dir.run_jj(["resolve", "--list"]), @r"
+    file    2-sided conflict including a directory
+    [EOF]
+    ");
+}
+
 #[test]
 fn test_description_with_dir_and_deletion() {
     let test_env = TestEnvironment::default();
@@ -1871,3 +2031,125 @@ fn test_multiple_conflicts_with_error() {
     [EOF]
     ");
 }
+
+#[test]
+fn test_resolve_dir_invocation_mode() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    create_commit_with_files(
+        &test_env.work_dir(&repo_path),
+        "base",
+        &[],
+        &[("dir/file1", "base1\n"), ("file2", "base2\n")],
+    );
+    create_commit_with_files(
+        &test_env.work_dir(&repo_path),
+        "a",
+        &["base"],
+        &[("dir/file1", "a1\n"), ("file2", "a2\n")],
+    );
+    create_commit_with_files(
+        &test_env.work_dir(&repo_path),
+        "b",
+        &["base"],
+        &[("dir/file1", "b1\n"), ("file2", "b2\n")],
+    );
+    create_commit_with_files(&test_env.work_dir(&repo_path), "conflict", &["a", "b"], &[]);
+    let edit_script = test_env.set_up_fake_diff_editor();
+    test_env.add_config(indoc! {r#"
+        ui.merge-editor = "fake-diff-editor"
+        merge-tools.fake-diff-editor.merge-args = ["$left", "$output"]
+        merge-tools.fake-diff-editor.merge-invocation-mode = "dir"
+    "#});
+
+    // The tool is invoked once with all files, and files left empty stay
+    // conflicted
+    std::fs::write(
+        &edit_script,
+        [
+            "print-files-before",
+            "print-files-after",
+            "write dir/file1\nresolution1\n",
+        ]
+        .join("\0"),
+    )
+    .unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["resolve"]);
+    insta::assert_snapshot!(output, @r"
+    dir/file1
+    file2
+    dir/file1
+    file2
+    [EOF]
+    ------- stderr -------
+    Resolving conflicts in: dir/file1
+    Resolving conflicts in: file2
+    Working copy now at: vruxwmqv 6b80032a conflict | (conflict) conflict
+    Parent commit      : zsuskuln 91858536 a | a
+    Parent commit      : royxmykx 5eb99f56 b | b
+    Added 0 files, modified 1 files, removed 0 files
+    Warning: There are unresolved conflicts at these paths:
+    file2    2-sided conflict
+    New conflicts appeared in 1 commits:
+      vruxwmqv 6b80032a conflict | (conflict) conflict
+    Hint: To resolve the conflicts, start by updating to it:
+      jj new vruxwmqv
+    Then use `jj resolve`, or edit the conflict markers in the file directly.
+    Once the conflicts are resolved, you may want to inspect the result with `jj diff`.
+    Then run `jj squash` to move the resolution into the conflicted commit.
+    [EOF]
+    ");
+    insta::assert_snapshot!(test_env.run_jj_in(&repo_path, ["diff", "--git"]), @r"
+    diff --git a/dir/file1 b/dir/file1
+    index 0000000000..95cc18629d 100644
+    --- a/dir/file1
+    +++ b/dir/file1
+    @@ -1,7 +1,1 @@
+    -<<<<<<< Conflict 1 of 1
+    -%%%%%%% Changes from base to side #1
+    --base1
+    -+a1
+    -+++++++ Contents of side #2
+    -b1
+    ->>>>>>> Conflict 1 of 1 ends
+    +resolution1
+    [EOF]
+    ");
+
+    // If no file is resolved, nothing changes
+    std::fs::write(&edit_script, "").unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["resolve"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Resolving conflicts in: file2
+    Error: Failed to resolve conflicts
+    Caused by: The output file is either unchanged or empty after the editor quit (run with --debug to see the exact invocation).
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // Conflict markers can be edited
+    test_env.add_config("merge-tools.fake-diff-editor.merge-tool-edits-conflict-markers = true");
+    std::fs::write(&edit_script, "dump file2 output2").unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["resolve"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Resolving conflicts in: file2
+    Error: Failed to resolve conflicts
+    Caused by: The output file is either unchanged or empty after the editor quit (run with --debug to see the exact invocation).
+    [EOF]
+    [exit status: 1]
+    ");
+    insta::assert_snapshot!(
+        std::fs::read_to_string(test_env.env_root().join("output2")).unwrap(), @r"
+    <<<<<<< Conflict 1 of 1
+    %%%%%%% Changes from base to side #1
+    -base2
+    +a2
+    +++++++ Contents of side #2
+    b2
+    >>>>>>> Conflict 1 of 1 ends
+    ");
+}
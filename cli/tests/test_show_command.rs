@@ -285,6 +285,7 @@ fn test_show_with_no_template() {
     - builtin_op_log_node
     - builtin_op_log_node_ascii
     - builtin_op_log_oneline
+    - builtin_resolve_list_json
     - commit_summary_separator
     - description_placeholder
     - email_placeholder
@@ -190,6 +190,222 @@ fn test_sparse_manage_patterns() {
     ");
 }
 
+#[test]
+fn test_sparse_fileset() {
+    let mut test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    let edit_script = test_env.set_up_fake_editor();
+
+    std::fs::create_dir_all(repo_path.join("src").join("testdata")).unwrap();
+    std::fs::create_dir_all(repo_path.join("src").join("sub").join("testdata")).unwrap();
+    std::fs::write(repo_path.join("file1"), "contents").unwrap();
+    std::fs::write(repo_path.join("src").join("file2"), "contents").unwrap();
+    std::fs::write(
+        repo_path.join("src").join("testdata").join("file3"),
+        "contents",
+    )
+    .unwrap();
+    std::fs::write(
+        repo_path
+            .join("src")
+            .join("sub")
+            .join("testdata")
+            .join("file4"),
+        "contents",
+    )
+    .unwrap();
+    std::fs::write(repo_path.join("src").join("sub").join("file5"), "contents").unwrap();
+
+    // File patterns are relative to the workspace root
+    let sub_dir = repo_path.join("src");
+    let output = test_env.run_jj_in(
+        &sub_dir,
+        [
+            "sparse",
+            "set",
+            "--fileset",
+            r#"src ~ glob:"src/**/testdata/**""#,
+        ],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 0 files, modified 0 files, removed 3 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&sub_dir, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r#"
+    src ~ glob:"src/**/testdata/**"
+    [EOF]
+    "#);
+    let output = test_env.run_jj_in(&repo_path, ["file", "list"]);
+    insta::assert_snapshot!(output, @r"
+    file1
+    src/file2
+    src/sub/file5
+    src/sub/testdata/file4
+    src/testdata/file3
+    [EOF]
+    ");
+    assert!(!repo_path.join("file1").exists());
+    assert!(repo_path.join("src").join("file2").exists());
+    assert!(!repo_path.join("src").join("testdata").exists());
+    assert!(repo_path.join("src").join("sub").join("file5").exists());
+
+    // --add and --remove extend the expression
+    let output = test_env.run_jj_in(
+        &repo_path,
+        ["sparse", "set", "--add", "file1", "--remove", "src/sub"],
+    );
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 1 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r#"
+    ((src ~ glob:"src/**/testdata/**") ~ (root:"src/sub")) | root:"file1"
+    [EOF]
+    "#);
+
+    // Invalid expressions are rejected
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "set", "--fileset", "src |"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Failed to parse fileset: Syntax error
+    Caused by:  --> 1:6
+      |
+    1 | src |
+      |      ^---
+      |
+      = expected `~` or <primary>
+    Hint: See https://jj-vcs.github.io/jj/latest/filesets/ or use `jj help -k filesets` for filesets syntax and how to match file paths.
+    [EOF]
+    [exit status: 1]
+    ");
+
+    // The expression can be edited
+    std::fs::write(&edit_script, "write\nfile1 | src/sub\n").unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "edit"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 2 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    file1 | src/sub
+    [EOF]
+    ");
+
+    // --clear switches back to path prefixes
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "set", "--clear", "--add", "src"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 2 files, modified 0 files, removed 1 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    src
+    [EOF]
+    ");
+}
+
+#[test]
+fn test_sparse_profile() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+
+    std::fs::create_dir_all(repo_path.join(".jj-sparse")).unwrap();
+    std::fs::create_dir(repo_path.join("frontend")).unwrap();
+    std::fs::create_dir(repo_path.join("backend")).unwrap();
+    std::fs::write(repo_path.join("file1"), "contents").unwrap();
+    std::fs::write(repo_path.join("frontend").join("file2"), "contents").unwrap();
+    std::fs::write(repo_path.join("backend").join("file3"), "contents").unwrap();
+    std::fs::write(
+        repo_path.join(".jj-sparse").join("frontend"),
+        "# The frontend and the profiles\n.jj-sparse | frontend\n",
+    )
+    .unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "first"])
+        .success();
+
+    // Unknown profiles are rejected
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "use", "backend"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No such sparse profile: backend
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "use", "frontend"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 0 files, modified 0 files, removed 2 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    .jj-sparse | frontend
+    [EOF]
+    ------- stderr -------
+    Using sparse profile frontend
+    [EOF]
+    ");
+    assert!(!repo_path.join("file1").exists());
+    assert!(repo_path.join("frontend").join("file2").exists());
+    assert!(!repo_path.join("backend").exists());
+
+    // The profile is read again when another commit is checked out
+    std::fs::write(
+        repo_path.join(".jj-sparse").join("frontend"),
+        ".jj-sparse | frontend | file1\n",
+    )
+    .unwrap();
+    test_env
+        .run_jj_in(&repo_path, ["commit", "-m", "second"])
+        .success();
+    test_env
+        .run_jj_in(&repo_path, ["new", "description(first)"])
+        .success();
+    assert!(!repo_path.join("file1").exists());
+    let output = test_env.run_jj_in(&repo_path, ["new", "description(second)"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Working copy now at: vruxwmqv 19f9f633 (empty) (no description set)
+    Parent commit      : rlvkpnrz 679c28ff second
+    Added 1 files, modified 1 files, removed 0 files
+    [EOF]
+    ");
+    assert!(repo_path.join("file1").exists());
+    assert!(!repo_path.join("backend").exists());
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r"
+    .jj-sparse | frontend | file1
+    [EOF]
+    ------- stderr -------
+    Using sparse profile frontend
+    [EOF]
+    ");
+
+    // Setting the patterns stops using the profile
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "set", "--add", "backend"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Added 1 files, modified 0 files, removed 0 files
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&repo_path, ["sparse", "list"]);
+    insta::assert_snapshot!(output, @r#"
+    (.jj-sparse | frontend | file1) | root:"backend"
+    [EOF]
+    "#);
+}
+
 #[test]
 fn test_sparse_editor_avoids_unc() {
     use std::path::PathBuf;
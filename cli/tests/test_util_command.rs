@@ -106,6 +106,69 @@ fn test_gc_operation_log() {
     "#);
 }
 
+#[test]
+fn test_util_index() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "repo"]).success();
+    let repo_path = test_env.env_root().join("repo");
+    for i in 0..3 {
+        std::fs::write(repo_path.join("file"), format!("{i}\n")).unwrap();
+        test_env
+            .run_jj_in(&repo_path, ["commit", "-m", &format!("commit {i}")])
+            .success();
+    }
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "index", "verify"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    No problems found in the index.
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "index", "compact", "--at-op=@-"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: Cannot compact the index from a non-head operation
+    [EOF]
+    [exit status: 1]
+    ");
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "index", "compact"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Compacted index of 11 commits into one segment file.
+    [EOF]
+    ");
+    let segments_dir = repo_path.join(".jj/repo/index/segments");
+    assert_eq!(segments_dir.read_dir().unwrap().count(), 1);
+
+    let output = test_env.run_jj_in(&repo_path, ["util", "index", "verify"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    No problems found in the index.
+    [EOF]
+    ");
+
+    // Corrupt the link from the previous operation
+    let op_id = test_env.work_dir(&repo_path).current_operation_id();
+    test_env
+        .run_jj_in(&repo_path, ["describe", "-m", "new description"])
+        .success();
+    let operations_dir = repo_path.join(".jj/repo/index/operations");
+    std::fs::write(operations_dir.join(&op_id), "0".repeat(128)).unwrap();
+    std::fs::write(segments_dir.join("0".repeat(128)), b"\0".repeat(24)).unwrap();
+    let output = test_env.run_jj_in(&repo_path, ["util", "index", "verify"]);
+    insta::assert_snapshot!(output.normalize_stdout_with(|s| s.replace(&op_id, "<op-id>")), @r"
+    Index of operation <op-id> cannot be loaded: Unexpected index version
+    [EOF]
+    ------- stderr -------
+    Error: Index verification failed
+    Hint: Run `jj debug reindex` to rebuild the index.
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
 #[test]
 fn test_shell_completions() {
     #[track_caller]
@@ -1251,6 +1251,178 @@ fn test_workspaces_forget_abandon_commits() {
     ");
 }
 
+/// Test that workspaces whose directories were deleted are reported
+#[test]
+fn test_workspaces_missing() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    test_env
+        .run_jj_in(&main_path, ["workspace", "add", "../secondary"])
+        .success();
+    test_env
+        .run_jj_in(&main_path, ["workspace", "add", "../third"])
+        .success();
+    let output = test_env.run_jj_in(&main_path, ["workspace", "forget", "--missing"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    std::fs::remove_dir_all(&secondary_path).unwrap();
+    let output = test_env.run_jj_in(&main_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    default: qpvuntsm 230dd059 (empty) (no description set)
+    secondary: uuqppmxq 57d63245 (empty) (no description set) (missing: $TEST_ENV/secondary)
+    third: pmmvwywv 44a7931a (empty) (no description set)
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&main_path, ["workspace", "forget", "--missing"]);
+    insta::assert_snapshot!(output, @"");
+    let output = test_env.run_jj_in(&main_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    default: qpvuntsm 230dd059 (empty) (no description set)
+    third: pmmvwywv 44a7931a (empty) (no description set)
+    [EOF]
+    ");
+}
+
+/// Test reattaching workspaces after moving them
+#[test]
+fn test_workspaces_repair() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    let main_path = test_env.env_root().join("main");
+    let secondary_path = test_env.env_root().join("secondary");
+
+    test_env
+        .run_jj_in(&main_path, ["workspace", "add", "../secondary"])
+        .success();
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Nothing changed.
+    [EOF]
+    ");
+
+    // The secondary workspace can no longer find the moved repo
+    let moved_main_path = test_env.env_root().join("moved-main");
+    std::fs::rename(&main_path, &moved_main_path).unwrap();
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: The repository directory at $TEST_ENV/main/.jj/repo is missing. Was it moved?
+    Hint: Run `jj workspace repair` in the workspace containing the repository to reattach this workspace.
+    [EOF]
+    [exit status: 1]
+    ");
+    let output = test_env.run_jj_in(&moved_main_path, ["workspace", "repair"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Reattached workspace secondary at $TEST_ENV/secondary
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&secondary_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    default: qpvuntsm 230dd059 (empty) (no description set)
+    secondary: uuqppmxq 57d63245 (empty) (no description set)
+    [EOF]
+    ");
+
+    // A moved secondary workspace is reported as missing until it's reattached
+    let moved_secondary_path = test_env.env_root().join("moved-secondary");
+    std::fs::rename(&secondary_path, &moved_secondary_path).unwrap();
+    std::fs::rename(&moved_main_path, &main_path).unwrap();
+    let output = test_env.run_jj_in(&main_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    default: qpvuntsm 230dd059 (empty) (no description set)
+    secondary: uuqppmxq 57d63245 (empty) (no description set) (missing: $TEST_ENV/secondary)
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair", "../moved-secondary"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Reattached workspace secondary at $TEST_ENV/moved-secondary
+    [EOF]
+    ");
+    let output = test_env.run_jj_in(&main_path, ["workspace", "list"]);
+    insta::assert_snapshot!(output, @r"
+    default: qpvuntsm 230dd059 (empty) (no description set)
+    secondary: uuqppmxq 57d63245 (empty) (no description set)
+    [EOF]
+    ");
+
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair", "."]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Error: No workspace that can be reattached at .
+    [EOF]
+    [exit status: 1]
+    ");
+}
+
+/// Test that workspaces of other repos aren't reattached
+#[test]
+fn test_workspaces_repair_other_repo() {
+    let test_env = TestEnvironment::default();
+    test_env.run_jj_in(".", ["git", "init", "main"]).success();
+    test_env.run_jj_in(".", ["git", "init", "other"]).success();
+    let main_path = test_env.env_root().join("main");
+    let other_path = test_env.env_root().join("other");
+    test_env
+        .run_jj_in(&main_path, ["workspace", "add", "../secondary"])
+        .success();
+    test_env
+        .run_jj_in(
+            &other_path,
+            [
+                "workspace",
+                "add",
+                "--name",
+                "secondary",
+                "../other-secondary",
+            ],
+        )
+        .success();
+    let repo_pointer_path = test_env
+        .env_root()
+        .join("other-secondary")
+        .join(".jj")
+        .join("repo");
+    let repo_pointer = std::fs::read(&repo_pointer_path).unwrap();
+
+    // The workspace name is tracked in the main repo, but the workspace
+    // belongs to the other repo
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair", "../other-secondary"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Workspace secondary at ../other-secondary belongs to another repo
+    Nothing changed.
+    [EOF]
+    ");
+    assert_eq!(std::fs::read(&repo_pointer_path).unwrap(), repo_pointer);
+
+    // The workspace name isn't tracked in the main repo
+    test_env
+        .run_jj_in(
+            &test_env.env_root().join("other-secondary"),
+            ["workspace", "rename", "third"],
+        )
+        .success();
+    let output = test_env.run_jj_in(&main_path, ["workspace", "repair", "../other-secondary"]);
+    insta::assert_snapshot!(output, @r"
+    ------- stderr -------
+    Warning: Workspace third at ../other-secondary is not tracked in the repo
+    Nothing changed.
+    [EOF]
+    ");
+    assert_eq!(std::fs::read(&repo_pointer_path).unwrap(), repo_pointer);
+}
+
 /// Test context of commit summary template
 #[test]
 fn test_list_workspaces_template() {
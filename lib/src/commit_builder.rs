@@ -28,6 +28,7 @@ use crate::commit::is_backend_commit_empty;
 use crate::commit::Commit;
 use crate::repo::MutableRepo;
 use crate::repo::Repo;
+use crate::resolution_cache::record_resolved_conflicts;
 use crate::settings::JJRng;
 use crate::settings::SignSettings;
 use crate::settings::UserSettings;
@@ -353,6 +354,7 @@ impl DetachedCommitBuilder {
         if let Some(rewrite_source) = self.rewrite_source {
             if rewrite_source.change_id() == commit.change_id() {
                 mut_repo.set_rewritten_commit(rewrite_source.id().clone(), commit.id().clone());
+                record_resolutions(&self.store, &rewrite_source, &commit)?;
             }
         }
         Ok(commit)
@@ -400,3 +402,25 @@ fn write_to_store(
         .write_commit(commit, should_sign.then_some(&mut &sign_fn))
         .block_on()
 }
+
+/// Records how conflicts in `old_commit` were resolved in `new_commit`, if
+/// reusing resolutions is enabled. Only in-place edits are considered, since
+/// a conflict may also disappear because the commit was rebased.
+fn record_resolutions(
+    store: &Arc<Store>,
+    old_commit: &Commit,
+    new_commit: &Commit,
+) -> BackendResult<()> {
+    let Some(cache) = store.resolution_cache() else {
+        return Ok(());
+    };
+    if old_commit.parent_ids() != new_commit.parent_ids() || !old_commit.has_conflict()? {
+        return Ok(());
+    }
+    let old_tree = old_commit.tree()?;
+    let new_tree = new_commit.tree()?;
+    if let Err(err) = record_resolved_conflicts(cache, store, &old_tree, &new_tree) {
+        tracing::warn!(?err, "failed to record conflict resolutions");
+    }
+    Ok(())
+}
@@ -14,6 +14,8 @@
 
 //! Post-processing functions for [`StackedConfig`].
 
+use std::collections::HashMap;
+use std::env;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -44,9 +46,15 @@ pub struct ConfigResolutionContext<'a> {
     pub home_dir: Option<&'a Path>,
     /// Repository path, which is usually `<workspace_root>/.jj/repo`.
     pub repo_path: Option<&'a Path>,
+    /// Workspace name.
+    pub workspace_name: Option<&'a str>,
     /// Space-separated subcommand. `jj file show ...` should result in `"file
     /// show"`.
     pub command: Option<&'a str>,
+    /// Hostname of the current machine.
+    pub hostname: &'a str,
+    /// Environment variables.
+    pub environment: &'a HashMap<String, String>,
 }
 
 /// Conditions to enable the parent table.
@@ -66,7 +74,18 @@ struct ScopeCondition {
     /// - `--when.commands = ["foo bar"]` -> matches "foo bar", "foo bar baz",
     ///   NOT "foo"
     pub commands: Option<Vec<String>>,
-    // TODO: maybe add "workspaces"?
+    /// Workspace names to match exactly.
+    pub workspaces: Option<Vec<String>>,
+    /// Platforms to match. Either the operating system (e.g. `"linux"`,
+    /// `"macos"`, `"windows"`) or the family (`"unix"`, `"windows"`).
+    pub platforms: Option<Vec<String>>,
+    /// Hostnames to match exactly.
+    pub hostnames: Option<Vec<String>>,
+    /// Environment variables to match.
+    /// - `--when.environments = ["NAME"]` -> matches if `NAME` is set
+    /// - `--when.environments = ["NAME=value"]` -> matches if `NAME` is set to
+    ///   `value`
+    pub environments: Option<Vec<String>>,
 }
 
 impl ScopeCondition {
@@ -93,7 +112,11 @@ impl ScopeCondition {
 
     fn matches(&self, context: &ConfigResolutionContext) -> bool {
         matches_path_prefix(self.repositories.as_deref(), context.repo_path)
+            && matches_workspace(self.workspaces.as_deref(), context.workspace_name)
             && matches_command(self.commands.as_deref(), context.command)
+            && matches_platform(self.platforms.as_deref())
+            && matches_hostname(self.hostnames.as_deref(), context.hostname)
+            && matches_environment(self.environments.as_deref(), context.environment)
     }
 }
 
@@ -127,6 +150,37 @@ fn matches_command(candidates: Option<&[String]>, actual: Option<&str>) -> bool
     }
 }
 
+fn matches_workspace(candidates: Option<&[String]>, actual: Option<&str>) -> bool {
+    match (candidates, actual) {
+        (Some(candidates), Some(actual)) => candidates.iter().any(|candidate| candidate == actual),
+        (Some(_), None) => false, // workspace name not known
+        (None, _) => true,
+    }
+}
+
+fn matches_platform(candidates: Option<&[String]>) -> bool {
+    candidates.is_none_or(|candidates| {
+        candidates
+            .iter()
+            .any(|candidate| candidate == env::consts::OS || candidate == env::consts::FAMILY)
+    })
+}
+
+fn matches_hostname(candidates: Option<&[String]>, actual: &str) -> bool {
+    candidates.is_none_or(|candidates| candidates.iter().any(|candidate| candidate == actual))
+}
+
+fn matches_environment(candidates: Option<&[String]>, actual: &HashMap<String, String>) -> bool {
+    candidates.is_none_or(|candidates| {
+        candidates
+            .iter()
+            .any(|candidate| match candidate.split_once('=') {
+                Some((name, value)) => actual.get(name).is_some_and(|v| v == value),
+                None => actual.contains_key(candidate),
+            })
+    })
+}
+
 /// Evaluates condition for each layer and scope, flattens scoped tables.
 /// Returns new config that only contains enabled layers and tables.
 pub fn resolve(
@@ -400,10 +454,23 @@ fn migrate_layer(
 mod tests {
     use assert_matches::assert_matches;
     use indoc::indoc;
+    use once_cell::sync::Lazy;
 
     use super::*;
     use crate::config::ConfigSource;
 
+    fn empty_context() -> ConfigResolutionContext<'static> {
+        static EMPTY_ENVIRONMENT: Lazy<HashMap<String, String>> = Lazy::new(HashMap::new);
+        ConfigResolutionContext {
+            home_dir: None,
+            repo_path: None,
+            workspace_name: None,
+            command: None,
+            hostname: "",
+            environment: &EMPTY_ENVIRONMENT,
+        }
+    }
+
     #[test]
     fn test_expand_home() {
         let home_dir = Some(Path::new("/home/dir"));
@@ -428,12 +495,14 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: None,
+            ..empty_context()
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo")),
             command: None,
+            ..empty_context()
         };
         assert!(condition.matches(&context));
     }
@@ -442,37 +511,42 @@ mod tests {
     fn test_condition_repo_path() {
         let condition = ScopeCondition {
             repositories: Some(["/foo", "/bar"].map(PathBuf::from).into()),
-            commands: None,
+            ..Default::default()
         };
 
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: None,
             command: None,
+            ..empty_context()
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo")),
             command: None,
+            ..empty_context()
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/fooo")),
             command: None,
+            ..empty_context()
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/foo/baz")),
             command: None,
+            ..empty_context()
         };
         assert!(condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new("/bar")),
             command: None,
+            ..empty_context()
         };
         assert!(condition.matches(&context));
     }
@@ -481,31 +555,35 @@ mod tests {
     fn test_condition_repo_path_windows() {
         let condition = ScopeCondition {
             repositories: Some(["c:/foo", r"d:\bar/baz"].map(PathBuf::from).into()),
-            commands: None,
+            ..Default::default()
         };
 
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"c:\foo")),
             command: None,
+            ..empty_context()
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"c:\foo\baz")),
             command: None,
+            ..empty_context()
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"d:\foo")),
             command: None,
+            ..empty_context()
         };
         assert!(!condition.matches(&context));
         let context = ConfigResolutionContext {
             home_dir: None,
             repo_path: Some(Path::new(r"d:/bar\baz")),
             command: None,
+            ..empty_context()
         };
         assert_eq!(condition.matches(&context), cfg!(windows));
     }
@@ -524,6 +602,7 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 2);
@@ -561,6 +640,7 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 7);
@@ -600,6 +680,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: None,
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 1);
@@ -609,6 +690,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 3);
@@ -620,6 +702,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/bar/.jj/repo")),
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 2);
@@ -630,6 +713,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/home/dir/baz/.jj/repo")),
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 2);
@@ -660,6 +744,7 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 1);
@@ -669,6 +754,7 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: Some("foo"),
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 3);
@@ -680,6 +766,7 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: Some("bar"),
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 2);
@@ -690,6 +777,7 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: Some("foo baz"),
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 4);
@@ -703,6 +791,7 @@ mod tests {
             home_dir: None,
             repo_path: None,
             command: Some("fooqux"),
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 1);
@@ -724,6 +813,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: None,
             command: None,
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 1);
@@ -734,6 +824,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo")),
             command: Some("other"),
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 1);
@@ -744,6 +835,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/qux")),
             command: Some("ABC"),
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 1);
@@ -754,6 +846,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/bar")),
             command: Some("DEF"),
+            ..empty_context()
         };
         let resolved_config = resolve(&source_config, &context).unwrap();
         assert_eq!(resolved_config.layers().len(), 2);
@@ -761,6 +854,141 @@ mod tests {
         insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.1'");
     }
 
+    #[test]
+    fn test_resolve_workspace_name() {
+        let mut source_config = StackedConfig::empty();
+        source_config.add_layer(new_user_layer(indoc! {"
+            a = 'a #0'
+            [[--scope]]
+            --when.workspaces = ['release']
+            a = 'a #0.1 release'
+            [[--scope]]
+            --when.repositories = ['/foo']
+            --when.workspaces = ['default', 'dev']
+            a = 'a #0.2 default|dev'
+        "}));
+
+        // workspaces of the same repo share the repo path
+        let context = ConfigResolutionContext {
+            repo_path: Some(Path::new("/foo/.jj/repo")),
+            workspace_name: Some("release"),
+            ..empty_context()
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 2);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.1 release'");
+
+        let context = ConfigResolutionContext {
+            repo_path: Some(Path::new("/foo/.jj/repo")),
+            workspace_name: Some("dev"),
+            ..empty_context()
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 2);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.2 default|dev'");
+
+        let context = ConfigResolutionContext {
+            repo_path: Some(Path::new("/bar/.jj/repo")),
+            workspace_name: Some("dev"),
+            ..empty_context()
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 1);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+
+        let context = empty_context();
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 1);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+    }
+
+    #[test]
+    fn test_resolve_platform() {
+        let mut source_config = StackedConfig::empty();
+        source_config.add_layer(new_user_layer(indoc! {"
+            a = 'a #0'
+            [[--scope]]
+            --when.platforms = ['unix']
+            a = 'a #0.1 unix'
+            [[--scope]]
+            --when.platforms = ['windows']
+            a = 'a #0.2 windows'
+            [[--scope]]
+            --when.platforms = ['linux', 'macos']
+            a = 'a #0.3 linux|macos'
+        "}));
+
+        let context = empty_context();
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        let values = resolved_config
+            .layers()
+            .iter()
+            .map(|layer| layer.data.to_string())
+            .collect_vec();
+        assert_eq!(values[0].trim_end(), "a = 'a #0'");
+        assert_eq!(
+            values.contains(&"a = 'a #0.1 unix'\n".to_owned()),
+            cfg!(unix)
+        );
+        assert_eq!(
+            values.contains(&"a = 'a #0.2 windows'\n".to_owned()),
+            cfg!(windows)
+        );
+        assert_eq!(
+            values.contains(&"a = 'a #0.3 linux|macos'\n".to_owned()),
+            cfg!(any(target_os = "linux", target_os = "macos"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_hostname_and_environment() {
+        let mut source_config = StackedConfig::empty();
+        source_config.add_layer(new_user_layer(indoc! {"
+            a = 'a #0'
+            [[--scope]]
+            --when.hostnames = ['host-a', 'host-b']
+            a = 'a #0.1 host-a|host-b'
+            [[--scope]]
+            --when.environments = ['CI']
+            a = 'a #0.2 CI'
+            [[--scope]]
+            --when.environments = ['CI=true', 'RELEASE=1']
+            a = 'a #0.3 CI=true|RELEASE=1'
+        "}));
+
+        let context = ConfigResolutionContext {
+            hostname: "host-c",
+            ..empty_context()
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 1);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+
+        let environment = HashMap::from([("CI".to_owned(), "false".to_owned())]);
+        let context = ConfigResolutionContext {
+            hostname: "host-b",
+            environment: &environment,
+            ..empty_context()
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 3);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.1 host-a|host-b'");
+        insta::assert_snapshot!(resolved_config.layers()[2].data, @"a = 'a #0.2 CI'");
+
+        let environment = HashMap::from([("RELEASE".to_owned(), "1".to_owned())]);
+        let context = ConfigResolutionContext {
+            environment: &environment,
+            ..empty_context()
+        };
+        let resolved_config = resolve(&source_config, &context).unwrap();
+        assert_eq!(resolved_config.layers().len(), 2);
+        insta::assert_snapshot!(resolved_config.layers()[0].data, @"a = 'a #0'");
+        insta::assert_snapshot!(resolved_config.layers()[1].data, @"a = 'a #0.3 CI=true|RELEASE=1'");
+    }
+
     #[test]
     fn test_resolve_invalid_condition() {
         let new_config = |text: &str| {
@@ -772,6 +1000,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
             command: None,
+            ..empty_context()
         };
         assert_matches!(
             resolve(&new_config("--when.repositories = 0"), &context),
@@ -790,6 +1019,7 @@ mod tests {
             home_dir: Some(Path::new("/home/dir")),
             repo_path: Some(Path::new("/foo/.jj/repo")),
             command: None,
+            ..empty_context()
         };
         assert_matches!(
             resolve(&new_config("[--scope]"), &context),
@@ -256,6 +256,9 @@ pub enum ConflictMarkerStyle {
     Snapshot,
     /// Style which replicates Git's "diff3" style to support external tools.
     Git,
+    /// Style which replicates Git's "zdiff3" style, which is like "diff3" but
+    /// moves lines common to both sides out of the conflict.
+    Zdiff3,
 }
 
 /// Characters which can be repeated to form a conflict marker line when
@@ -461,6 +464,19 @@ fn materialize_conflict_hunks(
                         output,
                     )?;
                 }
+                (ConflictMarkerStyle::Zdiff3, [left, base, right]) => {
+                    let (prefix_len, suffix_len) = common_line_prefix_suffix_len(left, right);
+                    output.write_all(&left[..prefix_len])?;
+                    materialize_git_style_conflict(
+                        &left[prefix_len..left.len() - suffix_len],
+                        base,
+                        &right[prefix_len..right.len() - suffix_len],
+                        &conflict_info,
+                        conflict_marker_len,
+                        output,
+                    )?;
+                    output.write_all(&left[left.len() - suffix_len..])?;
+                }
                 _ => {
                     materialize_jj_style_conflict(
                         hunk,
@@ -519,6 +535,32 @@ fn materialize_git_style_conflict(
     Ok(())
 }
 
+/// Returns the lengths in bytes of the lines at the start and at the end which
+/// are the same in `left` and `right`. The common prefix and suffix don't
+/// overlap.
+fn common_line_prefix_suffix_len(left: &[u8], right: &[u8]) -> (usize, usize) {
+    let left_lines = left.lines_with_terminator().collect_vec();
+    let right_lines = right.lines_with_terminator().collect_vec();
+    let num_prefix_lines = zip(&left_lines, &right_lines)
+        .take_while(|(left_line, right_line)| left_line == right_line)
+        .count();
+    let num_suffix_lines = zip(
+        left_lines[num_prefix_lines..].iter().rev(),
+        right_lines[num_prefix_lines..].iter().rev(),
+    )
+    .take_while(|(left_line, right_line)| left_line == right_line)
+    .count();
+    let prefix_len = left_lines[..num_prefix_lines]
+        .iter()
+        .map(|line| line.len())
+        .sum();
+    let suffix_len = left_lines[left_lines.len() - num_suffix_lines..]
+        .iter()
+        .map(|line| line.len())
+        .sum();
+    (prefix_len, suffix_len)
+}
+
 fn materialize_jj_style_conflict(
     hunk: &Merge<BString>,
     conflict_info: &str,
@@ -910,6 +952,93 @@ fn parse_git_style_conflict_hunk(input: &[u8], expected_marker_len: usize) -> Me
     }
 }
 
+/// Moves the lines which the "zdiff3" style materialized outside of 2-sided
+/// conflicts back into the sides of the parsed `hunks`, so that the base isn't
+/// changed by parsing.
+///
+/// The number of common lines is computed from the `original` conflict. If the
+/// parsed conflicts no longer line up with the original ones, the common lines
+/// are left outside of the conflicts, as if they were added to all terms.
+fn restore_zdiff3_common_lines(hunks: &mut Vec<Merge<BString>>, original: &Merge<BString>) {
+    let MergeResult::Conflict(original_hunks) = files::merge(original) else {
+        return;
+    };
+    let common_lines = original_hunks
+        .iter()
+        .filter(|hunk| !hunk.is_resolved())
+        .map(|hunk| match hunk.as_slice() {
+            [left, _base, right] => {
+                let (prefix_len, suffix_len) = common_line_prefix_suffix_len(left, right);
+                let num_prefix_lines = left[..prefix_len].lines_with_terminator().count();
+                let num_suffix_lines = left[left.len() - suffix_len..]
+                    .lines_with_terminator()
+                    .count();
+                (num_prefix_lines, num_suffix_lines)
+            }
+            _ => (0, 0),
+        })
+        .collect_vec();
+    let conflict_indices = hunks
+        .iter()
+        .positions(|hunk| !hunk.is_resolved())
+        .collect_vec();
+    if conflict_indices.len() != common_lines.len() {
+        return;
+    }
+
+    for (index, (num_prefix_lines, num_suffix_lines)) in zip(conflict_indices, common_lines) {
+        if num_prefix_lines > 0 && index > 0 {
+            if let Some(split) = hunks[index - 1]
+                .as_resolved()
+                .and_then(|resolved| split_last_lines(resolved, num_prefix_lines))
+            {
+                let mut resolved = hunks[index - 1].as_resolved().unwrap().clone();
+                let common = resolved.split_off(split);
+                hunks[index - 1] = Merge::resolved(resolved);
+                hunks[index] = add_to_sides(&hunks[index], &common, b"");
+            }
+        }
+        if num_suffix_lines > 0 && index + 1 < hunks.len() {
+            if let Some(split) = hunks[index + 1]
+                .as_resolved()
+                .and_then(|resolved| split_first_lines(resolved, num_suffix_lines))
+            {
+                let mut common = hunks[index + 1].as_resolved().unwrap().clone();
+                let resolved = common.split_off(split);
+                hunks[index + 1] = Merge::resolved(resolved.into());
+                hunks[index] = add_to_sides(&hunks[index], b"", &common);
+            }
+        }
+    }
+    hunks.retain(|hunk| hunk.as_resolved().is_none_or(|content| !content.is_empty()));
+}
+
+/// Returns the position where the last `num_lines` lines of `content` start.
+fn split_last_lines(content: &[u8], num_lines: usize) -> Option<usize> {
+    let lines = content.lines_with_terminator().collect_vec();
+    let num_kept_lines = lines.len().checked_sub(num_lines)?;
+    Some(lines[..num_kept_lines].iter().map(|line| line.len()).sum())
+}
+
+/// Returns the position where the first `num_lines` lines of `content` end.
+fn split_first_lines(content: &[u8], num_lines: usize) -> Option<usize> {
+    let lines = content.lines_with_terminator().collect_vec();
+    if lines.len() < num_lines {
+        return None;
+    }
+    Some(lines[..num_lines].iter().map(|line| line.len()).sum())
+}
+
+/// Surrounds the sides of a 2-sided conflict hunk with `prefix` and `suffix`,
+/// leaving the base unchanged.
+fn add_to_sides(hunk: &Merge<BString>, prefix: &[u8], suffix: &[u8]) -> Merge<BString> {
+    let [left, base, right] = hunk.as_slice() else {
+        return hunk.clone();
+    };
+    let surround = |side: &BString| -> BString { [prefix, side, suffix].concat().into() };
+    Merge::from_vec(vec![surround(left), base.clone(), surround(right)])
+}
+
 /// Parses conflict markers in `content` and returns an updated version of
 /// `file_ids` with the new contents. If no (valid) conflict markers remain, a
 /// single resolves `FileId` will be returned.
@@ -953,6 +1082,10 @@ pub async fn update_from_content(
         return Ok(Merge::normal(file_id));
     };
 
+    if conflict_marker_style == ConflictMarkerStyle::Zdiff3 {
+        restore_zdiff3_common_lines(&mut hunks, &merge_hunk);
+    }
+
     // If there is a conflict at the end of the file and a term ends with a newline,
     // check whether the original term ended with a newline. If it didn't, then
     // remove the newline since it was added automatically when materializing.
@@ -0,0 +1,254 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Bloom filters of the paths changed by commits.
+//!
+//! Like Git's commit-graph changed-path filters, each commit gets a filter
+//! containing the paths it changed compared to its (merged) parents, and all
+//! ancestor directories of these paths. A filter can tell that a commit
+//! definitely didn't change a path, which lets the revset engine skip diffing
+//! the trees of most commits when evaluating `files()`.
+
+use std::collections::BTreeSet;
+use std::iter;
+
+use blake2::Blake2b512;
+use digest::Digest as _;
+use futures::StreamExt as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+
+use crate::backend::BackendResult;
+use crate::commit::Commit;
+use crate::fileset::FilePattern;
+use crate::fileset::FilesetExpression;
+use crate::index::Index;
+use crate::matchers::EverythingMatcher;
+use crate::repo_path::RepoPath;
+use crate::rewrite;
+
+/// Commits changing more paths (including ancestor directories) than this
+/// don't get a filter. They're always checked by diffing trees.
+const MAX_CHANGED_PATHS: usize = 512;
+const BITS_PER_PATH: usize = 10;
+const MIN_FILTER_BYTES: usize = 8;
+const NUM_HASHES: u64 = 7;
+
+/// Hash of a path, from which the bit positions in a filter are derived.
+#[derive(Clone, Copy, Debug)]
+struct PathHash(u64, u64);
+
+impl PathHash {
+    fn new(path: &RepoPath) -> Self {
+        let digest = Blake2b512::digest(path.as_internal_file_string().as_bytes());
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().unwrap());
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().unwrap());
+        PathHash(h1, h2)
+    }
+
+    fn bit_positions(self, num_bits: u64) -> impl Iterator<Item = u64> {
+        let PathHash(h1, h2) = self;
+        (0..NUM_HASHES).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+/// Builds filter data containing the given paths and their ancestor
+/// directories. Returns `None` if there are too many paths.
+pub(super) fn build_filter<'a>(paths: impl IntoIterator<Item = &'a RepoPath>) -> Option<Vec<u8>> {
+    let mut all_paths = BTreeSet::new();
+    for path in paths {
+        all_paths.extend(
+            iter::successors(Some(path), |path| path.parent()).filter(|path| !path.is_root()),
+        );
+        if all_paths.len() > MAX_CHANGED_PATHS {
+            return None;
+        }
+    }
+    if all_paths.is_empty() {
+        return Some(vec![]);
+    }
+    let num_bytes = (all_paths.len() * BITS_PER_PATH)
+        .div_ceil(8)
+        .max(MIN_FILTER_BYTES);
+    let mut data = vec![0; num_bytes];
+    let num_bits = u64::try_from(num_bytes * 8).unwrap();
+    for path in all_paths {
+        for bit in PathHash::new(path).bit_positions(num_bits) {
+            data[(bit / 8) as usize] |= 1 << (bit % 8);
+        }
+    }
+    Some(data)
+}
+
+/// Computes the filter of the paths changed by `commit`. The parents of the
+/// commit must be indexed.
+pub(super) fn compute_filter(index: &dyn Index, commit: &Commit) -> BackendResult<Option<Vec<u8>>> {
+    let store = commit.store();
+    let parents: Vec<_> = commit.parents().try_collect()?;
+    let from_tree = rewrite::merge_commit_trees_no_resolve_without_repo(store, index, &parents)?;
+    let to_tree = commit.tree()?;
+    let mut changed_paths = vec![];
+    let mut tree_diff = from_tree.diff_stream(&to_tree, &EverythingMatcher);
+    async {
+        while let Some(entry) = tree_diff.next().await {
+            entry.values?;
+            changed_paths.push(entry.path);
+            if changed_paths.len() > MAX_CHANGED_PATHS {
+                break;
+            }
+        }
+        Ok::<_, crate::backend::BackendError>(())
+    }
+    .block_on()?;
+    Ok(build_filter(changed_paths.iter().map(AsRef::as_ref)))
+}
+
+/// Set of paths to look up in changed-path filters.
+#[derive(Clone, Debug)]
+pub(super) struct ChangedPathQuery {
+    hashes: Vec<PathHash>,
+}
+
+impl ChangedPathQuery {
+    /// Builds query that matches commits which may have changed any path
+    /// matched by the `expression`. Returns `None` if the filters can't tell
+    /// anything about the `expression`, e.g. if it matches everything.
+    pub(super) fn from_fileset(expression: &FilesetExpression) -> Option<Self> {
+        let paths = query_paths(expression)?;
+        let hashes = paths.iter().map(|path| PathHash::new(path)).collect();
+        Some(ChangedPathQuery { hashes })
+    }
+
+    /// Returns false if the commit of the `filter` definitely didn't change
+    /// any of the queried paths.
+    pub(super) fn may_match(&self, filter: &[u8]) -> bool {
+        if filter.is_empty() {
+            return false;
+        }
+        let num_bits = u64::try_from(filter.len() * 8).unwrap();
+        self.hashes.iter().any(|hash| {
+            hash.bit_positions(num_bits)
+                .all(|bit| filter[(bit / 8) as usize] & (1 << (bit % 8)) != 0)
+        })
+    }
+}
+
+/// Collects paths such that any path matched by the `expression` is one of
+/// them or lives under one of them.
+fn query_paths(expression: &FilesetExpression) -> Option<Vec<&RepoPath>> {
+    match expression {
+        FilesetExpression::None => Some(vec![]),
+        FilesetExpression::All => None,
+        FilesetExpression::Pattern(pattern) => {
+            let path = match pattern {
+                FilePattern::FilePath(path) => path,
+                FilePattern::PrefixPath(path) => path,
+                FilePattern::FileGlob { dir, .. } => dir,
+            };
+            (!path.is_root()).then(|| vec![path.as_ref()])
+        }
+        FilesetExpression::UnionAll(expressions) => {
+            let mut paths = vec![];
+            for expression in expressions {
+                paths.extend(query_paths(expression)?);
+            }
+            Some(paths)
+        }
+        FilesetExpression::Intersection(expression1, expression2) => {
+            query_paths(expression1).or_else(|| query_paths(expression2))
+        }
+        FilesetExpression::Difference(expression1, _) => query_paths(expression1),
+    }
+}
+
+/// Returns false if the `path` is definitely not in the `filter`.
+#[cfg(test)]
+pub(super) fn filter_may_contain(filter: &[u8], path: &RepoPath) -> bool {
+    let query = ChangedPathQuery {
+        hashes: vec![PathHash::new(path)],
+    };
+    query.may_match(filter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repo_path::RepoPathBuf;
+
+    fn repo_path(value: &str) -> &RepoPath {
+        RepoPath::from_internal_string(value)
+    }
+
+    #[test]
+    fn test_build_filter() {
+        assert_eq!(build_filter([]), Some(vec![]));
+        assert!(!filter_may_contain(&[], repo_path("a")));
+
+        let filter = build_filter([repo_path("dir/sub/file"), repo_path("file")]).unwrap();
+        assert_eq!(filter.len(), MIN_FILTER_BYTES);
+        assert!(filter_may_contain(&filter, repo_path("dir/sub/file")));
+        assert!(filter_may_contain(&filter, repo_path("dir/sub")));
+        assert!(filter_may_contain(&filter, repo_path("dir")));
+        assert!(filter_may_contain(&filter, repo_path("file")));
+
+        let paths = (0..MAX_CHANGED_PATHS)
+            .map(|i| RepoPathBuf::from_internal_string(format!("file{i}")))
+            .collect_vec();
+        let filter = build_filter(paths.iter().map(AsRef::as_ref)).unwrap();
+        assert_eq!(filter.len(), MAX_CHANGED_PATHS * BITS_PER_PATH / 8);
+        assert!(paths.iter().all(|path| filter_may_contain(&filter, path)));
+        // False positives are rare
+        let num_false_positives = (0..1000)
+            .filter(|i| filter_may_contain(&filter, repo_path(&format!("other{i}"))))
+            .count();
+        assert!(num_false_positives < 50, "{num_false_positives}");
+
+        let paths = (0..=MAX_CHANGED_PATHS)
+            .map(|i| RepoPathBuf::from_internal_string(format!("file{i}")))
+            .collect_vec();
+        assert_eq!(build_filter(paths.iter().map(AsRef::as_ref)), None);
+    }
+
+    #[test]
+    fn test_query_paths() {
+        let file = || FilesetExpression::file_path(repo_path("dir/file").to_owned());
+        let prefix = || FilesetExpression::prefix_path(repo_path("dir2").to_owned());
+        let root = || FilesetExpression::prefix_path(RepoPathBuf::root());
+        assert_eq!(query_paths(&FilesetExpression::none()), Some(vec![]));
+        assert_eq!(query_paths(&FilesetExpression::all()), None);
+        assert_eq!(query_paths(&root()), None);
+        assert_eq!(
+            query_paths(&FilesetExpression::union_all(vec![file(), prefix()])),
+            Some(vec![repo_path("dir/file"), repo_path("dir2")])
+        );
+        assert_eq!(
+            query_paths(&FilesetExpression::union_all(vec![file(), root()])),
+            None
+        );
+        assert_eq!(
+            query_paths(&FilesetExpression::Intersection(
+                Box::new(FilesetExpression::all()),
+                Box::new(prefix())
+            )),
+            Some(vec![repo_path("dir2")])
+        );
+        assert_eq!(
+            query_paths(&FilesetExpression::Difference(
+                Box::new(prefix()),
+                Box::new(file())
+            )),
+            Some(vec![repo_path("dir2")])
+        );
+    }
+}
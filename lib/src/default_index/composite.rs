@@ -91,6 +91,9 @@ pub(super) trait IndexSegment: Send + Sync {
     fn num_parents(&self, local_pos: LocalPosition) -> u32;
 
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec;
+
+    /// Returns the changed-path filter of the entry, if indexed.
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<&[u8]>;
 }
 
 pub(super) type DynIndexSegment = dyn IndexSegment;
@@ -154,12 +157,16 @@ impl CompositeIndex {
         let mut num_merges = 0;
         let mut max_generation_number = 0;
         let mut change_ids = HashSet::new();
+        let mut num_changed_path_filters = 0;
         for pos in 0..num_commits {
             let entry = self.entry_by_pos(IndexPosition(pos));
             max_generation_number = max(max_generation_number, entry.generation_number());
             if entry.num_parents() > 1 {
                 num_merges += 1;
             }
+            if entry.changed_path_filter().is_some() {
+                num_changed_path_filters += 1;
+            }
             change_ids.insert(entry.change_id());
         }
         let num_heads = u32::try_from(self.all_heads_pos().count()).unwrap();
@@ -179,6 +186,7 @@ impl CompositeIndex {
             max_generation_number,
             num_heads,
             num_changes: change_ids.len().try_into().unwrap(),
+            num_changed_path_filters,
             levels,
         }
     }
@@ -461,6 +469,16 @@ impl Index for &CompositeIndex {
         self.commit_id_to_pos(commit_id).is_some()
     }
 
+    fn generation_number(&self, commit_id: &CommitId) -> Option<u64> {
+        let entry = self.entry_by_id(commit_id)?;
+        Some(entry.generation_number().into())
+    }
+
+    fn topological_position(&self, commit_id: &CommitId) -> Option<u64> {
+        let IndexPosition(pos) = self.commit_id_to_pos(commit_id)?;
+        Some(pos.into())
+    }
+
     fn is_ancestor(&self, ancestor_id: &CommitId, descendant_id: &CommitId) -> bool {
         let ancestor_pos = self.commit_id_to_pos(ancestor_id).unwrap();
         let descendant_pos = self.commit_id_to_pos(descendant_id).unwrap();
@@ -585,6 +603,7 @@ pub struct IndexStats {
     pub max_generation_number: u32,
     pub num_heads: u32,
     pub num_changes: u32,
+    pub num_changed_path_filters: u32,
     pub levels: Vec<IndexLevelStats>,
 }
 
@@ -21,6 +21,7 @@
 
 #![allow(missing_docs)]
 
+mod changed_path;
 mod composite;
 mod entry;
 mod mutable;
@@ -42,6 +43,7 @@ pub use self::readonly::ReadonlyIndexLoadError;
 pub use self::store::DefaultIndexStore;
 pub use self::store::DefaultIndexStoreError;
 pub use self::store::DefaultIndexStoreInitError;
+pub use self::store::IndexCorruption;
 
 #[cfg(test)]
 mod tests {
@@ -597,6 +599,74 @@ mod tests {
         );
     }
 
+    #[test_case(31; "single bucket")]
+    #[test_case(1000; "many buckets")]
+    #[test_case(70_000; "wide positions")]
+    fn lookup_commit_ids_in_saved_segment(num_commits: u32) {
+        let temp_dir = new_temp_dir();
+        let mut new_change_id = change_id_generator();
+        let mut mutable_segment = MutableIndexSegment::full(3, 16);
+
+        // Scatter commit ids over the whole id space like hashes
+        let ids = (1..=num_commits)
+            .map(|n| CommitId::from_bytes(&n.wrapping_mul(0x9e37_79b1).to_be_bytes()[1..]))
+            .collect_vec();
+        for id in &ids {
+            mutable_segment.add_commit_data(id.clone(), new_change_id(), &[]);
+        }
+        let segment = mutable_segment.save_in(temp_dir.path()).unwrap();
+        let sorted_ids = ids.iter().sorted().collect_vec();
+
+        for (pos, id) in ids.iter().enumerate() {
+            let pos = LocalPosition(pos.try_into().unwrap());
+            assert_eq!(segment.commit_id_to_pos(id), Some(pos));
+        }
+
+        let expected_neighbors = |id: &CommitId| {
+            let start = sorted_ids.partition_point(|&other| other < id);
+            let end = sorted_ids.partition_point(|&other| other <= id);
+            let prev = start.checked_sub(1).map(|i| sorted_ids[i].clone());
+            let next = sorted_ids.get(end).map(|&id| id.clone());
+            (prev, next)
+        };
+        let other_ids = ["000000", "7fffff", "800000", "ffffff"].map(CommitId::from_hex);
+        for id in ids.iter().step_by(7).chain(&other_ids) {
+            assert_eq!(
+                segment.resolve_neighbor_commit_ids(id),
+                expected_neighbors(id),
+                "{id}"
+            );
+        }
+
+        let expected_resolution = |prefix: &HexPrefix| {
+            let start = sorted_ids.partition_point(|id| id.as_bytes() < prefix.min_prefix_bytes());
+            let mut matches = sorted_ids[start..]
+                .iter()
+                .take_while(|&&id| prefix.matches(id));
+            match (matches.next(), matches.next()) {
+                (Some(&id), None) => PrefixResolution::SingleMatch(id.clone()),
+                (Some(_), Some(_)) => PrefixResolution::AmbiguousMatch,
+                (None, _) => PrefixResolution::NoMatch,
+            }
+        };
+        let prefixes = ids
+            .iter()
+            .step_by(13)
+            .flat_map(|id| {
+                let hex = id.hex();
+                (1..=hex.len()).map(move |len| hex[..len].to_owned())
+            })
+            .chain(["0", "7ff", "8000", "fffff"].map(str::to_owned));
+        for prefix in prefixes {
+            let prefix = HexPrefix::new(&prefix).unwrap();
+            assert_eq!(
+                segment.resolve_commit_id_prefix(&prefix),
+                expected_resolution(&prefix),
+                "{prefix:?}"
+            );
+        }
+    }
+
     #[test]
     fn resolve_change_id_prefix() {
         let temp_dir = new_temp_dir();
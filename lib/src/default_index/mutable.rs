@@ -31,6 +31,7 @@ use smallvec::smallvec;
 use smallvec::SmallVec;
 use tempfile::NamedTempFile;
 
+use super::changed_path;
 use super::composite::AsCompositeIndex;
 use super::composite::ChangeIdIndexImpl;
 use super::composite::CompositeIndex;
@@ -40,6 +41,7 @@ use super::entry::IndexPosition;
 use super::entry::LocalPosition;
 use super::entry::SmallIndexPositionsVec;
 use super::entry::SmallLocalPositionsVec;
+use super::readonly::CommitLookupLayout;
 use super::readonly::DefaultReadonlyIndex;
 use super::readonly::ReadonlyIndexSegment;
 use super::readonly::INDEX_SEGMENT_FILE_FORMAT_VERSION;
@@ -68,6 +70,7 @@ struct MutableGraphEntry {
     change_id: ChangeId,
     generation_number: u32,
     parent_positions: SmallIndexPositionsVec,
+    changed_path_filter: Option<Vec<u8>>,
 }
 
 pub(super) struct MutableIndexSegment {
@@ -78,6 +81,7 @@ pub(super) struct MutableIndexSegment {
     graph: Vec<MutableGraphEntry>,
     commit_lookup: BTreeMap<CommitId, LocalPosition>,
     change_lookup: BTreeMap<ChangeId, SmallLocalPositionsVec>,
+    index_changed_paths: bool,
 }
 
 impl MutableIndexSegment {
@@ -90,6 +94,7 @@ impl MutableIndexSegment {
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
+            index_changed_paths: false,
         }
     }
 
@@ -97,6 +102,7 @@ impl MutableIndexSegment {
         let num_parent_commits = parent_file.as_composite().num_commits();
         let commit_id_length = parent_file.commit_id_length();
         let change_id_length = parent_file.change_id_length();
+        let index_changed_paths = parent_file.has_changed_path_filters();
         Self {
             parent_file: Some(parent_file),
             num_parent_commits,
@@ -105,26 +111,67 @@ impl MutableIndexSegment {
             graph: vec![],
             commit_lookup: BTreeMap::new(),
             change_lookup: BTreeMap::new(),
+            index_changed_paths,
         }
     }
 
+    /// Creates a full segment containing the commits of the `segment` and all
+    /// of its ancestor segments.
+    pub(super) fn squashed_from(segment: &ReadonlyIndexSegment) -> Self {
+        let mut squashed =
+            MutableIndexSegment::full(segment.commit_id_length(), segment.change_id_length());
+        squashed.index_changed_paths = segment.has_changed_path_filters();
+        let segments = segment
+            .as_composite()
+            .ancestor_index_segments()
+            .collect_vec();
+        for segment in segments.into_iter().rev() {
+            squashed.add_commits_from(segment);
+        }
+        squashed
+    }
+
     pub(super) fn as_composite(&self) -> &CompositeIndex {
         CompositeIndex::new(self)
     }
 
     pub(super) fn add_commit(&mut self, commit: &Commit) {
-        self.add_commit_data(
+        if self.as_composite().has_id(commit.id()) {
+            return;
+        }
+        let changed_path_filter = if self.index_changed_paths {
+            // Commits whose trees can't be read are always checked by
+            // diffing trees, which will report the error if any.
+            changed_path::compute_filter(&self.as_composite(), commit)
+                .ok()
+                .flatten()
+        } else {
+            None
+        };
+        self.add_entry(
             commit.id().clone(),
             commit.change_id().clone(),
             commit.parent_ids(),
+            changed_path_filter,
         );
     }
 
+    #[cfg(test)]
     pub(super) fn add_commit_data(
         &mut self,
         commit_id: CommitId,
         change_id: ChangeId,
         parent_ids: &[CommitId],
+    ) {
+        self.add_entry(commit_id, change_id, parent_ids, None);
+    }
+
+    fn add_entry(
+        &mut self,
+        commit_id: CommitId,
+        change_id: ChangeId,
+        parent_ids: &[CommitId],
+        changed_path_filter: Option<Vec<u8>>,
     ) {
         if self.as_composite().has_id(&commit_id) {
             return;
@@ -134,6 +181,7 @@ impl MutableIndexSegment {
             change_id,
             generation_number: 0,
             parent_positions: SmallVec::new(),
+            changed_path_filter,
         };
         for parent_id in parent_ids {
             let parent_entry = self
@@ -162,7 +210,16 @@ impl MutableIndexSegment {
         for pos in other_segment.num_parent_commits()..other.num_commits() {
             let entry = other.entry_by_pos(IndexPosition(pos));
             let parent_ids = entry.parents().map(|entry| entry.commit_id()).collect_vec();
-            self.add_commit_data(entry.commit_id(), entry.change_id(), &parent_ids);
+            let local_pos = LocalPosition(pos - other_segment.num_parent_commits());
+            let changed_path_filter = other_segment
+                .changed_path_filter(local_pos)
+                .map(|filter| filter.to_vec());
+            self.add_entry(
+                entry.commit_id(),
+                entry.change_id(),
+                &parent_ids,
+                changed_path_filter,
+            );
         }
     }
 
@@ -228,6 +285,8 @@ impl MutableIndexSegment {
         buf.extend(0_u32.to_le_bytes());
         let change_overflow_offset = buf.len();
         buf.extend(0_u32.to_le_bytes());
+        let changed_path_data_offset = buf.len();
+        buf.extend((!0_u32).to_le_bytes());
 
         // Positions of change ids in the sorted table
         let change_id_pos_map: HashMap<&ChangeId, u32> = self
@@ -274,9 +333,29 @@ impl MutableIndexSegment {
             buf.extend_from_slice(entry.commit_id.as_bytes());
         }
 
+        let commit_lookup_layout = CommitLookupLayout::new(num_commits);
+        if commit_lookup_layout.num_buckets() > 0 {
+            let mut bucket_ends = vec![0_u32; commit_lookup_layout.num_buckets()];
+            for commit_id in self.commit_lookup.keys() {
+                bucket_ends[commit_lookup_layout.bucket_of(commit_id.as_bytes())] += 1;
+            }
+            let mut end = 0;
+            for count in bucket_ends {
+                end += count;
+                buf.extend(end.to_le_bytes());
+            }
+        }
+
+        let commit_lookup_base = buf.len();
+        let width = commit_lookup_layout.position_width();
         for LocalPosition(pos) in self.commit_lookup.values() {
-            buf.extend(pos.to_le_bytes());
+            buf.extend_from_slice(&pos.to_le_bytes()[..width]);
         }
+        let commit_lookup_size = buf.len() - commit_lookup_base;
+        buf.resize(
+            commit_lookup_base + commit_lookup_size.next_multiple_of(4),
+            0,
+        );
 
         for change_id in self.change_lookup.keys() {
             assert_eq!(change_id.as_bytes().len(), self.change_id_length);
@@ -312,6 +391,27 @@ impl MutableIndexSegment {
         for LocalPosition(pos) in change_overflow {
             buf.extend(pos.to_le_bytes());
         }
+
+        if self.index_changed_paths {
+            let mut changed_path_data = vec![];
+            for entry in &self.graph {
+                let offset = u32::try_from(changed_path_data.len()).unwrap();
+                buf.extend(offset.to_le_bytes());
+                if let Some(filter) = &entry.changed_path_filter {
+                    let len = u32::try_from(filter.len()).unwrap();
+                    assert!(len < OVERFLOW_FLAG);
+                    buf.extend(len.to_le_bytes());
+                    changed_path_data.extend_from_slice(filter);
+                } else {
+                    buf.extend((!0_u32).to_le_bytes());
+                }
+            }
+            let num_changed_path_bytes = u32::try_from(changed_path_data.len()).unwrap();
+            assert!(num_changed_path_bytes < OVERFLOW_FLAG);
+            buf[changed_path_data_offset..][..4]
+                .copy_from_slice(&num_changed_path_bytes.to_le_bytes());
+            buf.extend(changed_path_data);
+        }
     }
 
     /// If the MutableIndex has more than half the commits of its parent
@@ -341,6 +441,7 @@ impl MutableIndexSegment {
         } else {
             MutableIndexSegment::full(self.commit_id_length, self.change_id_length)
         };
+        squashed.index_changed_paths = self.index_changed_paths;
         for parent_file in files_to_squash.iter().rev() {
             squashed.add_commits_from(parent_file.as_ref());
         }
@@ -453,6 +554,12 @@ impl IndexSegment for MutableIndexSegment {
     fn parent_positions(&self, local_pos: LocalPosition) -> SmallIndexPositionsVec {
         self.graph[local_pos.0 as usize].parent_positions.clone()
     }
+
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<&[u8]> {
+        self.graph[local_pos.0 as usize]
+            .changed_path_filter
+            .as_deref()
+    }
 }
 
 /// In-memory mutable records for the on-disk commit index backend.
@@ -469,6 +576,12 @@ impl DefaultMutableIndex {
         DefaultMutableIndex(mutable_segment)
     }
 
+    /// Builds changed-path filters for the commits to be added. Incremental
+    /// indexes inherit the setting from the parent segment.
+    pub(super) fn enable_changed_path_filters(&mut self) {
+        self.0.index_changed_paths = true;
+    }
+
     #[cfg(test)]
     pub(crate) fn add_commit_data(
         &mut self,
@@ -504,6 +617,14 @@ impl Index for DefaultMutableIndex {
         self.as_composite().has_id(commit_id)
     }
 
+    fn generation_number(&self, commit_id: &CommitId) -> Option<u64> {
+        self.as_composite().generation_number(commit_id)
+    }
+
+    fn topological_position(&self, commit_id: &CommitId) -> Option<u64> {
+        self.as_composite().topological_position(commit_id)
+    }
+
     fn is_ancestor(&self, ancestor_id: &CommitId, descendant_id: &CommitId) -> bool {
         self.as_composite().is_ancestor(ancestor_id, descendant_id)
     }
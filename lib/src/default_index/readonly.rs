@@ -21,6 +21,7 @@ use std::fmt::Formatter;
 use std::fs::File;
 use std::io;
 use std::io::Read;
+use std::ops::Range;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -104,7 +105,7 @@ impl ReadonlyIndexLoadError {
 }
 
 /// Current format version of the index segment file.
-pub(crate) const INDEX_SEGMENT_FILE_FORMAT_VERSION: u32 = 6;
+pub(crate) const INDEX_SEGMENT_FILE_FORMAT_VERSION: u32 = 7;
 
 /// If set, the value is stored in the overflow table.
 pub(crate) const OVERFLOW_FLAG: u32 = 0x8000_0000;
@@ -137,6 +138,64 @@ impl ChangeLocalPosition {
     }
 }
 
+/// Layout of the sorted commit id lookup table, which is derived from the
+/// number of local commits.
+///
+/// The lookup table is split into buckets by the leading bits of the commit
+/// ids, like a fanout table. Since commit ids are hashes, buckets are roughly
+/// equally sized, and a lookup only needs to binary search the entries sharing
+/// the same prefix bits. Positions in the lookup table are stored in the
+/// smallest number of bytes that can represent all local positions. The commit
+/// ids themselves are still stored in full in the graph entries.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(super) struct CommitLookupLayout {
+    radix_bits: u32,
+    position_width: usize,
+}
+
+impl CommitLookupLayout {
+    /// Aim for this number of commits per bucket.
+    const COMMITS_PER_BUCKET: u32 = 16;
+    const MAX_RADIX_BITS: u32 = 16;
+
+    pub(super) fn new(num_commits: u32) -> Self {
+        let radix_bits = (num_commits / Self::COMMITS_PER_BUCKET)
+            .checked_ilog2()
+            .map_or(0, |bits| bits.min(Self::MAX_RADIX_BITS));
+        let position_width = match num_commits {
+            0..=0x100 => 1,
+            0x101..=0x1_0000 => 2,
+            0x1_0001..=0x100_0000 => 3,
+            _ => 4,
+        };
+        CommitLookupLayout {
+            radix_bits,
+            position_width,
+        }
+    }
+
+    /// Number of buckets, or 0 if the lookup table is too small to be split.
+    pub(super) fn num_buckets(&self) -> usize {
+        if self.radix_bits == 0 {
+            0
+        } else {
+            1 << self.radix_bits
+        }
+    }
+
+    /// Returns the bucket which `id_bytes` (or prefix bytes) belongs to.
+    pub(super) fn bucket_of(&self, id_bytes: &[u8]) -> usize {
+        let leading_bytes = [0, 1].map(|i| id_bytes.get(i).copied().unwrap_or(0));
+        let leading_bits = u32::from(u16::from_be_bytes(leading_bytes));
+        (leading_bits >> (16 - self.radix_bits)) as usize
+    }
+
+    /// Number of bytes per lookup table entry.
+    pub(super) fn position_width(&self) -> usize {
+        self.position_width
+    }
+}
+
 struct CommitGraphEntry<'a> {
     data: &'a [u8],
 }
@@ -186,6 +245,8 @@ impl CommitGraphEntry<'_> {
 /// u32: number of local change ids
 /// u32: number of overflow parent entries
 /// u32: number of overflow change id positions
+/// u32: number of changed-path filter bytes, or 0xffff_ffff if changed-path
+///      filters aren't indexed
 /// for each entry, in some topological order with parents first:
 ///   u32: generation number
 ///   if number of parents <= 2:
@@ -198,8 +259,13 @@ impl CommitGraphEntry<'_> {
 ///     u32: (>=0x8000_0000) number of parents (in the overflow table), bit-negated
 ///   u32: change id position in the sorted change ids table
 ///   <commit id length number of bytes>: commit id
+/// if number of local commit entries >= 32:
+///   for each bucket of the leading commit id bits (see `CommitLookupLayout`):
+///     u32: end position of the bucket in the commit id lookup table
 /// for each entry, sorted by commit id:
-///   u32: local position in the graph entries table
+///   <1 to 4 bytes depending on number of local commit entries>: local
+///     position in the graph entries table
+/// <0 to 3 bytes>: padding to align the commit id lookup table to 4 bytes
 /// for each entry, sorted by change id:
 ///   <change id length number of bytes>: change id
 /// for each entry, sorted by change id:
@@ -211,13 +277,18 @@ impl CommitGraphEntry<'_> {
 ///   u32: global index position
 /// for each overflow change id entry:
 ///   u32: local position in the graph entries table
+/// if changed-path filters are indexed:
+///   for each entry, in the same order as the graph entries:
+///     u32: offset of the filter in the changed-path filter data
+///     u32: (< 0x8000_0000) length of the filter in bytes
+///          (==0xffff_ffff) no filter
+///   <number of changed-path filter bytes>: changed-path filter data
 /// ```
 ///
 /// Note that u32 fields are 4-byte aligned so long as the parent file name
 /// (which is hexadecimal hash) and commit/change ids aren't of exotic length.
 // TODO: replace the table by a trie so we don't have to repeat the full commit
 //       ids
-// TODO: add a fanout table like git's commit graph has?
 pub(super) struct ReadonlyIndexSegment {
     parent_file: Option<Arc<ReadonlyIndexSegment>>,
     num_parent_commits: u32,
@@ -228,12 +299,16 @@ pub(super) struct ReadonlyIndexSegment {
     num_local_commits: u32,
     num_local_change_ids: u32,
     num_change_overflow_entries: u32,
+    commit_lookup_layout: CommitLookupLayout,
     // Base data offsets in bytes:
+    commit_radix_base: usize,
     commit_lookup_base: usize,
     change_id_table_base: usize,
     change_pos_table_base: usize,
     parent_overflow_base: usize,
     change_overflow_base: usize,
+    changed_path_table_base: usize,
+    changed_path_data_base: Option<usize>,
     data: Vec<u8>,
 }
 
@@ -329,24 +404,42 @@ impl ReadonlyIndexSegment {
         let num_local_change_ids = read_u32(file)?;
         let num_parent_overflow_entries = read_u32(file)?;
         let num_change_overflow_entries = read_u32(file)?;
+        let num_changed_path_bytes = read_u32(file)?;
         let mut data = vec![];
         file.read_to_end(&mut data).map_err(from_io_err)?;
 
+        let commit_lookup_layout = CommitLookupLayout::new(num_local_commits);
         let commit_graph_entry_size = CommitGraphEntry::size(commit_id_length);
         let graph_size = (num_local_commits as usize) * commit_graph_entry_size;
-        let commit_lookup_size = (num_local_commits as usize) * 4;
+        let commit_radix_size = commit_lookup_layout.num_buckets() * 4;
+        let commit_lookup_size = ((num_local_commits as usize)
+            * commit_lookup_layout.position_width())
+        .next_multiple_of(4);
         let change_id_table_size = (num_local_change_ids as usize) * change_id_length;
         let change_pos_table_size = (num_local_change_ids as usize) * 4;
         let parent_overflow_size = (num_parent_overflow_entries as usize) * 4;
         let change_overflow_size = (num_change_overflow_entries as usize) * 4;
+        let (changed_path_table_size, changed_path_data_size) = if num_changed_path_bytes == !0 {
+            (0, None)
+        } else {
+            (
+                (num_local_commits as usize) * 8,
+                Some(num_changed_path_bytes as usize),
+            )
+        };
 
         let graph_base = 0;
-        let commit_lookup_base = graph_base + graph_size;
+        let commit_radix_base = graph_base + graph_size;
+        let commit_lookup_base = commit_radix_base + commit_radix_size;
         let change_id_table_base = commit_lookup_base + commit_lookup_size;
         let change_pos_table_base = change_id_table_base + change_id_table_size;
         let parent_overflow_base = change_pos_table_base + change_pos_table_size;
         let change_overflow_base = parent_overflow_base + parent_overflow_size;
-        let expected_size = change_overflow_base + change_overflow_size;
+        let changed_path_table_base = change_overflow_base + change_overflow_size;
+        let changed_path_data_base =
+            changed_path_data_size.map(|_| changed_path_table_base + changed_path_table_size);
+        let expected_size =
+            changed_path_table_base + changed_path_table_size + changed_path_data_size.unwrap_or(0);
 
         if data.len() != expected_size {
             return Err(ReadonlyIndexLoadError::invalid_data(
@@ -364,11 +457,15 @@ impl ReadonlyIndexSegment {
             num_local_commits,
             num_local_change_ids,
             num_change_overflow_entries,
+            commit_lookup_layout,
+            commit_radix_base,
             commit_lookup_base,
             change_id_table_base,
             change_pos_table_base,
             parent_overflow_base,
             change_overflow_base,
+            changed_path_table_base,
+            changed_path_data_base,
             data,
         }))
     }
@@ -390,7 +487,7 @@ impl ReadonlyIndexSegment {
     }
 
     fn graph_entry(&self, local_pos: LocalPosition) -> CommitGraphEntry {
-        let table = &self.data[..self.commit_lookup_base];
+        let table = &self.data[..self.commit_radix_base];
         let entry_size = CommitGraphEntry::size(self.commit_id_length);
         let offset = (local_pos.0 as usize) * entry_size;
         CommitGraphEntry {
@@ -398,10 +495,29 @@ impl ReadonlyIndexSegment {
         }
     }
 
+    /// Returns the range of lookup positions where the commit ids sharing the
+    /// leading bits with `id_bytes` are stored.
+    fn commit_lookup_bucket_range(&self, id_bytes: &[u8]) -> Range<u32> {
+        if self.commit_lookup_layout.num_buckets() == 0 {
+            return 0..self.num_local_commits;
+        }
+        let table = &self.data[self.commit_radix_base..self.commit_lookup_base];
+        let bucket_end = |bucket: usize| {
+            let offset = bucket * 4;
+            u32::from_le_bytes(table[offset..][..4].try_into().unwrap())
+        };
+        let bucket = self.commit_lookup_layout.bucket_of(id_bytes);
+        let start = bucket.checked_sub(1).map_or(0, bucket_end);
+        start..bucket_end(bucket)
+    }
+
     fn commit_lookup_pos(&self, lookup_pos: u32) -> LocalPosition {
         let table = &self.data[self.commit_lookup_base..self.change_id_table_base];
-        let offset = (lookup_pos as usize) * 4;
-        LocalPosition(u32::from_le_bytes(table[offset..][..4].try_into().unwrap()))
+        let width = self.commit_lookup_layout.position_width();
+        let offset = (lookup_pos as usize) * width;
+        let mut buf = [0; 4];
+        buf[..width].copy_from_slice(&table[offset..][..width]);
+        LocalPosition(u32::from_le_bytes(buf))
     }
 
     fn change_lookup_id(&self, lookup_pos: u32) -> ChangeId {
@@ -436,16 +552,24 @@ impl ReadonlyIndexSegment {
         &self,
         overflow_pos: u32,
     ) -> impl Iterator<Item = LocalPosition> + use<'_> {
-        let table = &self.data[self.change_overflow_base..];
+        let table = &self.data[self.change_overflow_base..self.changed_path_table_base];
         let offset = (overflow_pos as usize) * 4;
         table[offset..]
             .chunks_exact(4)
             .map(|chunk| LocalPosition(u32::from_le_bytes(chunk.try_into().unwrap())))
     }
 
+    /// Returns true if the segment stores changed-path filters.
+    pub(super) fn has_changed_path_filters(&self) -> bool {
+        self.changed_path_data_base.is_some()
+    }
+
     /// Binary searches commit id by `prefix`. Returns the lookup position.
     fn commit_id_byte_prefix_to_lookup_pos(&self, prefix: &[u8]) -> PositionLookupResult {
-        binary_search_pos_by(self.num_local_commits, |pos| {
+        // Commit ids in the other buckets are either less or greater than the
+        // prefix, so the search can be narrowed down to the bucket.
+        let range = self.commit_lookup_bucket_range(prefix);
+        binary_search_pos_by(range, self.num_local_commits, |pos| {
             let local_pos = self.commit_lookup_pos(pos);
             let entry = self.graph_entry(local_pos);
             entry.commit_id_bytes().cmp(prefix)
@@ -454,7 +578,8 @@ impl ReadonlyIndexSegment {
 
     /// Binary searches change id by `prefix`. Returns the lookup position.
     fn change_id_byte_prefix_to_lookup_pos(&self, prefix: &[u8]) -> PositionLookupResult {
-        binary_search_pos_by(self.num_local_change_ids, |pos| {
+        let size = self.num_local_change_ids;
+        binary_search_pos_by(0..size, size, |pos| {
             let change_id_bytes = self.change_lookup_id_bytes(pos);
             change_id_bytes.cmp(prefix)
         })
@@ -587,6 +712,20 @@ impl IndexSegment for ReadonlyIndexSegment {
             self.overflow_parents(overflow_pos, num_parents)
         }
     }
+
+    fn changed_path_filter(&self, local_pos: LocalPosition) -> Option<&[u8]> {
+        let data_base = self.changed_path_data_base?;
+        let table = &self.data[self.changed_path_table_base..data_base];
+        let entry = &table[(local_pos.0 as usize) * 8..][..8];
+        let offset = u32::from_le_bytes(entry[0..4].try_into().unwrap());
+        let len = u32::from_le_bytes(entry[4..8].try_into().unwrap());
+        if len == !0 {
+            return None;
+        }
+        self.data[data_base..]
+            .get(offset as usize..)?
+            .get(..len as usize)
+    }
 }
 
 /// Commit index backend which stores data on local disk.
@@ -623,6 +762,14 @@ impl Index for DefaultReadonlyIndex {
         self.as_composite().has_id(commit_id)
     }
 
+    fn generation_number(&self, commit_id: &CommitId) -> Option<u64> {
+        self.as_composite().generation_number(commit_id)
+    }
+
+    fn topological_position(&self, commit_id: &CommitId) -> Option<u64> {
+        self.as_composite().topological_position(commit_id)
+    }
+
     fn is_ancestor(&self, ancestor_id: &CommitId, descendant_id: &CommitId) -> bool {
         self.as_composite().is_ancestor(ancestor_id, descendant_id)
     }
@@ -724,10 +871,17 @@ impl PositionLookupResult {
     }
 }
 
-/// Binary searches u32 position with the given comparison function.
-fn binary_search_pos_by(size: u32, mut f: impl FnMut(u32) -> Ordering) -> PositionLookupResult {
-    let mut low = 0;
-    let mut high = size;
+/// Binary searches u32 position within the `range` of the lookup table of the
+/// given `size` with the given comparison function.
+fn binary_search_pos_by(
+    range: Range<u32>,
+    size: u32,
+    mut f: impl FnMut(u32) -> Ordering,
+) -> PositionLookupResult {
+    let Range {
+        start: mut low,
+        end: mut high,
+    } = range;
     while low < high {
         let mid = (low + high) / 2;
         let cmp = f(mid);
@@ -31,6 +31,7 @@ use futures::StreamExt as _;
 use itertools::Itertools as _;
 use pollster::FutureExt as _;
 
+use super::changed_path::ChangedPathQuery;
 use super::rev_walk::EagerRevWalk;
 use super::rev_walk::PeekableRevWalk;
 use super::rev_walk::RevWalk;
@@ -47,6 +48,7 @@ use crate::conflicts::ConflictMarkerStyle;
 use crate::conflicts::MaterializedTreeValue;
 use crate::default_index::AsCompositeIndex;
 use crate::default_index::CompositeIndex;
+use crate::default_index::IndexEntry;
 use crate::default_index::IndexPosition;
 use crate::graph::GraphNode;
 use crate::matchers::Matcher;
@@ -62,6 +64,8 @@ use crate::revset::RevsetEvaluationError;
 use crate::revset::RevsetFilterPredicate;
 use crate::revset::GENERATION_RANGE_FULL;
 use crate::rewrite;
+use crate::signing::SigStatus;
+use crate::signing::Verification;
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::union_find;
@@ -1222,8 +1226,12 @@ fn build_predicate_fn(
         }
         RevsetFilterPredicate::File(expr) => {
             let matcher: Rc<dyn Matcher> = expr.to_matcher().into();
+            let changed_path_query = ChangedPathQuery::from_fileset(expr);
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
+                if !may_have_changed_paths(&entry, changed_path_query.as_ref()) {
+                    return Ok(false);
+                }
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(has_diff_from_parent(&store, index, &commit, &*matcher)?)
             })
@@ -1231,8 +1239,12 @@ fn build_predicate_fn(
         RevsetFilterPredicate::DiffContains { text, files } => {
             let text_pattern = text.clone();
             let files_matcher: Rc<dyn Matcher> = files.to_matcher().into();
+            let changed_path_query = ChangedPathQuery::from_fileset(files);
             box_pure_predicate_fn(move |index, pos| {
                 let entry = index.entry_by_pos(pos);
+                if !may_have_changed_paths(&entry, changed_path_query.as_ref()) {
+                    return Ok(false);
+                }
                 let commit = store.get_commit(&entry.commit_id())?;
                 Ok(matches_diff_from_parent(
                     &store,
@@ -1248,6 +1260,35 @@ fn build_predicate_fn(
             let commit = store.get_commit(&entry.commit_id())?;
             Ok(commit.has_conflict()?)
         }),
+        RevsetFilterPredicate::Signed => box_pure_predicate_fn(move |index, pos| {
+            let entry = index.entry_by_pos(pos);
+            let commit = store.get_commit(&entry.commit_id())?;
+            Ok(commit.is_signed())
+        }),
+        RevsetFilterPredicate::SignedBy(pattern) => {
+            let pattern = pattern.clone();
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                let Some(verification) = verify_commit(&commit)? else {
+                    return Ok(false);
+                };
+                Ok(verification.status == SigStatus::Good
+                    && [&verification.key, &verification.display]
+                        .into_iter()
+                        .flatten()
+                        .any(|s| pattern.matches(s)))
+            })
+        }
+        RevsetFilterPredicate::SignatureStatus(status) => {
+            let status = *status;
+            box_pure_predicate_fn(move |index, pos| {
+                let entry = index.entry_by_pos(pos);
+                let commit = store.get_commit(&entry.commit_id())?;
+                let verification = verify_commit(&commit)?;
+                Ok(verification.is_some_and(|verification| verification.status == status))
+            })
+        }
         RevsetFilterPredicate::Extension(ext) => {
             let ext = ext.clone();
             box_pure_predicate_fn(move |index, pos| {
@@ -1259,6 +1300,21 @@ fn build_predicate_fn(
     }
 }
 
+/// Returns false if the changed-path filter of the `entry` tells that the
+/// commit didn't change any of the paths in the `query`.
+fn may_have_changed_paths(entry: &IndexEntry, query: Option<&ChangedPathQuery>) -> bool {
+    match (query, entry.changed_path_filter()) {
+        (Some(query), Some(filter)) => query.may_match(filter),
+        _ => true,
+    }
+}
+
+fn verify_commit(commit: &Commit) -> Result<Option<Verification>, RevsetEvaluationError> {
+    commit
+        .verification()
+        .map_err(|err| RevsetEvaluationError::Other(err.into()))
+}
+
 fn has_diff_from_parent(
     store: &Arc<Store>,
     index: &CompositeIndex,
@@ -27,12 +27,16 @@ use itertools::Itertools as _;
 use tempfile::NamedTempFile;
 use thiserror::Error;
 
+use super::composite::IndexSegment as _;
+use super::entry::IndexPosition;
 use super::mutable::DefaultMutableIndex;
+use super::mutable::MutableIndexSegment;
 use super::readonly::DefaultReadonlyIndex;
 use super::readonly::ReadonlyIndexLoadError;
 use super::readonly::ReadonlyIndexSegment;
 use crate::backend::BackendError;
 use crate::backend::BackendInitError;
+use crate::backend::ChangeId;
 use crate::backend::CommitId;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::dag_walk;
@@ -46,7 +50,11 @@ use crate::index::IndexStore;
 use crate::index::IndexWriteError;
 use crate::index::MutableIndex;
 use crate::index::ReadonlyIndex;
+use crate::lock::FileLock;
+use crate::lock::FileLockError;
+use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId as _;
+use crate::object_id::PrefixResolution;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
 use crate::operation::Operation;
@@ -79,6 +87,10 @@ pub enum DefaultIndexStoreError {
     LoadIndex(ReadonlyIndexLoadError),
     #[error("Failed to write commit index file")]
     SaveIndex(#[source] io::Error),
+    #[error("Failed to remove unused commit index files")]
+    RemoveUnusedFiles(#[source] PathError),
+    #[error("Failed to lock commit index store")]
+    Lock(#[source] FileLockError),
     #[error("Failed to index commits at operation {op_id}")]
     IndexCommits {
         op_id: OperationId,
@@ -88,9 +100,64 @@ pub enum DefaultIndexStoreError {
     OpStore(#[from] OpStoreError),
 }
 
+/// Problem found by [`DefaultIndexStore::verify_index_at_operation()`].
+#[derive(Debug, Error)]
+pub enum IndexCorruption {
+    #[error("Index of operation {op_id} cannot be loaded")]
+    LoadOperationIndex {
+        op_id: OperationId,
+        source: DefaultIndexStoreError,
+    },
+    #[error("Commit {commit_id} referenced by operation {op_id} is not indexed")]
+    MissingCommit {
+        op_id: OperationId,
+        commit_id: CommitId,
+    },
+    #[error("Indexed commit {commit_id} cannot be read from the backend")]
+    ReadCommit {
+        commit_id: CommitId,
+        source: BackendError,
+    },
+    #[error(
+        "Indexed commit {commit_id} has parents [{}], but the backend says [{}]",
+        .indexed.iter().join(", "),
+        .actual.iter().join(", ")
+    )]
+    ParentsMismatch {
+        commit_id: CommitId,
+        indexed: Vec<CommitId>,
+        actual: Vec<CommitId>,
+    },
+    #[error("Indexed commit {commit_id} is not positioned after its parent {parent_id}")]
+    ParentOrder {
+        commit_id: CommitId,
+        parent_id: CommitId,
+    },
+    #[error("Indexed commit {commit_id} has change id {indexed}, but the backend says {actual}")]
+    ChangeIdMismatch {
+        commit_id: CommitId,
+        indexed: ChangeId,
+        actual: ChangeId,
+    },
+    #[error("Indexed commit {commit_id} has generation number {indexed}, expected {expected}")]
+    GenerationNumberMismatch {
+        commit_id: CommitId,
+        indexed: u32,
+        expected: u32,
+    },
+    #[error("Commit id lookup table doesn't map {commit_id} to its entry")]
+    CommitIdLookup { commit_id: CommitId },
+    #[error("Change id lookup table doesn't map {change_id} to commit {commit_id}")]
+    ChangeIdLookup {
+        commit_id: CommitId,
+        change_id: ChangeId,
+    },
+}
+
 #[derive(Debug)]
 pub struct DefaultIndexStore {
     dir: PathBuf,
+    changed_paths: bool,
 }
 
 impl DefaultIndexStore {
@@ -101,6 +168,7 @@ impl DefaultIndexStore {
     pub fn init(dir: &Path) -> Result<Self, DefaultIndexStoreInitError> {
         let store = DefaultIndexStore {
             dir: dir.to_owned(),
+            changed_paths: false,
         };
         store.ensure_base_dirs()?;
         Ok(store)
@@ -109,9 +177,17 @@ impl DefaultIndexStore {
     pub fn load(dir: &Path) -> DefaultIndexStore {
         DefaultIndexStore {
             dir: dir.to_owned(),
+            changed_paths: false,
         }
     }
 
+    /// Enables changed-path filters for newly indexed commits. Commits that
+    /// are already indexed get filters when the index is rebuilt.
+    pub fn with_changed_paths(mut self, enabled: bool) -> Self {
+        self.changed_paths = enabled;
+        self
+    }
+
     pub fn reinit(&self) -> Result<(), DefaultIndexStoreInitError> {
         // Create base directories in case the store was initialized by old jj.
         self.ensure_base_dirs()?;
@@ -133,6 +209,202 @@ impl DefaultIndexStore {
         Ok(())
     }
 
+    /// Merges all index segments of the given `operation` into one segment
+    /// file. Links from the other operations and segment files no longer in
+    /// use are removed. Indexes of the other operations will be rebuilt on
+    /// demand.
+    pub fn compact_index_at_operation(
+        &self,
+        operation: &Operation,
+        store: &Arc<Store>,
+    ) -> Result<DefaultReadonlyIndex, DefaultIndexStoreError> {
+        let index_segment = match self.load_index_segments_at_operation(
+            operation.id(),
+            store.commit_id_length(),
+            store.change_id_length(),
+        ) {
+            Err(DefaultIndexStoreError::LoadAssociation(err))
+                if err.kind() == io::ErrorKind::NotFound =>
+            {
+                self.build_index_segments_at_operation(operation, store)?
+            }
+            result => result?,
+        };
+        // Hold the lock so that segment files and links being written by other
+        // processes aren't removed.
+        let _lock = self.lock()?;
+        let compacted_segment = if index_segment.parent_file().is_some() {
+            let compacted_segment = MutableIndexSegment::squashed_from(&index_segment)
+                .save_in(&self.segments_dir())
+                .map_err(DefaultIndexStoreError::SaveIndex)?;
+            self.associate_file_with_operation(&compacted_segment, operation.id())
+                .map_err(|source| DefaultIndexStoreError::AssociateIndex {
+                    op_id: operation.id().clone(),
+                    source,
+                })?;
+            compacted_segment
+        } else {
+            index_segment
+        };
+
+        // Links and segment files might still be removed while the other
+        // processes are loading them. They'll be rebuilt as needed.
+        let op_link_name = operation.id().hex();
+        let remove_files_except = |dir: &Path, name_to_keep: &str| {
+            for entry in dir.read_dir().context(dir)? {
+                let entry = entry.context(dir)?;
+                if entry.file_name() != name_to_keep {
+                    let path = entry.path();
+                    fs::remove_file(&path).context(&path)?;
+                }
+            }
+            Ok::<_, PathError>(())
+        };
+        remove_files_except(&self.operations_dir(), &op_link_name)
+            .and_then(|()| remove_files_except(&self.segments_dir(), compacted_segment.name()))
+            .map_err(DefaultIndexStoreError::RemoveUnusedFiles)?;
+        Ok(DefaultReadonlyIndex::from_segment(compacted_segment))
+    }
+
+    /// Checks the links from operations to index segments, and the index at
+    /// the given `operation` against the backend. Returns the problems found.
+    pub fn verify_index_at_operation(
+        &self,
+        operation: &Operation,
+        store: &Arc<Store>,
+    ) -> Result<Vec<IndexCorruption>, DefaultIndexStoreError> {
+        let commit_id_length = store.commit_id_length();
+        let change_id_length = store.change_id_length();
+        let mut problems = vec![];
+
+        let operations_dir = self.operations_dir();
+        let mut loaded_segment_names = HashSet::new();
+        let op_ids: Vec<OperationId> = operations_dir
+            .read_dir()
+            .map_err(DefaultIndexStoreError::LoadAssociation)?
+            .map_ok(|entry| entry.file_name())
+            .filter_map_ok(|name| OperationId::try_from_hex(name.to_str()?).ok())
+            .try_collect()
+            .map_err(DefaultIndexStoreError::LoadAssociation)?;
+        for op_id in op_ids.iter().sorted() {
+            let op_id_file = operations_dir.join(op_id.hex());
+            let result = fs::read_to_string(op_id_file)
+                .map_err(DefaultIndexStoreError::LoadAssociation)
+                .and_then(|name| {
+                    if loaded_segment_names.insert(name.clone()) {
+                        ReadonlyIndexSegment::load(
+                            &self.segments_dir(),
+                            name,
+                            commit_id_length,
+                            change_id_length,
+                        )
+                        .map_err(DefaultIndexStoreError::LoadIndex)?;
+                    }
+                    Ok(())
+                });
+            if let Err(source) = result {
+                problems.push(IndexCorruption::LoadOperationIndex {
+                    op_id: op_id.clone(),
+                    source,
+                });
+            }
+        }
+
+        let index_segment = match self.load_index_segments_at_operation(
+            operation.id(),
+            commit_id_length,
+            change_id_length,
+        ) {
+            Ok(index_segment) => index_segment,
+            Err(source) => {
+                if !op_ids.contains(operation.id()) {
+                    problems.push(IndexCorruption::LoadOperationIndex {
+                        op_id: operation.id().clone(),
+                        source,
+                    });
+                }
+                return Ok(problems);
+            }
+        };
+        let index = index_segment.as_composite();
+        for commit_id in operation.view()?.all_referenced_commit_ids() {
+            if !index.has_id(commit_id) {
+                problems.push(IndexCorruption::MissingCommit {
+                    op_id: operation.id().clone(),
+                    commit_id: commit_id.clone(),
+                });
+            }
+        }
+        for pos in 0..index.num_commits() {
+            let entry = index.entry_by_pos(IndexPosition(pos));
+            let commit_id = entry.commit_id();
+            let change_id = entry.change_id();
+            if index.commit_id_to_pos(&commit_id) != Some(entry.position()) {
+                problems.push(IndexCorruption::CommitIdLookup {
+                    commit_id: commit_id.clone(),
+                });
+            }
+            let change_id_prefix = HexPrefix::from_bytes(change_id.as_bytes());
+            match index.resolve_change_id_prefix(&change_id_prefix) {
+                PrefixResolution::SingleMatch((id, positions))
+                    if id == change_id && positions.contains(&entry.position()) => {}
+                _ => problems.push(IndexCorruption::ChangeIdLookup {
+                    commit_id: commit_id.clone(),
+                    change_id: change_id.clone(),
+                }),
+            }
+
+            let parents = entry.parents().collect_vec();
+            for parent in &parents {
+                if parent.position() >= entry.position() {
+                    problems.push(IndexCorruption::ParentOrder {
+                        commit_id: commit_id.clone(),
+                        parent_id: parent.commit_id(),
+                    });
+                }
+            }
+            let expected_generation_number = parents
+                .iter()
+                .map(|parent| parent.generation_number() + 1)
+                .max()
+                .unwrap_or(0);
+            if entry.generation_number() != expected_generation_number {
+                problems.push(IndexCorruption::GenerationNumberMismatch {
+                    commit_id: commit_id.clone(),
+                    indexed: entry.generation_number(),
+                    expected: expected_generation_number,
+                });
+            }
+
+            let commit = match store.get_commit(&commit_id) {
+                Ok(commit) => commit,
+                Err(source) => {
+                    problems.push(IndexCorruption::ReadCommit { commit_id, source });
+                    continue;
+                }
+            };
+            let indexed_parent_ids = parents
+                .iter()
+                .map(|parent| parent.commit_id())
+                .collect_vec();
+            if indexed_parent_ids != commit.parent_ids() {
+                problems.push(IndexCorruption::ParentsMismatch {
+                    commit_id: commit_id.clone(),
+                    indexed: indexed_parent_ids,
+                    actual: commit.parent_ids().to_vec(),
+                });
+            }
+            if &change_id != commit.change_id() {
+                problems.push(IndexCorruption::ChangeIdMismatch {
+                    commit_id,
+                    indexed: change_id,
+                    actual: commit.change_id().clone(),
+                });
+            }
+        }
+        Ok(problems)
+    }
+
     fn ensure_base_dirs(&self) -> Result<(), PathError> {
         for dir in [self.operations_dir(), self.segments_dir()] {
             file_util::create_or_reuse_dir(&dir).context(&dir)?;
@@ -233,6 +505,9 @@ impl DefaultIndexStore {
                 mutable_index = DefaultMutableIndex::incremental(parent_file);
             }
         }
+        if self.changed_paths {
+            mutable_index.enable_changed_path_filters();
+        }
 
         tracing::info!(
             ?maybe_parent_file,
@@ -288,6 +563,7 @@ impl DefaultIndexStore {
         mutable_index: DefaultMutableIndex,
         op_id: &OperationId,
     ) -> Result<Arc<ReadonlyIndexSegment>, DefaultIndexStoreError> {
+        let _lock = self.lock()?;
         let index_segment = mutable_index
             .squash_and_save_in(&self.segments_dir())
             .map_err(DefaultIndexStoreError::SaveIndex)?;
@@ -299,6 +575,10 @@ impl DefaultIndexStore {
         Ok(index_segment)
     }
 
+    fn lock(&self) -> Result<FileLock, DefaultIndexStoreError> {
+        FileLock::lock(self.dir.join("lock")).map_err(DefaultIndexStoreError::Lock)
+    }
+
     /// Records a link from the given operation to the this index version.
     fn associate_file_with_operation(
         &self,
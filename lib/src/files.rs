@@ -202,6 +202,54 @@ pub fn merge<T: AsRef<[u8]>>(slices: &Merge<T>) -> MergeResult {
     merge_hunks(&Diff::by_line(diff_inputs), num_diffs)
 }
 
+/// Merges `slices` like [`merge()`], but re-merges each conflicted line hunk
+/// word by word.
+///
+/// This resolves hunks where the sides changed different words of the same
+/// lines, which is common in prose. Conflicts are only left for hunks where
+/// the word edits overlap, and those conflicts still span whole lines.
+pub fn merge_by_word<T: AsRef<[u8]>>(slices: &Merge<T>) -> MergeResult {
+    match merge(slices) {
+        MergeResult::Resolved(content) => MergeResult::Resolved(content),
+        MergeResult::Conflict(hunks) => merge_hunks_by_word(hunks),
+    }
+}
+
+/// Re-merges each conflicted hunk of a line-based merge word by word.
+///
+/// `hunks` is the result of [`merge()`]. Hunks that can't be resolved word by
+/// word are returned unchanged.
+pub fn merge_hunks_by_word(hunks: impl IntoIterator<Item = Merge<BString>>) -> MergeResult {
+    let mut resolved_hunk = BString::new(vec![]);
+    let mut conflict_hunks: Vec<Merge<BString>> = vec![];
+    for hunk in hunks {
+        if let Some(content) = hunk.as_resolved() {
+            resolved_hunk.extend_from_slice(content);
+            continue;
+        }
+        let num_diffs = hunk.removes().len();
+        let word_merge = merge_hunks(&Diff::by_word(hunk.removes().chain(hunk.adds())), num_diffs);
+        match word_merge {
+            MergeResult::Resolved(content) => resolved_hunk.extend_from_slice(&content),
+            MergeResult::Conflict(_) => {
+                if !resolved_hunk.is_empty() {
+                    conflict_hunks.push(Merge::resolved(mem::take(&mut resolved_hunk)));
+                }
+                conflict_hunks.push(hunk);
+            }
+        }
+    }
+
+    if conflict_hunks.is_empty() {
+        MergeResult::Resolved(resolved_hunk)
+    } else {
+        if !resolved_hunk.is_empty() {
+            conflict_hunks.push(Merge::resolved(resolved_hunk));
+        }
+        MergeResult::Conflict(conflict_hunks)
+    }
+}
+
 fn merge_hunks(diff: &Diff, num_diffs: usize) -> MergeResult {
     let mut resolved_hunk = BString::new(vec![]);
     let mut merge_hunks: Vec<Merge<BString>> = vec![];
@@ -440,6 +488,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_merge_by_word() {
+        let merge_by_word = |removes: &[&[u8]], adds: &[&[u8]]| {
+            super::merge_by_word(&Merge::from_removes_adds(removes, adds))
+        };
+        // Two sides changed different words of the same line
+        assert_eq!(
+            merge_by_word(
+                &[b"the quick fox\n"],
+                &[b"the slow fox\n", b"the quick dog\n"]
+            ),
+            MergeResult::Resolved(hunk(b"the slow dog\n"))
+        );
+        // Two sides changed the same word
+        assert_eq!(
+            merge_by_word(
+                &[b"the quick fox\n"],
+                &[b"the slow fox\n", b"the fast fox\n"]
+            ),
+            MergeResult::Conflict(vec![Merge::from_removes_adds(
+                vec![hunk(b"the quick fox\n")],
+                vec![hunk(b"the slow fox\n"), hunk(b"the fast fox\n")]
+            )])
+        );
+        // Only the line hunk with overlapping word edits is left conflicted
+        assert_eq!(
+            merge_by_word(&[b"a b\nc\nd e\n"], &[b"A b\nc\nD e\n", b"a B\nc\nd2 e\n"]),
+            MergeResult::Conflict(vec![
+                Merge::resolved(hunk(b"A B\nc\n")),
+                Merge::from_removes_adds(
+                    vec![hunk(b"d e\n")],
+                    vec![hunk(b"D e\n"), hunk(b"d2 e\n")]
+                ),
+            ])
+        );
+        // Changes to different lines are merged as usual
+        assert_eq!(
+            merge_by_word(&[b"a\nb\nc\n"], &[b"a2\nb\nc\n", b"a\nb\nc2\n"]),
+            MergeResult::Resolved(hunk(b"a2\nb\nc2\n"))
+        );
+    }
+
     #[test]
     fn test_merge_multi_hunk() {
         // Two sides left one line unchanged, and added conflicting additional lines
@@ -15,8 +15,11 @@
 //! API for transforming file content, for example to apply formatting, and
 //! propagate those changes across revisions.
 
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::io::Read as _;
+use std::ops::Range;
 use std::sync::mpsc::channel;
 
 use futures::StreamExt as _;
@@ -25,12 +28,16 @@ use jj_lib::backend::BackendError;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::FileId;
 use jj_lib::backend::TreeValue;
+use jj_lib::diff::find_line_ranges;
+use jj_lib::diff::Diff;
+use jj_lib::diff::DiffHunkKind;
 use jj_lib::matchers::Matcher;
 use jj_lib::merged_tree::MergedTree;
 use jj_lib::merged_tree::MergedTreeBuilder;
 use jj_lib::merged_tree::TreeDiffEntry;
 use jj_lib::repo::MutableRepo;
 use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::revset::RevsetExpression;
 use jj_lib::revset::RevsetIteratorExt as _;
@@ -43,9 +50,6 @@ use rayon::prelude::ParallelIterator as _;
 use crate::revset::RevsetEvaluationError;
 
 /// Represents a file whose content may be transformed by a FileFixer.
-// TODO: Add the set of changed line/byte ranges, so those can be passed into code formatters via
-// flags. This will help avoid introducing unrelated changes when working on code with out of date
-// formatting.
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub struct FileToFix {
     /// Unique identifier for the file content.
@@ -56,6 +60,39 @@ pub struct FileToFix {
     ///  - Update parts of the file's content that should be derived from the
     ///    file's path.
     pub repo_path: RepoPathBuf,
+
+    /// The lines that were changed relative to the parent tree. They can be
+    /// passed to code formatters, so that working on code with out of date
+    /// formatting doesn't introduce unrelated changes. If the file was also
+    /// fixed in an ancestor, the lines are relative to the parent tree of that
+    /// ancestor, so the lines fixed there are fixed again.
+    ///
+    /// This is always [`ChangedLines::All`] unless
+    /// [`FileFixer::uses_changed_lines()`] returns true.
+    pub changed_lines: ChangedLines,
+}
+
+/// Lines of a [`FileToFix`] that were changed relative to the parent tree.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub enum ChangedLines {
+    /// The file wasn't a regular file in the parent tree, e.g. because it was
+    /// added or was conflicted there, so all of its lines are considered
+    /// changed.
+    All,
+    /// Zero-based ranges of lines that were added or modified. This is empty if
+    /// lines were only removed.
+    Ranges(Vec<Range<usize>>),
+}
+
+/// Files to fix at a path in a commit.
+#[derive(Clone, Debug)]
+struct PathToFix {
+    files_to_fix: Vec<FileToFix>,
+    /// The file in the parent tree of the first commit the path was fixed in,
+    /// which the changed lines are computed against. `None` if it wasn't a
+    /// regular file, or if the path was inherited from ancestors with
+    /// different bases.
+    base_file_id: Option<FileId>,
 }
 
 /// Error fixing files.
@@ -94,6 +131,15 @@ pub trait FileFixer {
         store: &Store,
         files_to_fix: &'a HashSet<FileToFix>,
     ) -> Result<HashMap<&'a FileToFix, FileId>, FixError>;
+
+    /// Returns true if the fixer uses [`FileToFix::changed_lines`].
+    ///
+    /// Computing the changed lines requires reading the files, and files with
+    /// the same content can no longer be fixed only once if their changed lines
+    /// differ.
+    fn uses_changed_lines(&self) -> bool {
+        false
+    }
 }
 
 /// Aggregate information about the outcome of the file fixer.
@@ -117,6 +163,7 @@ pub struct FixSummary {
 // pipes.
 pub struct ParallelFileFixer<T> {
     fix_fn: T,
+    uses_changed_lines: bool,
 }
 
 impl<T> ParallelFileFixer<T>
@@ -125,7 +172,16 @@ where
 {
     /// Creates a ParallelFileFixer.
     pub fn new(fix_fn: T) -> Self {
-        Self { fix_fn }
+        Self {
+            fix_fn,
+            uses_changed_lines: false,
+        }
+    }
+
+    /// Sets whether `fix_fn` uses [`FileToFix::changed_lines`].
+    pub fn with_changed_lines(mut self, uses_changed_lines: bool) -> Self {
+        self.uses_changed_lines = uses_changed_lines;
+        self
     }
 }
 
@@ -160,6 +216,10 @@ where
         }
         Ok(result)
     }
+
+    fn uses_changed_lines(&self) -> bool {
+        self.uses_changed_lines
+    }
 }
 
 /// Updates files with formatting fixes or other changes, using the given
@@ -172,7 +232,9 @@ where
 /// After the FileFixer is done, descendants are also updated, which ensures
 /// that the fixes are not lost. This will never result in new conflicts. Files
 /// with existing conflicts are updated on all sides of the conflict, which
-/// can potentially increase or decrease the number of conflict markers.
+/// can potentially increase or decrease the number of conflict markers. The
+/// updated terms are merged again when the tree is written, so conflicts that
+/// only differ by formatting are resolved.
 pub fn fix_files(
     root_commits: Vec<CommitId>,
     matcher: &dyn Matcher,
@@ -210,20 +272,39 @@ pub fn fix_files(
     );
 
     let mut unique_files_to_fix: HashSet<FileToFix> = HashSet::new();
-    let mut commit_paths: HashMap<CommitId, HashSet<RepoPathBuf>> = HashMap::new();
+    let mut commit_paths: HashMap<CommitId, HashMap<RepoPathBuf, PathToFix>> = HashMap::new();
     for commit in commits.iter().rev() {
-        let mut paths: HashSet<RepoPathBuf> = HashSet::new();
+        let mut paths: HashMap<RepoPathBuf, PathToFix> = HashMap::new();
 
         // If --include-unchanged-files, we always fix every matching file in the tree.
         // Otherwise, we fix the matching changed files in this commit, plus any that
         // were fixed in ancestors, so we don't lose those changes. We do this
-        // instead of rebasing onto those changes, to avoid merge conflicts.
+        // instead of rebasing onto those changes, to avoid merge conflicts. The
+        // inherited files keep the changed lines computed in the ancestors, so
+        // they are fixed the same way. If an inherited file is modified again,
+        // its changed lines are computed against the content the ancestors'
+        // changed lines were computed against, so the fixed lines aren't
+        // reverted.
         let parent_tree = if include_unchanged_files {
             MergedTree::resolved(Tree::empty(repo_mut.store().clone(), RepoPathBuf::root()))
         } else {
             for parent_id in commit.parent_ids() {
                 if let Some(parent_paths) = commit_paths.get(parent_id) {
-                    paths.extend(parent_paths.iter().cloned());
+                    for (repo_path, parent_path) in parent_paths {
+                        match paths.entry(repo_path.clone()) {
+                            Entry::Occupied(mut entry) => {
+                                let path = entry.get_mut();
+                                path.files_to_fix
+                                    .extend(parent_path.files_to_fix.iter().cloned());
+                                if path.base_file_id != parent_path.base_file_id {
+                                    path.base_file_id = None;
+                                }
+                            }
+                            Entry::Vacant(entry) => {
+                                entry.insert(parent_path.clone());
+                            }
+                        }
+                    }
                 }
             }
             commit.parent_tree(repo_mut)?
@@ -236,24 +317,54 @@ pub fn fix_files(
                 values,
             }) = diff_stream.next().await
             {
-                let (_before, after) = values?;
+                let (before, after) = values?;
+                let base_file_id = if let Some(inherited) = paths.get(&repo_path) {
+                    inherited.base_file_id.clone()
+                } else {
+                    match before.into_resolved() {
+                        Ok(Some(TreeValue::File { id, executable: _ })) => Some(id),
+                        _ => None,
+                    }
+                };
+                let base_content = match &base_file_id {
+                    Some(id) if file_fixer.uses_changed_lines() => {
+                        Some(read_file(repo_mut.store(), &repo_path, id)?)
+                    }
+                    _ => None,
+                };
                 // Deleted files have no file content to fix, and they have no terms in `after`,
                 // so we don't add any files-to-fix for them. Conflicted files produce one
                 // file-to-fix for each side of the conflict.
+                let mut files_to_fix = vec![];
                 for term in after.into_iter().flatten() {
                     // We currently only support fixing the content of normal files, so we skip
                     // directories and symlinks, and we ignore the executable bit.
                     if let TreeValue::File { id, executable: _ } = term {
                         // TODO: Skip the file if its content is larger than some configured size,
                         // preferably without actually reading it yet.
+                        let changed_lines = match &base_content {
+                            Some(base_content) => {
+                                let content = read_file(repo_mut.store(), &repo_path, &id)?;
+                                ChangedLines::Ranges(changed_line_ranges(base_content, &content))
+                            }
+                            None => ChangedLines::All,
+                        };
                         let file_to_fix = FileToFix {
                             file_id: id.clone(),
                             repo_path: repo_path.clone(),
+                            changed_lines,
                         };
                         unique_files_to_fix.insert(file_to_fix.clone());
-                        paths.insert(repo_path.clone());
+                        files_to_fix.push(file_to_fix);
                     }
                 }
+                if !files_to_fix.is_empty() {
+                    let path = PathToFix {
+                        files_to_fix,
+                        base_file_id,
+                    };
+                    paths.insert(repo_path, path);
+                }
             }
             Ok::<(), BackendError>(())
         }
@@ -283,15 +394,16 @@ pub fn fix_files(
         let old_tree = rewriter.old_commit().tree()?;
         let mut tree_builder = MergedTreeBuilder::new(old_tree.id().clone());
         let mut changes = 0;
-        for repo_path in repo_paths {
+        for (repo_path, path) in repo_paths {
             let old_value = old_tree.path_value(repo_path)?;
             let new_value = old_value.map(|old_term| {
                 if let Some(TreeValue::File { id, executable }) = old_term {
-                    let file_to_fix = FileToFix {
-                        file_id: id.clone(),
-                        repo_path: repo_path.clone(),
-                    };
-                    if let Some(new_id) = fixed_file_ids.get(&file_to_fix) {
+                    let new_id = path
+                        .files_to_fix
+                        .iter()
+                        .find(|file_to_fix| file_to_fix.file_id == *id)
+                        .and_then(|file_to_fix| fixed_file_ids.get(file_to_fix));
+                    if let Some(new_id) = new_id {
                         return Some(TreeValue::File {
                             id: new_id.clone(),
                             executable: *executable,
@@ -321,3 +433,31 @@ pub fn fix_files(
     tracing::debug!(?summary);
     Ok(summary)
 }
+
+fn read_file(store: &Store, path: &RepoPath, id: &FileId) -> Result<Vec<u8>, BackendError> {
+    let mut content = vec![];
+    store
+        .read_file(path, id)?
+        .read_to_end(&mut content)
+        .map_err(|err| BackendError::ReadFile {
+            path: path.to_owned(),
+            id: id.clone(),
+            source: err.into(),
+        })?;
+    Ok(content)
+}
+
+/// Returns the ranges of lines in `after` that were added or modified relative
+/// to `before`.
+fn changed_line_ranges(before: &[u8], after: &[u8]) -> Vec<Range<usize>> {
+    let mut ranges = vec![];
+    let mut line = 0;
+    for hunk in Diff::by_line([before, after]).hunks() {
+        let num_lines = find_line_ranges(hunk.contents[1]).len();
+        if hunk.kind == DiffHunkKind::Different && num_lines > 0 {
+            ranges.push(line..line + num_lines);
+        }
+        line += num_lines;
+    }
+    ranges
+}
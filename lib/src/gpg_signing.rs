@@ -14,10 +14,13 @@
 
 #![allow(missing_docs)]
 
+use std::env;
+use std::ffi::OsStr;
 use std::ffi::OsString;
 use std::fmt::Debug;
 use std::io;
 use std::io::Write as _;
+use std::path::PathBuf;
 use std::process::Command;
 use std::process::ExitStatus;
 use std::process::Stdio;
@@ -31,6 +34,7 @@ use crate::signing::SigStatus;
 use crate::signing::SignError;
 use crate::signing::SigningBackend;
 use crate::signing::Verification;
+use crate::signing::VerificationState;
 
 // Search for one of the:
 //  [GNUPG:] GOODSIG <long keyid> <primary uid..>
@@ -129,6 +133,48 @@ pub enum GpgError {
     Io(#[from] std::io::Error),
 }
 
+/// Returns the GnuPG home directory containing the keyrings, which can be
+/// overridden by `--homedir` in the `extra_args`.
+fn gnupg_home_dir(extra_args: &[OsString]) -> Option<PathBuf> {
+    let mut args = extra_args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--homedir" {
+            return args.next().map(PathBuf::from);
+        } else if let Some(dir) = arg.to_str().and_then(|arg| arg.strip_prefix("--homedir=")) {
+            return Some(dir.into());
+        }
+    }
+    if let Some(dir) = env::var_os("GNUPGHOME") {
+        Some(dir.into())
+    } else if cfg!(windows) {
+        env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("gnupg"))
+    } else {
+        env::var_os("HOME").map(|dir| PathBuf::from(dir).join(".gnupg"))
+    }
+}
+
+/// Builds the verification state of a GnuPG program, which depends on the
+/// given `keyring_files` in the home directory.
+fn gnupg_verification_state(
+    program: &OsStr,
+    allow_expired_keys: bool,
+    extra_args: &[OsString],
+    keyring_files: &[&str],
+) -> Option<VerificationState> {
+    let home_dir = gnupg_home_dir(extra_args)?;
+    let mut state = VerificationState::new();
+    state
+        .add_value(program.as_encoded_bytes())
+        .add_value([u8::from(allow_expired_keys)]);
+    for arg in extra_args {
+        state.add_value(arg.as_encoded_bytes());
+    }
+    for name in keyring_files {
+        state.add_file(&home_dir.join(name));
+    }
+    Some(state)
+}
+
 impl From<GpgError> for SignError {
     fn from(e: GpgError) -> Self {
         SignError::Backend(Box::new(e))
@@ -213,6 +259,15 @@ impl SigningBackend for GpgBackend {
 
         parse_gpg_verify_output(&output, self.allow_expired_keys)
     }
+
+    fn verification_state(&self) -> Option<VerificationState> {
+        gnupg_verification_state(
+            &self.program,
+            self.allow_expired_keys,
+            &self.extra_args,
+            &["pubring.kbx", "pubring.gpg", "trustdb.gpg"],
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -301,6 +356,15 @@ impl SigningBackend for GpgsmBackend {
 
         parse_gpg_verify_output(&output, self.allow_expired_keys)
     }
+
+    fn verification_state(&self) -> Option<VerificationState> {
+        gnupg_verification_state(
+            &self.program,
+            self.allow_expired_keys,
+            &self.extra_args,
+            &["pubring.kbx", "trustlist.txt"],
+        )
+    }
 }
 
 #[cfg(test)]
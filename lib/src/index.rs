@@ -98,6 +98,23 @@ pub trait Index: Send + Sync {
     /// Returns true if `commit_id` is present in the index.
     fn has_id(&self, commit_id: &CommitId) -> bool;
 
+    /// Returns the generation number of `commit_id`, which is the length of
+    /// the longest path from the root commit to the commit. The root commit
+    /// is at generation 0. Returns `None` if the commit isn't indexed.
+    ///
+    /// A commit's generation number is greater than those of its ancestors,
+    /// so a walk looking for ancestors of a commit can stop at commits with
+    /// lower generation numbers.
+    fn generation_number(&self, commit_id: &CommitId) -> Option<u64>;
+
+    /// Returns the position of `commit_id` in a topological order of the
+    /// indexed commits, in which ancestors come before their descendants.
+    /// Returns `None` if the commit isn't indexed.
+    ///
+    /// Positions are unique within the index, but may change when the index is
+    /// rebuilt.
+    fn topological_position(&self, commit_id: &CommitId) -> Option<u64>;
+
     /// Returns true if `ancestor_id` commit is an ancestor of the
     /// `descendant_id` commit, or if `ancestor_id` equals `descendant_id`.
     fn is_ancestor(&self, ancestor_id: &CommitId, descendant_id: &CommitId) -> bool;
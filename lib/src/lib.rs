@@ -79,6 +79,7 @@ pub mod local_working_copy;
 pub mod lock;
 pub mod matchers;
 pub mod merge;
+pub mod merge_driver;
 pub mod merged_tree;
 pub mod object_id;
 pub mod op_heads_store;
@@ -91,12 +92,14 @@ pub mod ref_name;
 pub mod refs;
 pub mod repo;
 pub mod repo_path;
+pub mod resolution_cache;
 pub mod revset;
 mod revset_parser;
 pub mod rewrite;
 #[cfg(feature = "testing")]
 pub mod secret_backend;
 pub mod settings;
+mod signature_cache;
 pub mod signing;
 // TODO: This file is mostly used for testing, whenever we no longer require it
 // in the lib it should be moved to the examples (e.g
@@ -108,6 +111,7 @@ pub mod ssh_signing;
 pub mod stacked_table;
 pub mod store;
 pub mod str_util;
+pub mod structured_merge;
 pub mod submodule_store;
 #[cfg(feature = "testing")]
 pub mod test_signing_backend;
@@ -119,6 +123,7 @@ pub mod union_find;
 pub mod view;
 pub mod working_copy;
 pub mod workspace;
+pub mod workspace_store;
 
 #[cfg(test)]
 mod tests {
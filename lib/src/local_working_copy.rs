@@ -74,6 +74,7 @@ use crate::conflicts::MaterializedTreeValue;
 use crate::conflicts::MIN_CONFLICT_MARKER_LEN;
 use crate::file_util::check_symlink_support;
 use crate::file_util::try_symlink;
+use crate::fileset::FilesetParseError;
 #[cfg(feature = "watchman")]
 use crate::fsmonitor::watchman;
 use crate::fsmonitor::FsmonitorSettings;
@@ -86,7 +87,6 @@ use crate::matchers::EverythingMatcher;
 use crate::matchers::FilesMatcher;
 use crate::matchers::IntersectionMatcher;
 use crate::matchers::Matcher;
-use crate::matchers::PrefixMatcher;
 use crate::merge::Merge;
 use crate::merge::MergeBuilder;
 use crate::merge::MergedTreeValue;
@@ -110,6 +110,7 @@ use crate::working_copy::SnapshotError;
 use crate::working_copy::SnapshotOptions;
 use crate::working_copy::SnapshotProgress;
 use crate::working_copy::SnapshotStats;
+use crate::working_copy::SparsePatterns;
 use crate::working_copy::UntrackedReason;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
@@ -412,8 +413,7 @@ pub struct TreeState {
     state_path: PathBuf,
     tree_id: MergedTreeId,
     file_states: FileStatesMap,
-    // Currently only path prefixes
-    sparse_patterns: Vec<RepoPathBuf>,
+    sparse_patterns: SparsePatterns,
     own_mtime: MillisSinceEpoch,
     symlink_support: bool,
 
@@ -502,18 +502,24 @@ fn is_file_state_entries_proto_unique_and_sorted(
 
 fn sparse_patterns_from_proto(
     proto: Option<&crate::protos::working_copy::SparsePatterns>,
-) -> Vec<RepoPathBuf> {
-    let mut sparse_patterns = vec![];
-    if let Some(proto_sparse_patterns) = proto {
-        for prefix in &proto_sparse_patterns.prefixes {
-            sparse_patterns.push(RepoPathBuf::from_internal_string(prefix));
-        }
-    } else {
+) -> Result<SparsePatterns, FilesetParseError> {
+    let Some(proto_sparse_patterns) = proto else {
         // For compatibility with old working copies.
         // TODO: Delete this is late 2022 or so.
-        sparse_patterns.push(RepoPathBuf::root());
+        return Ok(SparsePatterns::everything());
+    };
+    if let Some(text) = &proto_sparse_patterns.fileset {
+        return match &proto_sparse_patterns.profile {
+            Some(name) => SparsePatterns::parse_profile(name, text),
+            None => SparsePatterns::parse_fileset(text),
+        };
     }
-    sparse_patterns
+    let prefixes = proto_sparse_patterns
+        .prefixes
+        .iter()
+        .map(RepoPathBuf::from_internal_string)
+        .collect();
+    Ok(SparsePatterns::Prefixes(prefixes))
 }
 
 /// Creates intermediate directories from the `working_copy_path` to the
@@ -734,6 +740,11 @@ pub enum TreeStateError {
     WriteTreeState { path: PathBuf, source: io::Error },
     #[error("Persisting tree state to file {path}")]
     PersistTreeState { path: PathBuf, source: io::Error },
+    #[error("Parsing sparse patterns from {path}")]
+    ParseSparsePatterns {
+        path: PathBuf,
+        source: FilesetParseError,
+    },
     #[error("Filesystem monitor error")]
     Fsmonitor(#[source] Box<dyn Error + Send + Sync>),
 }
@@ -751,12 +762,12 @@ impl TreeState {
         self.file_states.all()
     }
 
-    pub fn sparse_patterns(&self) -> &Vec<RepoPathBuf> {
+    pub fn sparse_patterns(&self) -> &SparsePatterns {
         &self.sparse_patterns
     }
 
     fn sparse_matcher(&self) -> Box<dyn Matcher> {
-        Box::new(PrefixMatcher::new(&self.sparse_patterns))
+        self.sparse_patterns.to_matcher()
     }
 
     pub fn init(
@@ -777,7 +788,7 @@ impl TreeState {
             state_path,
             tree_id,
             file_states: FileStatesMap::new(),
-            sparse_patterns: vec![RepoPathBuf::root()],
+            sparse_patterns: SparsePatterns::everything(),
             own_mtime: MillisSinceEpoch(0),
             symlink_support: check_symlink_support().unwrap_or(false),
             watchman_clock: None,
@@ -842,7 +853,13 @@ impl TreeState {
         }
         self.file_states =
             FileStatesMap::from_proto(proto.file_states, proto.is_file_states_sorted);
-        self.sparse_patterns = sparse_patterns_from_proto(proto.sparse_patterns.as_ref());
+        self.sparse_patterns =
+            sparse_patterns_from_proto(proto.sparse_patterns.as_ref()).map_err(|err| {
+                TreeStateError::ParseSparsePatterns {
+                    path: tree_state_path.to_owned(),
+                    source: err,
+                }
+            })?;
         self.watchman_clock = proto.watchman_clock;
         Ok(())
     }
@@ -863,10 +880,20 @@ impl TreeState {
         // `FileStatesMap` is guaranteed to be sorted.
         proto.is_file_states_sorted = true;
         let mut sparse_patterns = crate::protos::working_copy::SparsePatterns::default();
-        for path in &self.sparse_patterns {
-            sparse_patterns
-                .prefixes
-                .push(path.as_internal_file_string().to_owned());
+        match &self.sparse_patterns {
+            SparsePatterns::Prefixes(prefixes) => {
+                sparse_patterns.prefixes = prefixes
+                    .iter()
+                    .map(|path| path.as_internal_file_string().to_owned())
+                    .collect();
+            }
+            SparsePatterns::Fileset { text, .. } => {
+                sparse_patterns.fileset = Some(text.clone());
+            }
+            SparsePatterns::Profile { name, text, .. } => {
+                sparse_patterns.fileset = Some(text.clone());
+                sparse_patterns.profile = Some(name.clone());
+            }
         }
         proto.sparse_patterns = Some(sparse_patterns);
         proto.watchman_clock = self.watchman_clock.clone();
@@ -1688,6 +1715,20 @@ impl TreeState {
             },
             other => CheckoutError::InternalBackendError(other),
         })?;
+        // Re-read the sparse profile from the new tree, and apply the new
+        // patterns to the old tree before switching trees.
+        let mut profile_stats = CheckoutStats::default();
+        if let Some(name) = self.sparse_patterns.profile_name() {
+            match SparsePatterns::load_profile(new_tree, name) {
+                Ok(sparse_patterns) if sparse_patterns != self.sparse_patterns => {
+                    profile_stats = self.set_sparse_patterns(sparse_patterns, options)?;
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::warn!(?err, "failed to load sparse profile; keeping old patterns");
+                }
+            }
+        }
         let stats = self
             .update(
                 &old_tree,
@@ -1697,12 +1738,17 @@ impl TreeState {
             )
             .block_on()?;
         self.tree_id = new_tree.id();
-        Ok(stats)
+        Ok(CheckoutStats {
+            updated_files: profile_stats.updated_files + stats.updated_files,
+            added_files: profile_stats.added_files + stats.added_files,
+            removed_files: profile_stats.removed_files + stats.removed_files,
+            skipped_files: profile_stats.skipped_files + stats.skipped_files,
+        })
     }
 
     pub fn set_sparse_patterns(
         &mut self,
-        sparse_patterns: Vec<RepoPathBuf>,
+        sparse_patterns: SparsePatterns,
         options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         let tree = self.current_tree().map_err(|err| match err {
@@ -1711,8 +1757,8 @@ impl TreeState {
             },
             other => CheckoutError::InternalBackendError(other),
         })?;
-        let old_matcher = PrefixMatcher::new(&self.sparse_patterns);
-        let new_matcher = PrefixMatcher::new(&sparse_patterns);
+        let old_matcher = self.sparse_patterns.to_matcher();
+        let new_matcher = sparse_patterns.to_matcher();
         let added_matcher = DifferenceMatcher::new(&new_matcher, &old_matcher);
         let removed_matcher = DifferenceMatcher::new(&old_matcher, &new_matcher);
         let empty_tree = MergedTree::resolved(Tree::empty(self.store.clone(), RepoPathBuf::root()));
@@ -1991,7 +2037,7 @@ impl WorkingCopy for LocalWorkingCopy {
         Ok(self.tree_state()?.current_tree_id())
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         Ok(self.tree_state()?.sparse_patterns())
     }
 
@@ -2025,11 +2071,29 @@ impl WorkingCopy for LocalWorkingCopy {
     }
 }
 
+fn workspace_id_from_proto(workspace_id: String) -> WorkspaceId {
+    if workspace_id.is_empty() {
+        // For compatibility with old working copies.
+        // TODO: Delete in mid 2022 or so
+        WorkspaceId::default()
+    } else {
+        WorkspaceId::new(workspace_id)
+    }
+}
+
 impl LocalWorkingCopy {
     pub fn name() -> &'static str {
         "local"
     }
 
+    /// Reads the workspace name from the working copy state in `state_path`
+    /// without loading the rest of the state.
+    pub fn load_workspace_id(state_path: &Path) -> Option<WorkspaceId> {
+        let buf = fs::read(state_path.join("checkout")).ok()?;
+        let proto = crate::protos::working_copy::Checkout::decode(&*buf).ok()?;
+        Some(workspace_id_from_proto(proto.workspace_id))
+    }
+
     /// Initializes a new working copy at `working_copy_path`. The working
     /// copy's state will be stored in the `state_path` directory. The working
     /// copy will have the empty tree checked out.
@@ -2101,13 +2165,7 @@ impl LocalWorkingCopy {
             let proto = crate::protos::working_copy::Checkout::decode(&*buf).unwrap();
             CheckoutState {
                 operation_id: OperationId::new(proto.operation_id),
-                workspace_id: if proto.workspace_id.is_empty() {
-                    // For compatibility with old working copies.
-                    // TODO: Delete in mid 2022 or so
-                    WorkspaceId::default()
-                } else {
-                    WorkspaceId::new(proto.workspace_id)
-                },
+                workspace_id: workspace_id_from_proto(proto.workspace_id),
             }
         })
     }
@@ -2312,13 +2370,13 @@ impl LockedWorkingCopy for LockedLocalWorkingCopy {
         Ok(())
     }
 
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError> {
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError> {
         self.wc.sparse_patterns()
     }
 
     fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
+        new_sparse_patterns: SparsePatterns,
         options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError> {
         // TODO: Write a "pending_checkout" file with new sparse patterns so we can
@@ -580,6 +580,27 @@ where
         })
     }
 
+    /// If this merge contains only files or absent entries, returns whether
+    /// the resolved file should be executable. Returns `None` if the files
+    /// added by the merge disagree on the executable bit in a way that can't be
+    /// resolved.
+    pub fn resolve_executable(&self) -> Option<bool> {
+        let executable_merge = self.maybe_map(|term| match borrow_tree_value(term.as_ref()) {
+            None => Some(None),
+            Some(TreeValue::File { id: _, executable }) => Some(Some(*executable)),
+            _ => None,
+        })?;
+        match executable_merge.resolve_trivial() {
+            Some(Some(executable)) => Some(*executable),
+            _ => executable_merge
+                .adds()
+                .flatten()
+                .all_equal_value()
+                .ok()
+                .copied(),
+        }
+    }
+
     /// If every non-`None` term of a `MergedTreeValue`
     /// is a `TreeValue::Tree`, this converts it to
     /// a `Merge<Tree>`, with empty trees instead of
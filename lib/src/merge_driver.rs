@@ -0,0 +1,488 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Per-path strategies for automatically resolving file conflicts.
+//!
+//! Merge drivers are configured in the `merge.drivers` table. Each driver
+//! applies to the files matched by its `patterns`, and is consulted whenever
+//! the line-based merge of such a file leaves conflicts.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use bstr::BString;
+use itertools::Itertools as _;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::config::ConfigGetError;
+use crate::content_hash::blake2b_hash;
+use crate::files;
+use crate::files::MergeResult;
+use crate::fileset;
+use crate::fileset::FilesetDiagnostics;
+use crate::fileset::FilesetExpression;
+use crate::fileset::FilesetParseError;
+use crate::matchers::Matcher;
+use crate::merge::Merge;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathUiConverter;
+use crate::settings::UserSettings;
+use crate::structured_merge;
+use crate::structured_merge::ContentMerger;
+use crate::structured_merge::JsonMerger;
+use crate::structured_merge::TomlMerger;
+
+/// Error while loading merge drivers from the config.
+#[derive(Debug, Error)]
+pub enum MergeDriverConfigError {
+    /// Failed to read the driver config.
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
+    /// Failed to parse one of the driver patterns.
+    #[error("Invalid pattern in `merge.drivers.{name}`")]
+    Pattern {
+        /// Name of the driver.
+        name: String,
+        /// Source error.
+        source: FilesetParseError,
+    },
+    /// The driver doesn't specify exactly one way to resolve conflicts.
+    #[error("`merge.drivers.{name}` must set exactly one of `strategy` and `command`")]
+    Ambiguous {
+        /// Name of the driver.
+        name: String,
+    },
+}
+
+/// Error while running a merge driver.
+#[derive(Debug, Error)]
+pub enum MergeDriverError {
+    /// Failed to set up the files passed to the external command.
+    #[error("Failed to prepare files for merge driver `{name}`")]
+    SetUp {
+        /// Name of the driver.
+        name: String,
+        /// Source error.
+        source: io::Error,
+    },
+    /// Failed to run the external command.
+    #[error("Failed to run merge driver `{name}`")]
+    Execute {
+        /// Name of the driver.
+        name: String,
+        /// Source error.
+        source: io::Error,
+    },
+}
+
+/// How conflicts in the matched files are resolved.
+#[derive(Clone, Debug)]
+pub enum MergeStrategy {
+    /// Conflicting hunks take the content of the first side. When rebasing,
+    /// this is the destination.
+    Ours,
+    /// Conflicting hunks take the content of the last side. When rebasing, this
+    /// is the commit being rebased.
+    Theirs,
+    /// Conflicting hunks take the content of all sides, one after another.
+    Union,
+    /// Conflicting line hunks are merged again word by word. Hunks where word
+    /// edits overlap are left conflicted.
+    Words,
+    /// Resolves 3-way conflicts by running an external command on the whole
+    /// file. The command can refer to `$base`, `$left`, `$right`, `$output`,
+    /// and `$path`. It must write the merged content to `$output` and exit
+    /// successfully, or the file is left conflicted.
+    ///
+    /// Trees are merged when commits are rewritten, but also when a merge
+    /// commit is diffed against its parents, so the command may run for
+    /// read-only operations, too. The result is cached for the lifetime of the
+    /// driver so that the command runs only once for the same contents.
+    Command(Vec<String>),
+    /// Merges the whole file by its structure, falling back to the line-based
+    /// conflict if that fails.
+    Structured(Arc<dyn ContentMerger>),
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum RawMergeStrategy {
+    Ours,
+    Theirs,
+    Union,
+    Words,
+    Json,
+    Toml,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct RawMergeDriverConfig {
+    patterns: Vec<String>,
+    #[serde(default)]
+    strategy: Option<RawMergeStrategy>,
+    #[serde(default)]
+    command: Option<Vec<String>>,
+}
+
+/// Results of [`MergeStrategy::Command`] keyed by the path and the hash of the
+/// contents.
+type CommandResults = HashMap<(RepoPathBuf, Vec<u8>), Option<BString>>;
+
+/// A strategy applied to the files matching a fileset.
+#[derive(Clone, Debug)]
+pub struct MergeDriver {
+    name: String,
+    matcher: Arc<dyn Matcher>,
+    strategy: MergeStrategy,
+    command_results: Arc<Mutex<CommandResults>>,
+}
+
+impl MergeDriver {
+    /// Creates a driver applying `strategy` to the files matching
+    /// `expression`.
+    pub fn new(name: String, expression: FilesetExpression, strategy: MergeStrategy) -> Self {
+        Self {
+            name,
+            matcher: expression.to_matcher().into(),
+            strategy,
+            command_results: Default::default(),
+        }
+    }
+
+    /// Name of the driver in the config.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The strategy used to resolve conflicts.
+    pub fn strategy(&self) -> &MergeStrategy {
+        &self.strategy
+    }
+
+    /// Tries to resolve the conflicted `contents` of the file at `path`.
+    ///
+    /// `hunks` is the result of the line-based merge of `contents`. Returns
+    /// `None` if the driver couldn't resolve any of the conflicted hunks.
+    /// Returns [`MergeResult::Conflict`] if the driver resolved some of the
+    /// hunks but others are still conflicted.
+    pub fn resolve(
+        &self,
+        path: &RepoPath,
+        contents: &Merge<Vec<u8>>,
+        hunks: &[Merge<BString>],
+    ) -> Result<Option<MergeResult>, MergeDriverError> {
+        let pick_sides = |hunk: &Merge<BString>| -> Vec<BString> {
+            if let Some(resolved) = hunk.as_resolved() {
+                return vec![resolved.clone()];
+            }
+            match &self.strategy {
+                MergeStrategy::Ours => vec![hunk.first().clone()],
+                MergeStrategy::Theirs => vec![hunk.adds().last().unwrap().clone()],
+                MergeStrategy::Union => hunk.adds().cloned().collect(),
+                MergeStrategy::Words | MergeStrategy::Command(_) | MergeStrategy::Structured(_) => {
+                    unreachable!()
+                }
+            }
+        };
+        match &self.strategy {
+            MergeStrategy::Ours | MergeStrategy::Theirs | MergeStrategy::Union => {
+                let mut merged = BString::default();
+                for side in hunks.iter().flat_map(pick_sides) {
+                    merged.extend_from_slice(&side);
+                }
+                Ok(Some(MergeResult::Resolved(merged)))
+            }
+            MergeStrategy::Words => {
+                let count_conflicts = |hunks: &[Merge<BString>]| {
+                    hunks.iter().filter(|hunk| !hunk.is_resolved()).count()
+                };
+                match files::merge_hunks_by_word(hunks.iter().cloned()) {
+                    MergeResult::Resolved(merged) => Ok(Some(MergeResult::Resolved(merged))),
+                    MergeResult::Conflict(word_hunks)
+                        if count_conflicts(&word_hunks) < count_conflicts(hunks) =>
+                    {
+                        Ok(Some(MergeResult::Conflict(word_hunks)))
+                    }
+                    MergeResult::Conflict(_) => Ok(None),
+                }
+            }
+            MergeStrategy::Command(command) => {
+                let key = (path.to_owned(), blake2b_hash(contents.as_slice()).to_vec());
+                if let Some(result) = self.command_results.lock().unwrap().get(&key) {
+                    return Ok(result.clone().map(MergeResult::Resolved));
+                }
+                let result = self.run_command(command, path, contents)?;
+                self.command_results
+                    .lock()
+                    .unwrap()
+                    .insert(key, result.clone());
+                Ok(result.map(MergeResult::Resolved))
+            }
+            MergeStrategy::Structured(merger) => {
+                Ok(structured_merge::merge_contents(merger.as_ref(), contents)
+                    .map(MergeResult::Resolved))
+            }
+        }
+    }
+
+    fn run_command(
+        &self,
+        command: &[String],
+        path: &RepoPath,
+        contents: &Merge<Vec<u8>>,
+    ) -> Result<Option<BString>, MergeDriverError> {
+        if contents.num_sides() != 2 {
+            return Ok(None);
+        }
+        let base = contents.get_remove(0).unwrap();
+        let left = contents.get_add(0).unwrap();
+        let right = contents.get_add(1).unwrap();
+        let set_up_err = |source| MergeDriverError::SetUp {
+            name: self.name.clone(),
+            source,
+        };
+        let temp_dir = tempfile::Builder::new()
+            .prefix("jj-merge-driver-")
+            .tempdir()
+            .map_err(set_up_err)?;
+        // Keep the file name so that the command can detect the file type.
+        let file_name = path
+            .split()
+            .map_or("file", |(_, name)| name.as_internal_str());
+        let mut variables = vec![("path", path.as_internal_file_string().to_owned())];
+        for (label, content) in [("base", base), ("left", left), ("right", right)] {
+            let dir = temp_dir.path().join(label);
+            fs::create_dir(&dir).map_err(set_up_err)?;
+            let file = dir.join(file_name);
+            fs::write(&file, content).map_err(set_up_err)?;
+            variables.push((label, file.to_string_lossy().into_owned()));
+        }
+        let output_dir = temp_dir.path().join("output");
+        fs::create_dir(&output_dir).map_err(set_up_err)?;
+        let output_file = output_dir.join(file_name);
+        variables.push(("output", output_file.to_string_lossy().into_owned()));
+
+        let args = command
+            .iter()
+            .map(|arg| {
+                variables.iter().fold(arg.clone(), |arg, (name, value)| {
+                    arg.replace(&format!("${name}"), value)
+                })
+            })
+            .collect_vec();
+        let Some((program, args)) = args.split_first() else {
+            return Ok(None);
+        };
+        let status = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .status()
+            .map_err(|source| MergeDriverError::Execute {
+                name: self.name.clone(),
+                source,
+            })?;
+        if !status.success() {
+            return Ok(None);
+        }
+        match fs::read(&output_file) {
+            Ok(content) => Ok(Some(content.into())),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(set_up_err(err)),
+        }
+    }
+}
+
+/// The configured merge drivers.
+#[derive(Clone, Debug, Default)]
+pub struct MergeDrivers {
+    drivers: Vec<MergeDriver>,
+}
+
+impl MergeDrivers {
+    /// Creates a set of drivers. The first driver matching a path is used.
+    pub fn new(drivers: Vec<MergeDriver>) -> Self {
+        Self { drivers }
+    }
+
+    /// Loads the drivers from the `merge.drivers` table. Drivers are ordered by
+    /// name.
+    pub fn from_settings(settings: &UserSettings) -> Result<Self, MergeDriverConfigError> {
+        let drivers = settings
+            .table_keys("merge.drivers")
+            .sorted()
+            .map(|name| {
+                let raw: RawMergeDriverConfig = settings.get(["merge", "drivers", name])?;
+                let strategy = match (raw.strategy, raw.command) {
+                    (Some(RawMergeStrategy::Ours), None) => MergeStrategy::Ours,
+                    (Some(RawMergeStrategy::Theirs), None) => MergeStrategy::Theirs,
+                    (Some(RawMergeStrategy::Union), None) => MergeStrategy::Union,
+                    (Some(RawMergeStrategy::Words), None) => MergeStrategy::Words,
+                    (Some(RawMergeStrategy::Json), None) => {
+                        MergeStrategy::Structured(Arc::new(JsonMerger))
+                    }
+                    (Some(RawMergeStrategy::Toml), None) => {
+                        MergeStrategy::Structured(Arc::new(TomlMerger))
+                    }
+                    (None, Some(command)) => MergeStrategy::Command(command),
+                    _ => {
+                        return Err(MergeDriverConfigError::Ambiguous {
+                            name: name.to_owned(),
+                        });
+                    }
+                };
+                // Patterns are relative to the workspace root.
+                let path_converter = RepoPathUiConverter::Fs {
+                    cwd: "".into(),
+                    base: "".into(),
+                };
+                let expression = raw
+                    .patterns
+                    .iter()
+                    .map(|pattern| {
+                        fileset::parse(&mut FilesetDiagnostics::new(), pattern, &path_converter)
+                    })
+                    .try_collect()
+                    .map_err(|source| MergeDriverConfigError::Pattern {
+                        name: name.to_owned(),
+                        source,
+                    })?;
+                Ok(MergeDriver::new(
+                    name.to_owned(),
+                    FilesetExpression::union_all(expression),
+                    strategy,
+                ))
+            })
+            .try_collect()?;
+        Ok(Self { drivers })
+    }
+
+    /// Returns true if no drivers are configured.
+    pub fn is_empty(&self) -> bool {
+        self.drivers.is_empty()
+    }
+
+    /// Returns the driver to use for the file at `path`, if any.
+    pub fn find(&self, path: &RepoPath) -> Option<&MergeDriver> {
+        self.drivers
+            .iter()
+            .find(|driver| driver.matcher.matches(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunks(hunks: &[&[&str]]) -> Vec<Merge<BString>> {
+        hunks
+            .iter()
+            .map(|terms| {
+                Merge::from_vec(terms.iter().map(|term| BString::from(*term)).collect_vec())
+            })
+            .collect()
+    }
+
+    fn resolve(strategy: MergeStrategy, hunks: &[Merge<BString>]) -> Option<MergeResult> {
+        let driver = MergeDriver::new("test".to_owned(), FilesetExpression::all(), strategy);
+        let path = RepoPath::from_internal_string("file");
+        driver
+            .resolve(path, &Merge::resolved(vec![]), hunks)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_hunks() {
+        let hunks = hunks(&[&["a\n"], &["left\n", "base\n", "right\n"], &["z\n"]]);
+        assert_eq!(
+            resolve(MergeStrategy::Ours, &hunks),
+            Some(MergeResult::Resolved("a\nleft\nz\n".into()))
+        );
+        assert_eq!(
+            resolve(MergeStrategy::Theirs, &hunks),
+            Some(MergeResult::Resolved("a\nright\nz\n".into()))
+        );
+        assert_eq!(
+            resolve(MergeStrategy::Union, &hunks),
+            Some(MergeResult::Resolved("a\nleft\nright\nz\n".into()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_words() {
+        // Only the second hunk can be resolved word by word
+        let conflicts = hunks(&[
+            &["a x\n", "a\n", "a y\n"],
+            &["b\n"],
+            &["c1 d\n", "c d\n", "c d1\n"],
+        ]);
+        assert_eq!(
+            resolve(MergeStrategy::Words, &conflicts),
+            Some(MergeResult::Conflict(hunks(&[
+                &["a x\n", "a\n", "a y\n"],
+                &["b\nc1 d1\n"],
+            ])))
+        );
+        // No hunk can be resolved word by word
+        let conflicts = hunks(&[&["a x\n", "a\n", "a y\n"]]);
+        assert_eq!(resolve(MergeStrategy::Words, &conflicts), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve_command_caches_result() {
+        let temp_dir = crate::tests::new_temp_dir();
+        let counter = temp_dir.path().join("counter");
+        let command = vec![
+            "sh".to_owned(),
+            "-c".to_owned(),
+            format!("echo >> '{}'; cat \"$1\" > \"$2\"", counter.display()),
+            "sh".to_owned(),
+            "$left".to_owned(),
+            "$output".to_owned(),
+        ];
+        let driver = MergeDriver::new(
+            "test".to_owned(),
+            FilesetExpression::all(),
+            MergeStrategy::Command(command),
+        );
+        let path = RepoPath::from_internal_string("file");
+        let contents = Merge::from_vec(vec![b"left\n".to_vec(), vec![], b"right\n".to_vec()]);
+        let hunks = hunks(&[&["left\n", "", "right\n"]]);
+        for _ in 0..2 {
+            assert_eq!(
+                driver.resolve(path, &contents, &hunks).unwrap(),
+                Some(MergeResult::Resolved("left\n".into()))
+            );
+        }
+        assert_eq!(fs::read(&counter).unwrap(), b"\n");
+    }
+
+    #[test]
+    fn test_resolve_command_requires_two_sides() {
+        let hunks = hunks(&[&["left\n", "base\n", "right\n"]]);
+        assert_eq!(
+            resolve(MergeStrategy::Command(vec!["false".to_owned()]), &hunks),
+            None
+        );
+    }
+}
@@ -55,7 +55,8 @@ use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
 use crate::repo_path::RepoPathComponent;
 use crate::store::Store;
-use crate::tree::try_resolve_file_conflict;
+use crate::tree::merge_file_conflict;
+use crate::tree::FileConflictMerge;
 use crate::tree::Tree;
 use crate::tree_builder::TreeBuilder;
 
@@ -540,11 +541,23 @@ async fn try_resolve_file_values<T: Borrow<TreeValue>>(
         .simplify();
     // No fast path for simplified.is_resolved(). If it could be resolved, it would
     // have been caught by values.resolve_trivial() above.
-    if let Some(resolved) = try_resolve_file_conflict(store, path, &simplified).await? {
-        Ok(Some(Merge::normal(resolved)))
-    } else {
+    match merge_file_conflict(store, path, &simplified).await? {
+        Some(FileConflictMerge::Resolved(resolved)) => Ok(Some(Merge::normal(resolved))),
+        Some(FileConflictMerge::Partial(new_ids)) => {
+            // Rewrite the unsimplified values so the conflict keeps the same
+            // number of sides.
+            Ok(Some(values.map(|value| {
+                value.as_ref().map(|value| match value.borrow() {
+                    TreeValue::File { id, executable } => TreeValue::File {
+                        id: new_ids.get(id).unwrap_or(id).clone(),
+                        executable: *executable,
+                    },
+                    value => value.clone(),
+                })
+            })))
+        }
         // Failed to merge the files, or the paths are not files
-        Ok(None)
+        None => Ok(None),
     }
 }
 
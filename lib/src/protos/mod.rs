@@ -11,3 +11,6 @@ pub mod simple_store {
 pub mod working_copy {
     include!("working_copy.rs");
 }
+pub mod workspace_store {
+    include!("workspace_store.rs");
+}
@@ -35,6 +35,13 @@ pub struct FileStateEntry {
 pub struct SparsePatterns {
     #[prost(string, repeated, tag = "1")]
     pub prefixes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Fileset expression with paths relative to the workspace root. If set,
+    /// `prefixes` is ignored.
+    #[prost(string, optional, tag = "2")]
+    pub fileset: ::core::option::Option<::prost::alloc::string::String>,
+    /// Name of the sparse profile the `fileset` was read from, if any.
+    #[prost(string, optional, tag = "3")]
+    pub profile: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -42,6 +42,7 @@ use crate::commit::Commit;
 use crate::commit::CommitByCommitterTimestamp;
 use crate::commit_builder::CommitBuilder;
 use crate::commit_builder::DetachedCommitBuilder;
+use crate::config::ConfigGetError;
 use crate::dag_walk;
 use crate::default_index::DefaultIndexStore;
 use crate::default_index::DefaultMutableIndex;
@@ -55,6 +56,8 @@ use crate::index::IndexStore;
 use crate::index::MutableIndex;
 use crate::index::ReadonlyIndex;
 use crate::merge::MergeBuilder;
+use crate::merge_driver::MergeDriverConfigError;
+use crate::merge_driver::MergeDrivers;
 use crate::object_id::HexPrefix;
 use crate::object_id::ObjectId as _;
 use crate::object_id::PrefixResolution;
@@ -81,6 +84,7 @@ use crate::refs::diff_named_ref_targets;
 use crate::refs::diff_named_remote_refs;
 use crate::refs::merge_ref_targets;
 use crate::refs::merge_remote_refs;
+use crate::resolution_cache::ResolutionCache;
 use crate::revset;
 use crate::revset::RevsetExpression;
 use crate::revset::RevsetIteratorExt as _;
@@ -157,6 +161,8 @@ pub enum RepoInitError {
     OpHeadsStore(#[from] OpHeadsStoreError),
     #[error(transparent)]
     Path(#[from] PathError),
+    #[error(transparent)]
+    MergeDrivers(#[from] MergeDriverConfigError),
 }
 
 impl ReadonlyRepo {
@@ -171,7 +177,13 @@ impl ReadonlyRepo {
     }
 
     pub fn default_index_store_initializer() -> &'static IndexStoreInitializer<'static> {
-        &|_settings, store_path| Ok(Box::new(DefaultIndexStore::init(store_path)?))
+        &|settings, store_path| {
+            let changed_paths = settings
+                .get_bool("index.changed-paths")
+                .map_err(|err| BackendInitError(err.into()))?;
+            let store = DefaultIndexStore::init(store_path)?.with_changed_paths(changed_paths);
+            Ok(Box::new(store))
+        }
     }
 
     pub fn default_submodule_store_initializer() -> &'static SubmoduleStoreInitializer<'static> {
@@ -196,7 +208,11 @@ impl ReadonlyRepo {
         let backend = backend_initializer(settings, &store_path)?;
         let backend_path = store_path.join("type");
         fs::write(&backend_path, backend.name()).context(&backend_path)?;
-        let store = Store::new(backend, signer);
+        let signer = signer.with_persistent_cache(&repo_path.join("signature_cache"));
+        let merge_drivers = MergeDrivers::from_settings(settings)?;
+        let resolution_cache = ResolutionCache::from_settings(settings, &repo_path)
+            .map_err(|err| BackendInitError(err.into()))?;
+        let store = Store::new(backend, signer, merge_drivers, resolution_cache);
 
         let op_store_path = repo_path.join("op_store");
         fs::create_dir(&op_store_path).context(&op_store_path)?;
@@ -439,7 +455,13 @@ impl Default for StoreFactories {
         // Index
         factories.add_index_store(
             DefaultIndexStore::name(),
-            Box::new(|_settings, store_path| Ok(Box::new(DefaultIndexStore::load(store_path)))),
+            Box::new(|settings, store_path| {
+                let changed_paths = settings
+                    .get_bool("index.changed-paths")
+                    .map_err(|err| BackendLoadError(err.into()))?;
+                let store = DefaultIndexStore::load(store_path).with_changed_paths(changed_paths);
+                Ok(Box::new(store))
+            }),
         );
 
         // SubmoduleStores
@@ -468,6 +490,10 @@ pub enum StoreLoadError {
     Backend(#[from] BackendLoadError),
     #[error(transparent)]
     Signing(#[from] SignInitError),
+    #[error(transparent)]
+    MergeDrivers(#[from] MergeDriverConfigError),
+    #[error(transparent)]
+    Config(#[from] ConfigGetError),
 }
 
 impl StoreFactories {
@@ -668,7 +694,10 @@ impl RepoLoader {
     ) -> Result<Self, StoreLoadError> {
         let store = Store::new(
             store_factories.load_backend(settings, &repo_path.join("store"))?,
-            Signer::from_settings(settings)?,
+            Signer::from_settings(settings)?
+                .with_persistent_cache(&repo_path.join("signature_cache")),
+            MergeDrivers::from_settings(settings)?,
+            ResolutionCache::from_settings(settings, repo_path)?,
         );
         let root_op_data = RootOperationData {
             root_commit_id: store.root_commit_id().clone(),
@@ -0,0 +1,406 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recorded conflict resolutions, reused when the same conflict reappears.
+//!
+//! When a conflicted file is resolved by rewriting its commit in place, the
+//! resolution of each conflicted hunk is recorded, keyed by a hash of the
+//! hunk's terms. When a later merge produces the same conflicted hunks, e.g.
+//! when rebasing the same stack again, the recorded resolutions are applied.
+
+use std::fs;
+use std::io;
+use std::io::Read as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use itertools::Itertools as _;
+use pollster::FutureExt as _;
+use tempfile::NamedTempFile;
+
+use crate::backend::BackendError;
+use crate::backend::BackendResult;
+use crate::backend::TreeValue;
+use crate::config::ConfigGetError;
+use crate::content_hash::blake2b_hash;
+use crate::files;
+use crate::files::MergeResult;
+use crate::merge::Merge;
+use crate::merged_tree::MergedTree;
+use crate::object_id::ObjectId as _;
+use crate::repo_path::RepoPath;
+use crate::repo_path::RepoPathBuf;
+use crate::settings::UserSettings;
+use crate::store::Store;
+use crate::tree::read_file_conflict_contents;
+
+/// A recorded resolution of a conflicted hunk.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordedResolution {
+    /// Hash of the conflicted hunk.
+    pub key: String,
+    /// Path of the file where the resolution was recorded.
+    pub path: RepoPathBuf,
+    /// Content which replaces the conflicted hunk.
+    pub resolution: BString,
+}
+
+/// Recorded resolutions stored in a directory.
+#[derive(Debug)]
+pub struct ResolutionCache {
+    dir: PathBuf,
+}
+
+impl ResolutionCache {
+    /// Creates a cache stored in `dir`, which is created when the first
+    /// resolution is recorded.
+    pub fn new(dir: PathBuf) -> Self {
+        ResolutionCache { dir }
+    }
+
+    /// Creates the cache of the repo at `repo_path` if reusing resolutions is
+    /// enabled by the `merge.reuse-resolutions` setting.
+    pub fn from_settings(
+        settings: &UserSettings,
+        repo_path: &Path,
+    ) -> Result<Option<Self>, ConfigGetError> {
+        let enabled = settings.get_bool("merge.reuse-resolutions")?;
+        Ok(enabled.then(|| Self::new(Self::dir_in_repo(repo_path))))
+    }
+
+    /// Returns the directory where the repo at `repo_path` stores the
+    /// resolutions.
+    pub fn dir_in_repo(repo_path: &Path) -> PathBuf {
+        repo_path.join("resolutions")
+    }
+
+    /// Returns the key of a conflicted hunk. The key doesn't depend on the
+    /// order of the sides, so that the resolution can be reused when merging
+    /// in the opposite direction.
+    pub fn hunk_key(hunk: &Merge<BString>) -> String {
+        let removes = hunk
+            .removes()
+            .map(|term| term.to_vec())
+            .sorted()
+            .collect_vec();
+        let adds = hunk.adds().map(|term| term.to_vec()).sorted().collect_vec();
+        hex::encode(blake2b_hash(&vec![removes, adds]))
+    }
+
+    /// Looks up the recorded resolution of the hunk with the given `key`.
+    pub fn get(&self, key: &str) -> Option<BString> {
+        let mut content = vec![];
+        fs::File::open(self.dir.join(key).join("resolution"))
+            .and_then(|mut file| file.read_to_end(&mut content))
+            .ok()?;
+        Some(content.into())
+    }
+
+    /// Records the `resolution` of the hunk with the given `key`, replacing
+    /// any existing recording.
+    pub fn insert(&self, key: &str, path: &RepoPath, resolution: &[u8]) -> io::Result<()> {
+        let entry_dir = self.dir.join(key);
+        fs::create_dir_all(&entry_dir)?;
+        for (name, content) in [
+            ("path", path.as_internal_file_string().as_bytes()),
+            ("resolution", resolution),
+        ] {
+            let mut temp_file = NamedTempFile::new_in(&entry_dir)?;
+            temp_file.write_all(content)?;
+            // Unlike content-addressed files, the existing file has to be
+            // replaced.
+            temp_file
+                .persist(entry_dir.join(name))
+                .map_err(|err| err.error)?;
+        }
+        Ok(())
+    }
+
+    /// Lists all recorded resolutions ordered by key.
+    pub fn list(&self) -> io::Result<Vec<RecordedResolution>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+        let mut resolutions = vec![];
+        for entry in entries {
+            let entry = entry?;
+            let Ok(key) = entry.file_name().into_string() else {
+                continue;
+            };
+            let Some(resolution) = self.get(&key) else {
+                continue;
+            };
+            let path = fs::read_to_string(entry.path().join("path"))
+                .ok()
+                .and_then(|path| RepoPathBuf::from_relative_path(path).ok())
+                .unwrap_or_else(RepoPathBuf::root);
+            resolutions.push(RecordedResolution {
+                key,
+                path,
+                resolution,
+            });
+        }
+        resolutions.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(resolutions)
+    }
+
+    /// Forgets the resolution recorded with the given `key`.
+    pub fn remove(&self, key: &str) -> io::Result<()> {
+        fs::remove_dir_all(self.dir.join(key))
+    }
+
+    /// Directory where the resolutions are stored.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Applies the recorded resolutions to the conflicted `hunks` produced by
+    /// [`files::merge()`]. Returns the resolved content if every conflicted
+    /// hunk has a recorded resolution.
+    pub fn resolve_hunks(&self, hunks: &[Merge<BString>]) -> Option<BString> {
+        let mut resolved = BString::default();
+        for hunk in hunks {
+            if let Some(content) = hunk.as_resolved() {
+                resolved.extend_from_slice(content);
+            } else {
+                resolved.extend_from_slice(&self.get(&Self::hunk_key(hunk))?);
+            }
+        }
+        Some(resolved)
+    }
+
+    /// Records how the conflicted `hunks` were resolved in the `resolved`
+    /// content of the file at `path`. Nothing is recorded if the resolved
+    /// content can't be aligned with the unconflicted hunks. Returns the
+    /// number of recorded resolutions.
+    pub fn record(
+        &self,
+        path: &RepoPath,
+        hunks: &[Merge<BString>],
+        resolved: &[u8],
+    ) -> io::Result<usize> {
+        let Some(resolutions) = align_resolutions(hunks, resolved) else {
+            return Ok(0);
+        };
+        for (hunk, resolution) in &resolutions {
+            self.insert(&Self::hunk_key(hunk), path, resolution)?;
+        }
+        Ok(resolutions.len())
+    }
+}
+
+/// Splits `resolved` into the resolutions of the conflicted `hunks`, assuming
+/// the unconflicted hunks were kept as is. Returns `None` if the unconflicted
+/// hunks can't be found at unambiguous line boundaries.
+fn align_resolutions<'a, 'b>(
+    hunks: &'a [Merge<BString>],
+    resolved: &'b [u8],
+) -> Option<Vec<(&'a Merge<BString>, &'b [u8])>> {
+    let mut rest = resolved;
+    let mut pending_conflict = None;
+    let mut resolutions = vec![];
+    for (i, hunk) in hunks.iter().enumerate() {
+        let Some(context) = hunk.as_resolved() else {
+            if pending_conflict.is_some() {
+                return None;
+            }
+            pending_conflict = Some(hunk);
+            continue;
+        };
+        match pending_conflict.take() {
+            None => {
+                rest = rest.strip_prefix(context.as_slice())?;
+            }
+            Some(conflict) => {
+                // The last context must be at the end of the file
+                let pos = if i == hunks.len() - 1 {
+                    let pos = rest.len().checked_sub(context.len())?;
+                    (rest.ends_with(context) && is_line_start(rest, pos)).then_some(pos)?
+                } else {
+                    let mut positions = (0..rest.len())
+                        .filter(|&pos| is_line_start(rest, pos))
+                        .filter(|&pos| rest[pos..].starts_with(context));
+                    let pos = positions.next()?;
+                    if positions.next().is_some() {
+                        return None;
+                    }
+                    pos
+                };
+                resolutions.push((conflict, &rest[..pos]));
+                rest = &rest[pos + context.len()..];
+            }
+        }
+    }
+    match pending_conflict {
+        Some(conflict) => resolutions.push((conflict, rest)),
+        None if !rest.is_empty() => return None,
+        None => {}
+    }
+    Some(resolutions)
+}
+
+fn is_line_start(text: &[u8], pos: usize) -> bool {
+    pos == 0 || text[pos - 1] == b'\n'
+}
+
+/// Records the resolutions of the file conflicts in `old_tree` which are
+/// resolved in `new_tree`. Returns the number of recorded resolutions.
+pub fn record_resolved_conflicts(
+    cache: &ResolutionCache,
+    store: &Store,
+    old_tree: &MergedTree,
+    new_tree: &MergedTree,
+) -> BackendResult<usize> {
+    let mut count = 0;
+    for (path, old_value) in old_tree.conflicts() {
+        let old_value = old_value?;
+        let Ok(Some(TreeValue::File { id, executable: _ })) =
+            new_tree.path_value(&path)?.into_resolved()
+        else {
+            continue;
+        };
+        // Read the conflict the same way as try_resolve_file_conflict() does,
+        // so that the hunks match when the conflict reappears.
+        let old_value = old_value.simplify();
+        let Some(file_id_conflict) = old_value.maybe_map(|term| match term {
+            Some(TreeValue::File { id, executable: _ }) => Some(id),
+            _ => None,
+        }) else {
+            continue;
+        };
+        let file_id_conflict = file_id_conflict.simplify();
+        let contents = read_file_conflict_contents(store, &path, &file_id_conflict).block_on()?;
+        let MergeResult::Conflict(hunks) = files::merge(&contents) else {
+            continue;
+        };
+        let mut resolved = vec![];
+        store
+            .read_file(&path, &id)?
+            .read_to_end(&mut resolved)
+            .map_err(|err| BackendError::ReadObject {
+                object_type: id.object_type(),
+                hash: id.hex(),
+                source: err.into(),
+            })?;
+        count += cache
+            .record(&path, &hunks, &resolved)
+            .map_err(|err| BackendError::Other(err.into()))?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunks(hunks: &[&[&str]]) -> Vec<Merge<BString>> {
+        hunks
+            .iter()
+            .map(|terms| {
+                Merge::from_vec(terms.iter().map(|term| BString::from(*term)).collect_vec())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_align_resolutions() {
+        let align = |hunks: &[Merge<BString>], resolved: &str| {
+            align_resolutions(hunks, resolved.as_bytes()).map(|resolutions| {
+                resolutions
+                    .into_iter()
+                    .map(|(hunk, resolution)| {
+                        (
+                            hunk.first().to_string(),
+                            resolution.to_str_lossy().into_owned(),
+                        )
+                    })
+                    .collect_vec()
+            })
+        };
+        let conflicts = hunks(&[
+            &["a\n"],
+            &["x1\n", "x\n", "x2\n"],
+            &["b\n"],
+            &["y1\n", "y\n", "y2\n"],
+        ]);
+        assert_eq!(
+            align(&conflicts, "a\nX\nb\nY\n"),
+            Some(vec![
+                ("x1\n".to_owned(), "X\n".to_owned()),
+                ("y1\n".to_owned(), "Y\n".to_owned()),
+            ])
+        );
+        // Resolution can be empty
+        assert_eq!(
+            align(&conflicts, "a\nb\n"),
+            Some(vec![
+                ("x1\n".to_owned(), "".to_owned()),
+                ("y1\n".to_owned(), "".to_owned()),
+            ])
+        );
+        // Unconflicted hunks were changed
+        assert_eq!(align(&conflicts, "A\nX\nb\nY\n"), None);
+        assert_eq!(align(&conflicts, "a\nX\nB\nY\n"), None);
+        // Context is only matched at line boundaries
+        assert_eq!(
+            align(&conflicts, "a\nXb\nb\nY\n"),
+            Some(vec![
+                ("x1\n".to_owned(), "Xb\n".to_owned()),
+                ("y1\n".to_owned(), "Y\n".to_owned()),
+            ])
+        );
+        // Ambiguous context
+        assert_eq!(align(&conflicts, "a\nb\nb\nY\n"), None);
+
+        let conflicts = hunks(&[&["x1\n", "x\n", "x2\n"], &["z\n"]]);
+        assert_eq!(
+            align(&conflicts, "X\nz\nz\n"),
+            Some(vec![("x1\n".to_owned(), "X\nz\n".to_owned())])
+        );
+        assert_eq!(align(&conflicts, "X\nz\nZ\n"), None);
+        assert_eq!(align(&conflicts, "Xz\n"), None);
+    }
+
+    #[test]
+    fn test_record_and_resolve_hunks() {
+        let temp_dir = crate::tests::new_temp_dir();
+        let cache = ResolutionCache::new(temp_dir.path().join("resolutions"));
+        let path = RepoPath::from_internal_string("file");
+        let conflicts = hunks(&[&["a\n"], &["x1\n", "x\n", "x2\n"], &["b\n"]]);
+        assert_eq!(cache.resolve_hunks(&conflicts), None);
+        assert_eq!(cache.list().unwrap(), vec![]);
+
+        assert_eq!(cache.record(path, &conflicts, b"a\nX\nb\n").unwrap(), 1);
+        assert_eq!(cache.resolve_hunks(&conflicts), Some("a\nX\nb\n".into()));
+        // The order of sides doesn't matter
+        let swapped = hunks(&[&["c\n"], &["x2\n", "x\n", "x1\n"]]);
+        assert_eq!(cache.resolve_hunks(&swapped), Some("c\nX\n".into()));
+        // Recording another resolution replaces the existing one
+        assert_eq!(cache.record(path, &conflicts, b"a\nY\nb\n").unwrap(), 1);
+        assert_eq!(cache.resolve_hunks(&conflicts), Some("a\nY\nb\n".into()));
+        assert_eq!(cache.record(path, &conflicts, b"a\nX\nb\n").unwrap(), 1);
+
+        let recorded = cache.list().unwrap();
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].path.as_ref(), path);
+        assert_eq!(recorded[0].resolution, "X\n");
+        cache.remove(&recorded[0].key).unwrap();
+        assert_eq!(cache.resolve_hunks(&conflicts), None);
+    }
+}
@@ -65,6 +65,7 @@ pub use crate::revset_parser::RevsetDiagnostics;
 pub use crate::revset_parser::RevsetParseError;
 pub use crate::revset_parser::RevsetParseErrorKind;
 pub use crate::revset_parser::UnaryOp;
+use crate::signing::SigStatus;
 use crate::store::Store;
 use crate::str_util::StringPattern;
 use crate::time_util::DatePattern;
@@ -180,6 +181,12 @@ pub enum RevsetFilterPredicate {
     },
     /// Commits with conflicts
     HasConflict,
+    /// Commits with a signature, whether or not it can be verified.
+    Signed,
+    /// Commits with a good signature whose key or signer matches the pattern.
+    SignedBy(StringPattern),
+    /// Commits with a signature of the given verification status.
+    SignatureStatus(SigStatus),
     /// Custom predicates provided by extensions
     Extension(Rc<dyn RevsetFilterExtension>),
 }
@@ -247,7 +254,7 @@ pub enum RevsetExpression<St: ExpressionState> {
     DagRange {
         roots: Rc<Self>,
         heads: Rc<Self>,
-        // TODO: maybe add generation_from_roots/heads?
+        generation_from_roots: Range<u64>,
     },
     // Commits reachable from "sources" within "domain"
     Reachable {
@@ -448,9 +455,20 @@ impl<St: ExpressionState> RevsetExpression<St> {
     /// Commits that are descendants of `self` and ancestors of `heads`, both
     /// inclusive.
     pub fn dag_range_to(self: &Rc<Self>, heads: &Rc<Self>) -> Rc<Self> {
+        self.dag_range_to_within(heads, GENERATION_RANGE_FULL)
+    }
+
+    /// Commits that are descendants of `self` and ancestors of `heads`, and
+    /// are within the given range of generations from `self`.
+    pub fn dag_range_to_within(
+        self: &Rc<Self>,
+        heads: &Rc<Self>,
+        generation_from_roots: Range<u64>,
+    ) -> Rc<Self> {
         Rc::new(Self::DagRange {
             roots: self.clone(),
             heads: heads.clone(),
+            generation_from_roots,
         })
     }
 
@@ -687,6 +705,18 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         };
         Ok(roots.descendants_range(generation))
     });
+    map.insert("dag_range", |diagnostics, function, context| {
+        let ([roots_arg, heads_arg], [depth_opt_arg]) = function.expect_arguments()?;
+        let roots = lower_expression(diagnostics, roots_arg, context)?;
+        let heads = lower_expression(diagnostics, heads_arg, context)?;
+        let generation = if let Some(depth_arg) = depth_opt_arg {
+            let depth = expect_literal(diagnostics, "integer", depth_arg)?;
+            0..depth
+        } else {
+            GENERATION_RANGE_FULL
+        };
+        Ok(roots.dag_range_to_within(&heads, generation))
+    });
     map.insert("connected", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let candidates = lower_expression(diagnostics, arg, context)?;
@@ -905,6 +935,24 @@ static BUILTIN_FUNCTION_MAP: Lazy<HashMap<&'static str, RevsetFunction>> = Lazy:
         function.expect_no_arguments()?;
         Ok(RevsetExpression::filter(RevsetFilterPredicate::HasConflict))
     });
+    map.insert("signed", |_diagnostics, function, _context| {
+        function.expect_no_arguments()?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::Signed))
+    });
+    map.insert("signed_by", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let pattern = expect_string_pattern(diagnostics, arg)?;
+        Ok(RevsetExpression::filter(RevsetFilterPredicate::SignedBy(
+            pattern,
+        )))
+    });
+    map.insert("signature", |diagnostics, function, _context| {
+        let [arg] = function.expect_exact_arguments()?;
+        let status = expect_literal(diagnostics, "signature status", arg)?;
+        Ok(RevsetExpression::filter(
+            RevsetFilterPredicate::SignatureStatus(status),
+        ))
+    });
     map.insert("present", |diagnostics, function, context| {
         let [arg] = function.expect_exact_arguments()?;
         let expression = lower_expression(diagnostics, arg, context)?;
@@ -1220,10 +1268,17 @@ fn try_transform_expression<St: ExpressionState, E>(
                     generation: generation.clone(),
                 }
             }),
-            RevsetExpression::DagRange { roots, heads } => {
-                transform_rec_pair((roots, heads), pre, post)?
-                    .map(|(roots, heads)| RevsetExpression::DagRange { roots, heads })
-            }
+            RevsetExpression::DagRange {
+                roots,
+                heads,
+                generation_from_roots,
+            } => transform_rec_pair((roots, heads), pre, post)?.map(|(roots, heads)| {
+                RevsetExpression::DagRange {
+                    roots,
+                    heads,
+                    generation_from_roots: generation_from_roots.clone(),
+                }
+            }),
             RevsetExpression::Reachable { sources, domain } => {
                 transform_rec_pair((sources, domain), pre, post)?
                     .map(|(sources, domain)| RevsetExpression::Reachable { sources, domain })
@@ -1409,10 +1464,20 @@ where
             }
             .into()
         }
-        RevsetExpression::DagRange { roots, heads } => {
+        RevsetExpression::DagRange {
+            roots,
+            heads,
+            generation_from_roots,
+        } => {
             let roots = folder.fold_expression(roots)?;
             let heads = folder.fold_expression(heads)?;
-            RevsetExpression::DagRange { roots, heads }.into()
+            let generation_from_roots = generation_from_roots.clone();
+            RevsetExpression::DagRange {
+                roots,
+                heads,
+                generation_from_roots,
+            }
+            .into()
         }
         RevsetExpression::Reachable { sources, domain } => {
             let sources = folder.fold_expression(sources)?;
@@ -2296,10 +2361,14 @@ impl VisibilityResolutionContext<'_> {
                 heads: self.resolve(heads).into(),
                 generation: generation.clone(),
             },
-            RevsetExpression::DagRange { roots, heads } => ResolvedExpression::DagRange {
+            RevsetExpression::DagRange {
+                roots,
+                heads,
+                generation_from_roots,
+            } => ResolvedExpression::DagRange {
                 roots: self.resolve(roots).into(),
                 heads: self.resolve(heads).into(),
-                generation_from_roots: GENERATION_RANGE_FULL,
+                generation_from_roots: generation_from_roots.clone(),
             },
             RevsetExpression::Reachable { sources, domain } => ResolvedExpression::Reachable {
                 sources: self.resolve(sources).into(),
@@ -2810,6 +2879,7 @@ mod tests {
         DagRange {
             roots: CommitRef(Symbol("foo")),
             heads: CommitRef(WorkingCopy(WorkspaceId("default"))),
+            generation_from_roots: 0..18446744073709551615,
         }
         "#);
         insta::assert_debug_snapshot!(
@@ -2817,6 +2887,7 @@ mod tests {
         DagRange {
             roots: CommitRef(Symbol("foo")),
             heads: CommitRef(Symbol("foo")),
+            generation_from_roots: 0..18446744073709551615,
         }
         "#);
         insta::assert_debug_snapshot!(
@@ -3033,6 +3104,7 @@ mod tests {
         DagRange {
             roots: CommitRef(Symbol("foo")),
             heads: CommitRef(Symbol("bar")),
+            generation_from_roots: 0..18446744073709551615,
         }
         "#);
         // Parse the nullary "dag range" operator
@@ -3469,6 +3541,7 @@ mod tests {
         DagRange {
             roots: CommitRef(Bookmarks(Substring(""))),
             heads: CommitRef(Tags(Substring(""))),
+            generation_from_roots: 0..18446744073709551615,
         }
         "#);
 
@@ -4278,6 +4351,7 @@ mod tests {
                 generation: 2..3,
             },
             heads: CommitRef(Symbol("bar")),
+            generation_from_roots: 0..18446744073709551615,
         }
         "#);
     }
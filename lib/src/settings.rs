@@ -109,7 +109,7 @@ impl SignSettings {
     /// and email.
     pub fn should_sign(&self, commit: &Commit) -> bool {
         match self.behavior {
-            SignBehavior::Drop => false,
+            SignBehavior::Drop | SignBehavior::OnPush => false,
             SignBehavior::Keep => {
                 commit.secure_sig.is_some() && commit.author.email == self.user_email
             }
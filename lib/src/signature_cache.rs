@@ -0,0 +1,348 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent cache of signature verification results.
+//!
+//! Verifying a signature usually spawns an external program, which is slow
+//! when done for every commit shown by `jj log`. The results are stored in a
+//! [`TableStore`] keyed by commit id. Each combination of backend
+//! configurations and keyring states gets its own table directory, so changing
+//! the configuration or importing keys invalidates the cached results.
+//!
+//! Good results are only trusted for [`GOOD_VERIFICATION_TTL`] so that keys
+//! which expired or were revoked since are eventually noticed.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use crate::backend::CommitId;
+use crate::object_id::ObjectId as _;
+use crate::signing::SigStatus;
+use crate::signing::Verification;
+use crate::stacked_table::ReadonlyTable;
+use crate::stacked_table::TableSegment as _;
+use crate::stacked_table::TableStore;
+use crate::stacked_table::TableStoreError;
+
+/// Version of the serialized [`Verification`] format. Bump this to start over
+/// with an empty cache.
+pub(crate) const VERIFICATION_CACHE_FORMAT_VERSION: u32 = 2;
+
+/// How long a good verification result is reused before the signature is
+/// checked again.
+const GOOD_VERIFICATION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Cache directories of other backend states are removed once they haven't
+/// been written to for this long.
+const STALE_DIR_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+#[derive(Default)]
+struct CacheState {
+    table_store: Option<TableStore>,
+    head: Option<Arc<ReadonlyTable>>,
+    pending: BTreeMap<CommitId, Verification>,
+}
+
+/// Cache of verification results in a directory. New results are kept in
+/// memory and written out when the cache is dropped.
+pub(crate) struct VerificationCache {
+    dir: PathBuf,
+    state: Mutex<CacheState>,
+}
+
+impl VerificationCache {
+    /// Creates a cache stored in `dir`, which is created when the first result
+    /// is written. Other directories next to `dir` which haven't been used for
+    /// a while are considered stale and removed at that point.
+    pub fn new(dir: PathBuf) -> Self {
+        VerificationCache {
+            dir,
+            state: Mutex::new(CacheState::default()),
+        }
+    }
+
+    pub fn get(&self, commit_id: &CommitId) -> Option<Verification> {
+        let mut state = self.state.lock().unwrap();
+        if let Some(verification) = state.pending.get(commit_id) {
+            return Some(verification.clone());
+        }
+        if state.head.is_none() {
+            if !self.dir.join("heads").is_dir() {
+                return None;
+            }
+            let table_store = state.table_store.get_or_insert_with(|| {
+                TableStore::load(self.dir.clone(), commit_id.as_bytes().len())
+            });
+            match table_store.get_head() {
+                Ok(head) => state.head = Some(head),
+                Err(err) => {
+                    tracing::warn!(?err, "failed to load signature verification cache");
+                    return None;
+                }
+            }
+        }
+        let table_store = state.table_store.as_ref()?;
+        if commit_id.as_bytes().len() != table_store.key_size() {
+            return None;
+        }
+        let value = state.head.as_ref()?.get_value(commit_id.as_bytes())?;
+        let (verification, verified_at) = deserialize_verification(value)?;
+        is_fresh(&verification, verified_at, SystemTime::now()).then_some(verification)
+    }
+
+    pub fn insert(&self, commit_id: CommitId, verification: Verification) {
+        let mut state = self.state.lock().unwrap();
+        state.pending.insert(commit_id, verification);
+    }
+
+    /// Writes the pending results to disk.
+    pub fn flush(&self) -> Result<(), VerificationCacheError> {
+        let mut state = self.state.lock().unwrap();
+        let Some((first_id, _)) = state.pending.first_key_value() else {
+            return Ok(());
+        };
+        let key_size = first_id.as_bytes().len();
+        if state.table_store.is_none() {
+            state.table_store = Some(self.create_table_store(key_size)?);
+        }
+        let table_store = state.table_store.as_ref().unwrap();
+        let (head, _lock) = table_store.get_head_locked()?;
+        let mut mut_table = head.start_mutation();
+        let now = SystemTime::now();
+        for (commit_id, verification) in &state.pending {
+            if commit_id.as_bytes().len() == table_store.key_size() {
+                let value = serialize_verification(verification, now);
+                mut_table.add_entry(commit_id.to_bytes(), value);
+            }
+        }
+        let head = table_store.save_table(mut_table)?;
+        state.head = Some(head);
+        state.pending.clear();
+        Ok(())
+    }
+
+    fn create_table_store(&self, key_size: usize) -> Result<TableStore, VerificationCacheError> {
+        let heads_dir = self.dir.join("heads");
+        if !heads_dir.is_dir() {
+            if let Some(parent_dir) = self.dir.parent() {
+                remove_stale_dirs(parent_dir, &self.dir, STALE_DIR_AGE);
+            }
+            fs::create_dir_all(&heads_dir)?;
+        }
+        Ok(TableStore::load(self.dir.clone(), key_size))
+    }
+}
+
+impl Drop for VerificationCache {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            tracing::warn!(?err, "failed to save signature verification cache");
+        }
+    }
+}
+
+impl fmt::Debug for VerificationCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerificationCache")
+            .field("dir", &self.dir)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum VerificationCacheError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    TableStore(#[from] TableStoreError),
+}
+
+/// Whether a cached result verified at `verified_at` can still be used.
+fn is_fresh(verification: &Verification, verified_at: SystemTime, now: SystemTime) -> bool {
+    match verification.status {
+        SigStatus::Good => now
+            .duration_since(verified_at)
+            .is_ok_and(|age| age < GOOD_VERIFICATION_TTL),
+        SigStatus::Unknown | SigStatus::Bad => true,
+    }
+}
+
+/// Removes the caches for other backend states which haven't been written to
+/// for `max_age`, so switching back and forth between configurations keeps
+/// the recently used caches. Errors are ignored since another process might be
+/// using them.
+fn remove_stale_dirs(parent_dir: &Path, current_dir: &Path, max_age: Duration) {
+    let Ok(entries) = fs::read_dir(parent_dir) else {
+        return;
+    };
+    let now = SystemTime::now();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path == current_dir || !path.is_dir() {
+            continue;
+        }
+        // The heads directory is updated whenever new results are written
+        let last_used = fs::metadata(path.join("heads"))
+            .or_else(|_| fs::metadata(&path))
+            .and_then(|metadata| metadata.modified());
+        let is_stale =
+            last_used.is_ok_and(|time| now.duration_since(time).is_ok_and(|age| age >= max_age));
+        if is_stale {
+            fs::remove_dir_all(&path).ok();
+        }
+    }
+}
+
+fn serialize_verification(verification: &Verification, verified_at: SystemTime) -> Vec<u8> {
+    let mut buf = vec![match verification.status {
+        SigStatus::Good => 0,
+        SigStatus::Unknown => 1,
+        SigStatus::Bad => 2,
+    }];
+    let timestamp = verified_at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    buf.extend(timestamp.to_le_bytes());
+    for field in [&verification.key, &verification.display] {
+        if let Some(value) = field {
+            buf.push(1);
+            buf.extend(u32::try_from(value.len()).unwrap().to_le_bytes());
+            buf.extend(value.as_bytes());
+        } else {
+            buf.push(0);
+        }
+    }
+    buf
+}
+
+fn deserialize_verification(mut data: &[u8]) -> Option<(Verification, SystemTime)> {
+    let (&status, rest) = data.split_first()?;
+    let status = match status {
+        0 => SigStatus::Good,
+        1 => SigStatus::Unknown,
+        2 => SigStatus::Bad,
+        _ => return None,
+    };
+    let (timestamp, rest) = rest.split_first_chunk::<8>()?;
+    let verified_at = SystemTime::UNIX_EPOCH + Duration::from_secs(u64::from_le_bytes(*timestamp));
+    data = rest;
+    let mut read_field = || -> Option<Option<String>> {
+        let (&present, rest) = data.split_first()?;
+        data = rest;
+        if present == 0 {
+            return Some(None);
+        }
+        let (len, rest) = data.split_first_chunk::<4>()?;
+        let len = u32::from_le_bytes(*len) as usize;
+        let value = rest.get(..len)?;
+        data = &rest[len..];
+        Some(Some(String::from_utf8(value.to_vec()).ok()?))
+    };
+    let key = read_field()?;
+    let display = read_field()?;
+    Some((Verification::new(status, key, display), verified_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    #[test]
+    fn test_serialize_verification() {
+        for verification in [
+            Verification::unknown(),
+            Verification::new(SigStatus::Good, Some("key".to_owned()), None),
+            Verification::new(SigStatus::Bad, None, Some("Some One <x@y.z>".to_owned())),
+        ] {
+            let verified_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1234);
+            let data = serialize_verification(&verification, verified_at);
+            assert_eq!(
+                deserialize_verification(&data),
+                Some((verification, verified_at))
+            );
+        }
+        assert_eq!(deserialize_verification(&[]), None);
+        assert_eq!(deserialize_verification(&[0; 8]), None);
+        assert_eq!(
+            deserialize_verification(&[0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 9, 0, 0, 0]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_fresh() {
+        let good = Verification::new(SigStatus::Good, Some("key".to_owned()), None);
+        let bad = Verification::new(SigStatus::Bad, Some("key".to_owned()), None);
+        let now = SystemTime::now();
+        let old = now - GOOD_VERIFICATION_TTL;
+        assert!(is_fresh(&good, now, now));
+        assert!(!is_fresh(&good, old, now));
+        assert!(is_fresh(&bad, old, now));
+        assert!(is_fresh(&Verification::unknown(), old, now));
+    }
+
+    #[test]
+    fn test_remove_stale_dirs() {
+        let temp_dir = new_temp_dir();
+        let current_dir = temp_dir.path().join("current");
+        let other_dir = temp_dir.path().join("other");
+        fs::create_dir_all(current_dir.join("heads")).unwrap();
+        fs::create_dir_all(other_dir.join("heads")).unwrap();
+
+        // Recently used caches are kept
+        remove_stale_dirs(temp_dir.path(), &current_dir, STALE_DIR_AGE);
+        assert!(current_dir.is_dir());
+        assert!(other_dir.is_dir());
+
+        remove_stale_dirs(temp_dir.path(), &current_dir, Duration::ZERO);
+        assert!(current_dir.is_dir());
+        assert!(!other_dir.exists());
+    }
+
+    #[test]
+    fn test_verification_cache_roundtrip() {
+        let temp_dir = new_temp_dir();
+        let dir = temp_dir.path().join("current");
+        let id1 = CommitId::from_hex("0123");
+        let id2 = CommitId::from_hex("4567");
+        let good = Verification::new(SigStatus::Good, Some("key".to_owned()), None);
+
+        let cache = VerificationCache::new(dir.clone());
+        assert_eq!(cache.get(&id1), None);
+        cache.insert(id1.clone(), good.clone());
+        assert_eq!(cache.get(&id1), Some(good.clone()));
+        drop(cache);
+        assert!(dir.is_dir());
+
+        let cache = VerificationCache::new(dir.clone());
+        assert_eq!(cache.get(&id1), Some(good.clone()));
+        assert_eq!(cache.get(&id2), None);
+        cache.insert(id2.clone(), Verification::unknown());
+        cache.flush().unwrap();
+        drop(cache);
+
+        let cache = VerificationCache::new(dir);
+        assert_eq!(cache.get(&id1), Some(good));
+        assert_eq!(cache.get(&id2), Some(Verification::unknown()));
+    }
+}
@@ -17,9 +17,14 @@
 
 use std::fmt::Debug;
 use std::fmt::Display;
+use std::path::Path;
+use std::str::FromStr;
 use std::sync::Mutex;
+use std::time::SystemTime;
 
+use blake2::Blake2b512;
 use clru::CLruCache;
+use digest::Digest as _;
 use thiserror::Error;
 
 use crate::backend::CommitId;
@@ -27,6 +32,8 @@ use crate::config::ConfigGetError;
 use crate::gpg_signing::GpgBackend;
 use crate::gpg_signing::GpgsmBackend;
 use crate::settings::UserSettings;
+use crate::signature_cache::VerificationCache;
+use crate::signature_cache::VERIFICATION_CACHE_FORMAT_VERSION;
 use crate::ssh_signing::SshBackend;
 use crate::store::COMMIT_CACHE_CAPACITY;
 #[cfg(feature = "testing")]
@@ -54,6 +61,19 @@ impl Display for SigStatus {
     }
 }
 
+impl FromStr for SigStatus {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "good" => Ok(SigStatus::Good),
+            "unknown" => Ok(SigStatus::Unknown),
+            "bad" => Ok(SigStatus::Bad),
+            _ => Err("Expected 'good', 'unknown', or 'bad'"),
+        }
+    }
+}
+
 /// The result of a signature verification.
 /// Key and display are optional additional info that backends can or can not
 /// provide to add additional information for the templater to potentially show.
@@ -114,6 +134,62 @@ pub trait SigningBackend: Debug + Send + Sync {
     /// verify(data, sign(data)?)?.status == SigStatus::Good
     /// ```
     fn verify(&self, data: &[u8], signature: &[u8]) -> SignResult<Verification>;
+
+    /// Describes what the results of `verify` depend on besides the data and
+    /// signature, such as the configuration and the keyrings that are read.
+    ///
+    /// Verification results are cached persistently as long as this doesn't
+    /// change. Returns `None` if the results shouldn't be cached across
+    /// processes.
+    fn verification_state(&self) -> Option<VerificationState> {
+        None
+    }
+}
+
+/// Configuration and key material that verification results depend on. See
+/// [`SigningBackend::verification_state()`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationState {
+    data: Vec<u8>,
+}
+
+impl VerificationState {
+    /// Creates an empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a configuration value.
+    pub fn add_value(&mut self, value: impl AsRef<[u8]>) -> &mut Self {
+        let value = value.as_ref();
+        self.data
+            .extend(u64::try_from(value.len()).unwrap().to_le_bytes());
+        self.data.extend(value);
+        self
+    }
+
+    /// Adds the path, size, and modification time of a file such as a keyring,
+    /// so the state changes when the file does. A missing file is also
+    /// recorded as such.
+    pub fn add_file(&mut self, path: &Path) -> &mut Self {
+        self.add_value(path.as_os_str().as_encoded_bytes());
+        let metadata = path.metadata().ok();
+        let size = metadata.as_ref().map(|metadata| metadata.len());
+        let mtime = metadata
+            .and_then(|metadata| metadata.modified().ok())
+            .and_then(|time| time.duration_since(SystemTime::UNIX_EPOCH).ok());
+        match (size, mtime) {
+            (Some(size), Some(mtime)) => self
+                .add_value(size.to_le_bytes())
+                .add_value(mtime.as_nanos().to_le_bytes()),
+            _ => self.add_value([]),
+        }
+    }
+
+    /// Returns the serialized state.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
 }
 
 /// An error type for the signing/verifying operations
@@ -159,6 +235,10 @@ pub enum SignBehavior {
     /// Always sign commits, regardless of who authored or signed them before.
     /// This is what jj does on `jj sign -f`.
     Force,
+    /// Don't sign commits when they are created or rewritten, but sign own
+    /// commits when they are pushed. Existing signatures are dropped on
+    /// rewrite, as with `Drop`.
+    OnPush,
 }
 
 /// Wraps low-level signing backends and adds caching, similar to `Store`.
@@ -172,6 +252,8 @@ pub struct Signer {
     /// for ownership reasons.
     backends: Vec<Box<dyn SigningBackend>>,
     cache: Mutex<CLruCache<CommitId, Verification>>,
+    /// Cache of verification results shared with other processes.
+    persistent_cache: Option<VerificationCache>,
 }
 
 impl Signer {
@@ -210,9 +292,33 @@ impl Signer {
             main_backend,
             backends: other_backends,
             cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
+            persistent_cache: None,
         }
     }
 
+    /// Caches verification results persistently in a subdirectory of `dir`
+    /// specific to the current [`VerificationState`] of the backends.
+    ///
+    /// Does nothing if any backend doesn't support persistent caching.
+    pub fn with_persistent_cache(mut self, dir: &Path) -> Self {
+        let mut hasher = Blake2b512::new();
+        hasher.update(VERIFICATION_CACHE_FORMAT_VERSION.to_le_bytes());
+        for backend in self.main_backend.iter().chain(&self.backends) {
+            let Some(state) = backend.verification_state() else {
+                return self;
+            };
+            let mut backend_state = VerificationState::new();
+            backend_state
+                .add_value(backend.name())
+                .add_value(state.as_bytes());
+            hasher.update(backend_state.as_bytes());
+        }
+        let state_hash = hasher.finalize();
+        let cache_dir = dir.join(hex::encode(&state_hash[..16]));
+        self.persistent_cache = Some(VerificationCache::new(cache_dir));
+        self
+    }
+
     /// Checks if the signer can sign, i.e. if a main backend is configured.
     pub fn can_sign(&self) -> bool {
         self.main_backend.is_some()
@@ -239,6 +345,17 @@ impl Signer {
         if let Some(check) = cached {
             return Ok(check);
         }
+        let persisted = self
+            .persistent_cache
+            .as_ref()
+            .and_then(|cache| cache.get(commit_id));
+        if let Some(check) = persisted {
+            self.cache
+                .lock()
+                .unwrap()
+                .put(commit_id.clone(), check.clone());
+            return Ok(check);
+        }
 
         let verification = self
             .main_backend
@@ -259,10 +376,7 @@ impl Signer {
             // realistically this is unlikely, but technically
             // it's correct to not cache unknowns here
             if verification.status != SigStatus::Unknown {
-                self.cache
-                    .lock()
-                    .unwrap()
-                    .put(commit_id.clone(), verification.clone());
+                self.cache_verification(commit_id, &verification);
             }
             Ok(verification)
         } else {
@@ -270,11 +384,18 @@ impl Signer {
             // have a backend that knows how to handle this signature
             //
             // not sure about how much of an optimization this is
-            self.cache
-                .lock()
-                .unwrap()
-                .put(commit_id.clone(), Verification::unknown());
+            self.cache_verification(commit_id, &Verification::unknown());
             Ok(Verification::unknown())
         }
     }
+
+    fn cache_verification(&self, commit_id: &CommitId, verification: &Verification) {
+        self.cache
+            .lock()
+            .unwrap()
+            .put(commit_id.clone(), verification.clone());
+        if let Some(cache) = &self.persistent_cache {
+            cache.insert(commit_id.clone(), verification.clone());
+        }
+    }
 }
@@ -33,6 +33,7 @@ use crate::signing::SigStatus;
 use crate::signing::SignError;
 use crate::signing::SigningBackend;
 use crate::signing::Verification;
+use crate::signing::VerificationState;
 
 #[derive(Debug)]
 pub struct SshBackend {
@@ -280,6 +281,17 @@ impl SigningBackend for SshBackend {
             }
         }
     }
+
+    fn verification_state(&self) -> Option<VerificationState> {
+        let mut state = VerificationState::new();
+        state.add_value(self.program.as_encoded_bytes());
+        if let Some(allowed_signers) = &self.allowed_signers {
+            state.add_file(Path::new(allowed_signers));
+        } else {
+            state.add_value([]);
+        }
+        Some(state)
+    }
 }
 
 #[cfg(test)]
@@ -42,9 +42,11 @@ use crate::commit::Commit;
 use crate::index::Index;
 use crate::merge::Merge;
 use crate::merge::MergedTreeValue;
+use crate::merge_driver::MergeDrivers;
 use crate::merged_tree::MergedTree;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::resolution_cache::ResolutionCache;
 use crate::signing::Signer;
 use crate::tree::Tree;
 use crate::tree_builder::TreeBuilder;
@@ -59,6 +61,8 @@ const TREE_CACHE_CAPACITY: usize = 1000;
 pub struct Store {
     backend: Box<dyn Backend>,
     signer: Signer,
+    merge_drivers: MergeDrivers,
+    resolution_cache: Option<ResolutionCache>,
     commit_cache: Mutex<CLruCache<CommitId, Arc<backend::Commit>>>,
     tree_cache: Mutex<CLruCache<(RepoPathBuf, TreeId), Arc<backend::Tree>>>,
 }
@@ -72,10 +76,17 @@ impl Debug for Store {
 }
 
 impl Store {
-    pub fn new(backend: Box<dyn Backend>, signer: Signer) -> Arc<Self> {
+    pub fn new(
+        backend: Box<dyn Backend>,
+        signer: Signer,
+        merge_drivers: MergeDrivers,
+        resolution_cache: Option<ResolutionCache>,
+    ) -> Arc<Self> {
         Arc::new(Store {
             backend,
             signer,
+            merge_drivers,
+            resolution_cache,
             commit_cache: Mutex::new(CLruCache::new(COMMIT_CACHE_CAPACITY.try_into().unwrap())),
             tree_cache: Mutex::new(CLruCache::new(TREE_CACHE_CAPACITY.try_into().unwrap())),
         })
@@ -89,6 +100,15 @@ impl Store {
         &self.signer
     }
 
+    pub fn merge_drivers(&self) -> &MergeDrivers {
+        &self.merge_drivers
+    }
+
+    /// Recorded conflict resolutions, if reusing them is enabled.
+    pub fn resolution_cache(&self) -> Option<&ResolutionCache> {
+        self.resolution_cache.as_ref()
+    }
+
     pub fn get_copy_records(
         &self,
         paths: Option<&[RepoPathBuf]>,
@@ -0,0 +1,650 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mergers that understand the structure of specific file formats.
+//!
+//! A [`ContentMerger`] merges files by their parsed structure instead of by
+//! lines, so that e.g. independent changes to adjacent keys of a JSON object
+//! don't conflict. If a merger can't parse the contents or finds conflicting
+//! changes, the file falls back to the line-based conflict.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fmt::Debug;
+use std::io::Write as _;
+
+use bstr::BString;
+use bstr::ByteSlice as _;
+use itertools::Itertools as _;
+use serde::de;
+use serde::ser::SerializeMap as _;
+use serde::ser::SerializeSeq as _;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::files;
+use crate::files::MergeResult;
+use crate::merge::Merge;
+
+/// Merges file contents by their structure.
+pub trait ContentMerger: Debug + Send + Sync {
+    /// Name of the merger, e.g. `"json"`.
+    fn name(&self) -> &str;
+
+    /// Merges `left` and `right`, which were both derived from `base`. Returns
+    /// `None` if any of the contents can't be parsed, or if they have
+    /// conflicting changes.
+    fn merge3(&self, base: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// Merges `contents` with `merger`, folding in one side at a time.
+pub fn merge_contents<T: AsRef<[u8]>>(
+    merger: &dyn ContentMerger,
+    contents: &Merge<T>,
+) -> Option<BString> {
+    let mut merged = contents.first().as_ref().to_vec();
+    for (base, side) in contents.removes().zip(contents.adds().skip(1)) {
+        merged = merger.merge3(base.as_ref(), &merged, side.as_ref())?;
+    }
+    Some(merged.into())
+}
+
+/// Merges sequences element-wise, treating each element as a line.
+///
+/// Elements are compared by the string returned by `key`.
+fn merge_sequences<T: Clone>(
+    base: &[T],
+    left: &[T],
+    right: &[T],
+    key: impl Fn(&T) -> String,
+) -> Option<Vec<T>> {
+    let mut ids: HashMap<String, usize> = HashMap::new();
+    let mut elements: Vec<T> = vec![];
+    let mut to_text = |items: &[T]| {
+        let mut text = vec![];
+        for item in items {
+            let id = *ids.entry(key(item)).or_insert_with(|| {
+                elements.push(item.clone());
+                elements.len() - 1
+            });
+            writeln!(text, "{id}").unwrap();
+        }
+        text
+    };
+    // Left goes first so that equal elements keep the formatting of the left
+    // side.
+    let left_text = to_text(left);
+    let base_text = to_text(base);
+    let right_text = to_text(right);
+    let texts = Merge::from_vec(vec![left_text, base_text, right_text]);
+    let MergeResult::Resolved(merged) = files::merge(&texts) else {
+        return None;
+    };
+    merged
+        .lines()
+        .map(|line| {
+            let id: usize = line.to_str().ok()?.parse().ok()?;
+            elements.get(id).cloned()
+        })
+        .collect()
+}
+
+/// Merges JSON files. The merged file is reformatted using the indentation of
+/// the first side.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonMerger;
+
+impl ContentMerger for JsonMerger {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn merge3(&self, base: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+        let base_value: JsonValue = serde_json::from_slice(base).ok()?;
+        let left_value: JsonValue = serde_json::from_slice(left).ok()?;
+        let right_value: JsonValue = serde_json::from_slice(right).ok()?;
+        let merged = merge_json_values(Some(&base_value), Some(&left_value), Some(&right_value))??;
+
+        let mut output = vec![];
+        match detect_json_indent(left) {
+            Some(indent) => {
+                let formatter = serde_json::ser::PrettyFormatter::with_indent(indent);
+                let mut serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+                merged.serialize(&mut serializer).ok()?;
+            }
+            None => serde_json::to_writer(&mut output, &merged).ok()?,
+        }
+        if left.ends_with(b"\n") {
+            output.push(b'\n');
+        }
+        Some(output)
+    }
+}
+
+/// Returns the indentation of the second line, or `None` if the JSON text
+/// doesn't span multiple lines.
+fn detect_json_indent(text: &[u8]) -> Option<&[u8]> {
+    let second_line = text.trim().lines().nth(1)?;
+    let indent_len = second_line
+        .iter()
+        .take_while(|&&b| b == b' ' || b == b'\t')
+        .count();
+    Some(&second_line[..indent_len])
+}
+
+/// JSON value which preserves the order of object keys.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(serde_json::Number),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl<'de> Deserialize<'de> for JsonValue {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct JsonValueVisitor;
+
+        impl<'de> de::Visitor<'de> for JsonValueVisitor {
+            type Value = JsonValue;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("any JSON value")
+            }
+
+            fn visit_unit<E>(self) -> Result<JsonValue, E> {
+                Ok(JsonValue::Null)
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<JsonValue, E> {
+                Ok(JsonValue::Bool(value))
+            }
+
+            fn visit_i64<E>(self, value: i64) -> Result<JsonValue, E> {
+                Ok(JsonValue::Number(value.into()))
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<JsonValue, E> {
+                Ok(JsonValue::Number(value.into()))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<JsonValue, E> {
+                serde_json::Number::from_f64(value)
+                    .map(JsonValue::Number)
+                    .ok_or_else(|| E::custom("invalid number"))
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<JsonValue, E> {
+                Ok(JsonValue::String(value.to_owned()))
+            }
+
+            fn visit_string<E>(self, value: String) -> Result<JsonValue, E> {
+                Ok(JsonValue::String(value))
+            }
+
+            fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<JsonValue, A::Error> {
+                let mut items = vec![];
+                while let Some(item) = seq.next_element()? {
+                    items.push(item);
+                }
+                Ok(JsonValue::Array(items))
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<JsonValue, A::Error> {
+                let mut entries = vec![];
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(JsonValue::Object(entries))
+            }
+        }
+
+        deserializer.deserialize_any(JsonValueVisitor)
+    }
+}
+
+impl Serialize for JsonValue {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(value) => serializer.serialize_bool(*value),
+            JsonValue::Number(value) => value.serialize(serializer),
+            JsonValue::String(value) => serializer.serialize_str(value),
+            JsonValue::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Merges JSON values. Returns `Some(None)` if the value should be removed,
+/// and `None` if the values conflict.
+fn merge_json_values(
+    base: Option<&JsonValue>,
+    left: Option<&JsonValue>,
+    right: Option<&JsonValue>,
+) -> Option<Option<JsonValue>> {
+    if let Some(resolved) = Merge::from_vec(vec![left, base, right]).resolve_trivial() {
+        return Some(resolved.cloned());
+    }
+    match (base, left?, right?) {
+        (None | Some(JsonValue::Object(_)), JsonValue::Object(left), JsonValue::Object(right)) => {
+            let base = match base {
+                Some(JsonValue::Object(base)) => base.as_slice(),
+                _ => &[],
+            };
+            fn get<'a>(entries: &'a [(String, JsonValue)], key: &str) -> Option<&'a JsonValue> {
+                entries.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+            }
+            let keys = left
+                .iter()
+                .chain(right)
+                .chain(base)
+                .map(|(key, _)| key.as_str())
+                .unique()
+                .collect_vec();
+            let mut merged = vec![];
+            for key in keys {
+                let value = merge_json_values(get(base, key), get(left, key), get(right, key))?;
+                if let Some(value) = value {
+                    merged.push((key.to_owned(), value));
+                }
+            }
+            Some(Some(JsonValue::Object(merged)))
+        }
+        (None | Some(JsonValue::Array(_)), JsonValue::Array(left), JsonValue::Array(right)) => {
+            let base = match base {
+                Some(JsonValue::Array(base)) => base.as_slice(),
+                _ => &[],
+            };
+            let merged = merge_sequences(base, left, right, |value| {
+                serde_json::to_string(value).unwrap()
+            })?;
+            Some(Some(JsonValue::Array(merged)))
+        }
+        _ => None,
+    }
+}
+
+/// Merges TOML files. Formatting and comments of the first side are preserved.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TomlMerger;
+
+impl ContentMerger for TomlMerger {
+    fn name(&self) -> &str {
+        "toml"
+    }
+
+    fn merge3(&self, base: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+        let parse =
+            |text: &[u8]| -> Option<toml_edit::DocumentMut> { text.to_str().ok()?.parse().ok() };
+        let base_doc = parse(base)?;
+        let mut left_doc = parse(left)?;
+        let right_doc = parse(right)?;
+        merge_toml_tables(
+            Some(base_doc.as_table()),
+            left_doc.as_table_mut(),
+            right_doc.as_table(),
+        )?;
+        Some(left_doc.to_string().into_bytes())
+    }
+}
+
+/// Merges `right` into `left`. Returns `None` if the tables conflict.
+fn merge_toml_tables(
+    base: Option<&dyn toml_edit::TableLike>,
+    left: &mut dyn toml_edit::TableLike,
+    right: &dyn toml_edit::TableLike,
+) -> Option<()> {
+    let keys = left
+        .iter()
+        .chain(right.iter())
+        .chain(base.iter().flat_map(|base| base.iter()))
+        .map(|(key, _)| key.to_owned())
+        .unique()
+        .collect_vec();
+    for key in &keys {
+        let base_item = base.and_then(|base| base.get(key));
+        let right_item = right.get(key);
+        let [base_str, left_str, right_str] =
+            [base_item, left.get(key), right_item].map(normalized_toml_item);
+        if right_str == base_str || left_str == right_str {
+            continue;
+        }
+        if left_str == base_str {
+            match right_item {
+                Some(item) => left.insert(key, detach_toml_item(item)),
+                None => left.remove(key),
+            };
+            continue;
+        }
+        let (Some(left_item), Some(right_item)) = (left.get_mut(key), right_item) else {
+            // Removed on one side and modified on the other
+            return None;
+        };
+        let base_item = base_item.filter(|item| !item.is_none());
+        match (base_item, left_item, right_item) {
+            (base_item, left_item, right_item)
+                if left_item.is_table_like()
+                    && right_item.is_table_like()
+                    && base_item.is_none_or(|item| item.is_table_like()) =>
+            {
+                merge_toml_tables(
+                    base_item.and_then(|item| item.as_table_like()),
+                    left_item.as_table_like_mut().unwrap(),
+                    right_item.as_table_like().unwrap(),
+                )?;
+            }
+            (
+                None | Some(toml_edit::Item::Value(toml_edit::Value::Array(_))),
+                toml_edit::Item::Value(toml_edit::Value::Array(left_array)),
+                toml_edit::Item::Value(toml_edit::Value::Array(right_array)),
+            ) => {
+                let base_values = match base_item {
+                    Some(toml_edit::Item::Value(toml_edit::Value::Array(array))) => {
+                        array.iter().cloned().collect_vec()
+                    }
+                    _ => vec![],
+                };
+                let merged = merge_sequences(
+                    &base_values,
+                    &left_array.iter().cloned().collect_vec(),
+                    &right_array.iter().cloned().collect_vec(),
+                    normalized_toml_value,
+                )?;
+                left_array.clear();
+                for value in merged {
+                    left_array.push_formatted(value);
+                }
+            }
+            (
+                None | Some(toml_edit::Item::ArrayOfTables(_)),
+                toml_edit::Item::ArrayOfTables(left_tables),
+                toml_edit::Item::ArrayOfTables(right_tables),
+            ) => {
+                let base_tables = match base_item {
+                    Some(toml_edit::Item::ArrayOfTables(tables)) => {
+                        tables.iter().cloned().collect_vec()
+                    }
+                    _ => vec![],
+                };
+                let merged = merge_sequences(
+                    &base_tables,
+                    &left_tables.iter().cloned().collect_vec(),
+                    &right_tables.iter().map(detach_toml_table).collect_vec(),
+                    |table| table.to_string(),
+                )?;
+                left_tables.clear();
+                for table in merged {
+                    left_tables.push(table);
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+/// Formats `item` ignoring its layout, so that items can be compared by value.
+fn normalized_toml_item(item: Option<&toml_edit::Item>) -> Option<String> {
+    match item? {
+        toml_edit::Item::None => None,
+        toml_edit::Item::Value(value) => Some(normalized_toml_value(value)),
+        toml_edit::Item::Table(table) => {
+            let mut table = table.clone();
+            toml_edit::TableLike::fmt(&mut table);
+            Some(table.to_string())
+        }
+        toml_edit::Item::ArrayOfTables(tables) => Some(
+            tables
+                .iter()
+                .map(|table| {
+                    let mut table = table.clone();
+                    toml_edit::TableLike::fmt(&mut table);
+                    table.to_string()
+                })
+                .join("\n"),
+        ),
+    }
+}
+
+fn normalized_toml_value(value: &toml_edit::Value) -> String {
+    let mut value = value.clone();
+    match &mut value {
+        toml_edit::Value::Array(array) => array.fmt(),
+        toml_edit::Value::InlineTable(table) => table.fmt(),
+        _ => {}
+    }
+    value.decor_mut().clear();
+    value.to_string()
+}
+
+/// Copies `item` from another document. Tables lose their position in that
+/// document, so they are placed after their preceding table when formatted.
+fn detach_toml_item(item: &toml_edit::Item) -> toml_edit::Item {
+    match item {
+        toml_edit::Item::Table(table) => toml_edit::Item::Table(detach_toml_table(table)),
+        toml_edit::Item::ArrayOfTables(tables) => {
+            toml_edit::Item::ArrayOfTables(tables.iter().map(detach_toml_table).collect())
+        }
+        item => item.clone(),
+    }
+}
+
+fn detach_toml_table(table: &toml_edit::Table) -> toml_edit::Table {
+    let mut detached = toml_edit::Table::new();
+    detached.set_implicit(table.is_implicit());
+    detached.set_dotted(table.is_dotted());
+    *detached.decor_mut() = table.decor().clone();
+    for (key, item) in table {
+        let key = table.key(key).unwrap();
+        detached.insert_formatted(key, detach_toml_item(item));
+    }
+    detached
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    fn merge3(merger: &dyn ContentMerger, base: &str, left: &str, right: &str) -> Option<String> {
+        let merged = merger.merge3(base.as_bytes(), left.as_bytes(), right.as_bytes())?;
+        Some(String::from_utf8(merged).unwrap())
+    }
+
+    #[test]
+    fn test_merge_sequences() {
+        let merge = |base: &str, left: &str, right: &str| {
+            let chars = |s: &str| s.chars().collect_vec();
+            merge_sequences(&chars(base), &chars(left), &chars(right), char::to_string)
+                .map(String::from_iter)
+        };
+        assert_eq!(merge("abc", "xabc", "abcy"), Some("xabcy".to_owned()));
+        assert_eq!(merge("abc", "ac", "abcd"), Some("acd".to_owned()));
+        assert_eq!(merge("abc", "axc", "ayc"), None);
+    }
+
+    #[test]
+    fn test_merge_json() {
+        let base = indoc! {r#"
+            {
+              "name": "foo",
+              "version": "1.0.0",
+              "dependencies": {
+                "a": "1",
+                "b": "1"
+              },
+              "files": ["a", "b"]
+            }
+        "#};
+        let left = indoc! {r#"
+            {
+              "name": "foo",
+              "version": "1.1.0",
+              "dependencies": {
+                "a": "2",
+                "b": "1"
+              },
+              "files": ["a", "b"]
+            }
+        "#};
+        let right = indoc! {r#"
+            {
+              "name": "foo",
+              "version": "1.0.0",
+              "dependencies": {
+                "a": "1",
+                "b": "2",
+                "c": "1"
+              },
+              "files": ["a", "b", "c"]
+            }
+        "#};
+        insta::assert_snapshot!(merge3(&JsonMerger, base, left, right).unwrap(), @r#"
+        {
+          "name": "foo",
+          "version": "1.1.0",
+          "dependencies": {
+            "a": "2",
+            "b": "2",
+            "c": "1"
+          },
+          "files": [
+            "a",
+            "b",
+            "c"
+          ]
+        }
+        "#);
+
+        // Conflicting changes to the same value
+        let right = left.replace("1.1.0", "2.0.0");
+        let left = left.replace(r#""a": "2""#, r#""a": "3""#);
+        assert_eq!(merge3(&JsonMerger, base, &left, &right), None);
+
+        // Unparsable content
+        assert_eq!(merge3(&JsonMerger, base, "{", right.as_str()), None);
+    }
+
+    #[test]
+    fn test_merge_json_compact() {
+        assert_eq!(
+            merge3(
+                &JsonMerger,
+                r#"{"a":1,"b":1}"#,
+                r#"{"a":2,"b":1}"#,
+                r#"{"a":1,"b":2}"#
+            ),
+            Some(r#"{"a":2,"b":2}"#.to_owned())
+        );
+    }
+
+    #[test]
+    fn test_merge_toml() {
+        let base = indoc! {r#"
+            # Package metadata
+            [package]
+            name = "foo"
+            version = "1.0.0"
+
+            [dependencies]
+            a = "1"
+            b = { version = "1", features = ["x"] }
+
+            [[bin]]
+            name = "foo"
+        "#};
+        let left = indoc! {r#"
+            # Package metadata
+            [package]
+            name = "foo"
+            version = "1.1.0"  # Bumped
+
+            [dependencies]
+            a = "1"
+            b = { version = "1", features = ["x", "y"] }
+
+            [[bin]]
+            name = "foo"
+        "#};
+        let right = indoc! {r#"
+            # Package metadata
+            [package]
+            name = "foo"
+            version = "1.0.0"
+
+            [dependencies]
+            a = "2"
+            b = { version = "1", features = ["x"] }
+            c = "1"
+
+            [[bin]]
+            name = "foo"
+
+            [[bin]]
+            name = "bar"
+        "#};
+        insta::assert_snapshot!(merge3(&TomlMerger, base, left, right).unwrap(), @r#"
+        # Package metadata
+        [package]
+        name = "foo"
+        version = "1.1.0"  # Bumped
+
+        [dependencies]
+        a = "2"
+        b = { version = "1", features = ["x", "y"] }
+        c = "1"
+
+        [[bin]]
+        name = "foo"
+
+        [[bin]]
+        name = "bar"
+        "#);
+
+        // Conflicting changes to the same value
+        let right = right.replace(r#"features = ["x"]"#, r#"features = ["z"]"#);
+        assert_eq!(merge3(&TomlMerger, base, left, &right), None);
+    }
+
+    #[test]
+    fn test_merge_contents_multiple_sides() {
+        let contents = Merge::from_vec(vec![
+            r#"{"a":2,"b":1,"c":1}"#,
+            r#"{"a":1,"b":1,"c":1}"#,
+            r#"{"a":1,"b":2,"c":1}"#,
+            r#"{"a":1,"b":1,"c":1}"#,
+            r#"{"a":1,"b":1,"c":2}"#,
+        ]);
+        assert_eq!(
+            merge_contents(&JsonMerger, &contents),
+            Some(r#"{"a":2,"b":2,"c":2}"#.into())
+        );
+    }
+}
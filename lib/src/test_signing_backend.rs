@@ -22,6 +22,7 @@ use jj_lib::signing::SignError;
 use jj_lib::signing::SignResult;
 use jj_lib::signing::SigningBackend;
 use jj_lib::signing::Verification;
+use jj_lib::signing::VerificationState;
 
 /// A test signing backend that uses a simple hash-based signature format.
 #[derive(Debug)]
@@ -73,4 +74,8 @@ impl SigningBackend for TestSigningBackend {
             })
         }
     }
+
+    fn verification_state(&self) -> Option<VerificationState> {
+        Some(VerificationState::new())
+    }
 }
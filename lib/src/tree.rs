@@ -14,6 +14,7 @@
 
 #![allow(missing_docs)]
 
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::fmt::Error;
 use std::fmt::Formatter;
@@ -22,6 +23,7 @@ use std::hash::Hasher;
 use std::io::Read as _;
 use std::sync::Arc;
 
+use bstr::BString;
 use futures::future::try_join_all;
 use itertools::Itertools as _;
 use tracing::instrument;
@@ -30,6 +32,7 @@ use crate::backend;
 use crate::backend::BackendError;
 use crate::backend::BackendResult;
 use crate::backend::ConflictId;
+use crate::backend::FileId;
 use crate::backend::TreeEntriesNonRecursiveIterator;
 use crate::backend::TreeEntry;
 use crate::backend::TreeId;
@@ -275,6 +278,29 @@ pub async fn try_resolve_file_conflict(
     filename: &RepoPath,
     conflict: &MergedTreeVal<'_>,
 ) -> BackendResult<Option<TreeValue>> {
+    match merge_file_conflict(store, filename, conflict).await? {
+        Some(FileConflictMerge::Resolved(value)) => Ok(Some(value)),
+        Some(FileConflictMerge::Partial(_)) | None => Ok(None),
+    }
+}
+
+/// Result of merging the content hunks of a file conflict.
+pub(crate) enum FileConflictMerge {
+    /// All hunks were resolved.
+    Resolved(TreeValue),
+    /// A merge driver resolved some of the conflicted hunks. Maps the file ids
+    /// of the conflict terms to new file ids where the resolved hunks are
+    /// replaced by their resolution, so they're the same in every term.
+    Partial(HashMap<FileId, FileId>),
+}
+
+/// Like [`try_resolve_file_conflict()`], but also returns the hunks a merge
+/// driver could resolve if others are still conflicted.
+pub(crate) async fn merge_file_conflict(
+    store: &Store,
+    filename: &RepoPath,
+    conflict: &MergedTreeVal<'_>,
+) -> BackendResult<Option<FileConflictMerge>> {
     // If there are any non-file or any missing parts in the conflict, we can't
     // merge it. We check early so we don't waste time reading file contents if
     // we can't merge them anyway. At the same time we determine whether the
@@ -298,10 +324,10 @@ pub async fn try_resolve_file_conflict(
     if let Some(&resolved_file_id) = file_id_conflict.resolve_trivial() {
         // Don't bother reading the file contents if the conflict can be trivially
         // resolved.
-        return Ok(Some(TreeValue::File {
+        return Ok(Some(FileConflictMerge::Resolved(TreeValue::File {
             id: resolved_file_id.clone(),
             executable,
-        }));
+        })));
     }
 
     // While the input conflict should be simplified by caller, it might contain
@@ -312,7 +338,81 @@ pub async fn try_resolve_file_conflict(
     //    cannot
     let file_id_conflict = file_id_conflict.simplify();
 
-    let content_futures = file_id_conflict.into_iter().map(|file_id| async {
+    let contents = read_file_conflict_contents(store, filename, &file_id_conflict).await?;
+    let merged_content = match files::merge(&contents) {
+        MergeResult::Resolved(merged_content) => merged_content,
+        MergeResult::Conflict(hunks) => {
+            let recorded = store
+                .resolution_cache()
+                .and_then(|cache| cache.resolve_hunks(&hunks));
+            if let Some(merged_content) = recorded {
+                merged_content
+            } else {
+                let Some(driver) = store.merge_drivers().find(filename) else {
+                    return Ok(None);
+                };
+                let resolved = driver
+                    .resolve(filename, &contents, &hunks)
+                    .map_err(|err| BackendError::Other(err.into()))?;
+                match resolved {
+                    Some(MergeResult::Resolved(merged_content)) => merged_content,
+                    Some(MergeResult::Conflict(hunks)) => {
+                        let new_ids = write_partially_resolved_terms(
+                            store,
+                            filename,
+                            &file_id_conflict,
+                            &hunks,
+                        )
+                        .await?;
+                        return Ok(Some(FileConflictMerge::Partial(new_ids)));
+                    }
+                    None => return Ok(None),
+                }
+            }
+        }
+    };
+    let id = store
+        .write_file(filename, &mut merged_content.as_slice())
+        .await?;
+    Ok(Some(FileConflictMerge::Resolved(TreeValue::File {
+        id,
+        executable,
+    })))
+}
+
+/// Writes each term of `file_id_conflict` with the resolved `hunks` applied.
+async fn write_partially_resolved_terms(
+    store: &Store,
+    filename: &RepoPath,
+    file_id_conflict: &Merge<&FileId>,
+    hunks: &[Merge<BString>],
+) -> BackendResult<HashMap<FileId, FileId>> {
+    let mut new_ids = HashMap::new();
+    for (i, &old_id) in file_id_conflict.iter().enumerate() {
+        if new_ids.contains_key(old_id) {
+            continue;
+        }
+        let mut content = BString::default();
+        for hunk in hunks {
+            if let Some(resolved) = hunk.as_resolved() {
+                content.extend_from_slice(resolved);
+            } else {
+                content.extend_from_slice(hunk.iter().nth(i).unwrap());
+            }
+        }
+        let new_id = store.write_file(filename, &mut content.as_slice()).await?;
+        new_ids.insert(old_id.clone(), new_id);
+    }
+    Ok(new_ids)
+}
+
+/// Reads the contents of each term of a file conflict.
+pub(crate) async fn read_file_conflict_contents(
+    store: &Store,
+    filename: &RepoPath,
+    file_id_conflict: &Merge<&FileId>,
+) -> BackendResult<Merge<Vec<u8>>> {
+    let content_futures = file_id_conflict.iter().map(|file_id| async {
         let mut content = vec![];
         let mut reader = store.read_file_async(filename, file_id).await?;
         reader
@@ -324,15 +424,5 @@ pub async fn try_resolve_file_conflict(
             })?;
         BackendResult::Ok(content)
     });
-    let contents = Merge::from_vec(try_join_all(content_futures).await?);
-    let merge_result = files::merge(&contents);
-    match merge_result {
-        MergeResult::Resolved(merged_content) => {
-            let id = store
-                .write_file(filename, &mut merged_content.as_slice())
-                .await?;
-            Ok(Some(TreeValue::File { id, executable }))
-        }
-        MergeResult::Conflict(_) => Ok(None),
-    }
+    Ok(Merge::from_vec(try_join_all(content_futures).await?))
 }
@@ -18,6 +18,8 @@
 use std::any::Any;
 use std::collections::BTreeMap;
 use std::ffi::OsString;
+use std::io::Read as _;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -27,14 +29,22 @@ use tracing::instrument;
 
 use crate::backend::BackendError;
 use crate::backend::MergedTreeId;
+use crate::backend::TreeValue;
 use crate::commit::Commit;
 use crate::conflicts::ConflictMarkerStyle;
 use crate::dag_walk;
+use crate::dsl_util;
+use crate::fileset;
+use crate::fileset::FilesetDiagnostics;
+use crate::fileset::FilesetExpression;
+use crate::fileset::FilesetParseError;
 use crate::fsmonitor::FsmonitorSettings;
 use crate::gitignore::GitIgnoreError;
 use crate::gitignore::GitIgnoreFile;
 use crate::matchers::EverythingMatcher;
 use crate::matchers::Matcher;
+use crate::matchers::PrefixMatcher;
+use crate::merged_tree::MergedTree;
 use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store::OpStoreError;
 use crate::op_store::OperationId;
@@ -46,6 +56,7 @@ use crate::repo::RewriteRootCommit;
 use crate::repo_path::InvalidRepoPathError;
 use crate::repo_path::RepoPath;
 use crate::repo_path::RepoPathBuf;
+use crate::repo_path::RepoPathUiConverter;
 use crate::store::Store;
 
 /// The trait all working-copy implementations must implement.
@@ -67,10 +78,8 @@ pub trait WorkingCopy: Send {
     fn tree_id(&self) -> Result<&MergedTreeId, WorkingCopyStateError>;
 
     /// Patterns that decide which paths from the current tree should be checked
-    /// out in the working copy. An empty list means that no paths should be
-    /// checked out in the working copy. A single `RepoPath::root()` entry means
-    /// that all files should be checked out.
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
+    /// out in the working copy.
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError>;
 
     /// Locks the working copy and returns an instance with methods for updating
     /// the working copy files and state.
@@ -136,7 +145,7 @@ pub trait LockedWorkingCopy {
     fn recover(&mut self, commit: &Commit) -> Result<(), ResetError>;
 
     /// See `WorkingCopy::sparse_patterns()`
-    fn sparse_patterns(&self) -> Result<&[RepoPathBuf], WorkingCopyStateError>;
+    fn sparse_patterns(&self) -> Result<&SparsePatterns, WorkingCopyStateError>;
 
     /// Updates the patterns that decide which paths from the current tree
     /// should be checked out in the working copy.
@@ -146,7 +155,7 @@ pub trait LockedWorkingCopy {
     // to use sparse).
     fn set_sparse_patterns(
         &mut self,
-        new_sparse_patterns: Vec<RepoPathBuf>,
+        new_sparse_patterns: SparsePatterns,
         options: &CheckoutOptions,
     ) -> Result<CheckoutStats, CheckoutError>;
 
@@ -158,6 +167,216 @@ pub trait LockedWorkingCopy {
     ) -> Result<Box<dyn WorkingCopy>, WorkingCopyStateError>;
 }
 
+/// Patterns that decide which paths from the current tree should be checked out
+/// in a working copy.
+#[derive(Clone, Debug)]
+pub enum SparsePatterns {
+    /// Paths under any of the workspace-relative prefixes. An empty list means
+    /// that no paths should be checked out. A single `RepoPath::root()` entry
+    /// means that all files should be checked out.
+    Prefixes(Vec<RepoPathBuf>),
+    /// Paths matching a fileset expression. File patterns in the expression
+    /// are relative to the workspace root.
+    Fileset {
+        /// The source text of the expression.
+        text: String,
+        /// The parsed expression.
+        expression: FilesetExpression,
+    },
+    /// Paths matching the fileset expression stored in the sparse profile
+    /// `.jj-sparse/<name>` in the repo. The profile is read again whenever
+    /// another commit is checked out.
+    Profile {
+        /// The name of the profile.
+        name: String,
+        /// The source text of the expression, as of the last checkout.
+        text: String,
+        /// The parsed expression.
+        expression: FilesetExpression,
+    },
+}
+
+impl SparsePatterns {
+    /// Patterns that check out all files.
+    pub fn everything() -> Self {
+        SparsePatterns::Prefixes(vec![RepoPathBuf::root()])
+    }
+
+    /// Parses `text` as a fileset expression with paths relative to the
+    /// workspace root.
+    pub fn parse_fileset(text: &str) -> Result<Self, FilesetParseError> {
+        Ok(SparsePatterns::Fileset {
+            text: text.to_owned(),
+            expression: parse_sparse_fileset(text)?,
+        })
+    }
+
+    /// Parses `text` as the contents of the sparse profile `name`. Lines
+    /// starting with `#` are ignored.
+    pub fn parse_profile(name: &str, text: &str) -> Result<Self, FilesetParseError> {
+        let text = text
+            .lines()
+            .filter(|line| !line.trim_start().starts_with('#'))
+            .join("\n")
+            .trim()
+            .to_owned();
+        let expression = parse_sparse_fileset(&text)?;
+        Ok(SparsePatterns::Profile {
+            name: name.to_owned(),
+            text,
+            expression,
+        })
+    }
+
+    /// Reads the sparse profile `name` from `tree`.
+    pub fn load_profile(tree: &MergedTree, name: &str) -> Result<Self, SparseProfileError> {
+        let path = sparse_profile_path(name)?;
+        let id = match tree.path_value(&path)?.into_resolved() {
+            Ok(Some(TreeValue::File { id, .. })) => id,
+            Ok(None) => return Err(SparseProfileError::NotFound(name.to_owned())),
+            Ok(Some(_)) | Err(_) => return Err(SparseProfileError::NotAFile(name.to_owned())),
+        };
+        let mut content = String::new();
+        tree.store()
+            .read_file(&path, &id)?
+            .read_to_string(&mut content)
+            .map_err(|err| BackendError::ReadFile {
+                path: path.clone(),
+                id: id.clone(),
+                source: err.into(),
+            })?;
+        Self::parse_profile(name, &content).map_err(|err| SparseProfileError::Parse {
+            name: name.to_owned(),
+            source: err,
+        })
+    }
+
+    /// Returns the name of the sparse profile in use, if any.
+    pub fn profile_name(&self) -> Option<&str> {
+        match self {
+            SparsePatterns::Prefixes(_) | SparsePatterns::Fileset { .. } => None,
+            SparsePatterns::Profile { name, .. } => Some(name),
+        }
+    }
+
+    /// Returns the path prefixes, or `None` if these patterns are a fileset
+    /// expression.
+    pub fn as_prefixes(&self) -> Option<&[RepoPathBuf]> {
+        match self {
+            SparsePatterns::Prefixes(prefixes) => Some(prefixes),
+            SparsePatterns::Fileset { .. } | SparsePatterns::Profile { .. } => None,
+        }
+    }
+
+    /// Returns the patterns as fileset expression source text. Path prefixes
+    /// are converted to an equivalent expression.
+    pub fn to_fileset_text(&self) -> String {
+        match self {
+            SparsePatterns::Prefixes(prefixes) => {
+                if prefixes.is_empty() {
+                    return "none()".to_owned();
+                }
+                prefixes
+                    .iter()
+                    .map(|path| {
+                        if path.is_root() {
+                            "all()".to_owned()
+                        } else {
+                            format!(
+                                r#"root:"{}""#,
+                                dsl_util::escape_string(path.as_internal_file_string())
+                            )
+                        }
+                    })
+                    .join(" | ")
+            }
+            SparsePatterns::Fileset { text, .. } | SparsePatterns::Profile { text, .. } => {
+                text.clone()
+            }
+        }
+    }
+
+    /// Builds a matcher for the paths to check out.
+    pub fn to_matcher(&self) -> Box<dyn Matcher> {
+        match self {
+            SparsePatterns::Prefixes(prefixes) => Box::new(PrefixMatcher::new(prefixes)),
+            SparsePatterns::Fileset { expression, .. }
+            | SparsePatterns::Profile { expression, .. } => expression.to_matcher(),
+        }
+    }
+}
+
+impl PartialEq for SparsePatterns {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (SparsePatterns::Prefixes(a), SparsePatterns::Prefixes(b)) => a == b,
+            (SparsePatterns::Fileset { text: a, .. }, SparsePatterns::Fileset { text: b, .. }) => {
+                a == b
+            }
+            (
+                SparsePatterns::Profile {
+                    name: name1,
+                    text: text1,
+                    ..
+                },
+                SparsePatterns::Profile {
+                    name: name2,
+                    text: text2,
+                    ..
+                },
+            ) => name1 == name2 && text1 == text2,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for SparsePatterns {}
+
+fn parse_sparse_fileset(text: &str) -> Result<FilesetExpression, FilesetParseError> {
+    let path_converter = RepoPathUiConverter::Fs {
+        cwd: PathBuf::new(),
+        base: PathBuf::new(),
+    };
+    fileset::parse(&mut FilesetDiagnostics::new(), text, &path_converter)
+}
+
+/// Directory in the repo containing the sparse profiles.
+pub const SPARSE_PROFILE_DIR: &str = ".jj-sparse";
+
+/// Returns the path of the sparse profile `name` in the repo.
+pub fn sparse_profile_path(name: &str) -> Result<RepoPathBuf, SparseProfileError> {
+    if name.is_empty() {
+        return Err(SparseProfileError::InvalidName(name.to_owned()));
+    }
+    RepoPathBuf::from_relative_path(Path::new(SPARSE_PROFILE_DIR).join(name))
+        .map_err(|_| SparseProfileError::InvalidName(name.to_owned()))
+}
+
+/// An error while loading a sparse profile.
+#[derive(Debug, Error)]
+pub enum SparseProfileError {
+    /// The profile name can't be used as a path in the repo.
+    #[error("Invalid sparse profile name: {0}")]
+    InvalidName(String),
+    /// The profile file doesn't exist.
+    #[error("No such sparse profile: {0}")]
+    NotFound(String),
+    /// The profile path is conflicted or not a regular file.
+    #[error("Sparse profile {0} is not a regular file")]
+    NotAFile(String),
+    /// The profile file isn't a valid fileset expression.
+    #[error("Failed to parse sparse profile {name}")]
+    Parse {
+        /// The name of the profile.
+        name: String,
+        /// The underlying error.
+        source: FilesetParseError,
+    },
+    /// Failed to read the profile file.
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+}
+
 /// An error while snapshotting the working copy.
 #[derive(Debug, Error)]
 pub enum SnapshotError {
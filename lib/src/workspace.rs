@@ -32,6 +32,7 @@ use crate::file_util::IoResultExt as _;
 use crate::file_util::PathError;
 use crate::local_working_copy::LocalWorkingCopy;
 use crate::local_working_copy::LocalWorkingCopyFactory;
+use crate::merge_driver::MergeDriverConfigError;
 use crate::op_heads_store::OpHeadsStoreError;
 use crate::op_store::OperationId;
 use crate::op_store::WorkspaceId;
@@ -60,6 +61,8 @@ use crate::working_copy::LockedWorkingCopy;
 use crate::working_copy::WorkingCopy;
 use crate::working_copy::WorkingCopyFactory;
 use crate::working_copy::WorkingCopyStateError;
+use crate::workspace_store::WorkspaceStore;
+use crate::workspace_store::WorkspaceStoreError;
 
 #[derive(Error, Debug)]
 pub enum WorkspaceInitError {
@@ -79,6 +82,10 @@ pub enum WorkspaceInitError {
     Backend(#[from] BackendInitError),
     #[error(transparent)]
     SignInit(#[from] SignInitError),
+    #[error(transparent)]
+    MergeDrivers(#[from] MergeDriverConfigError),
+    #[error(transparent)]
+    WorkspaceStore(#[from] WorkspaceStoreError),
 }
 
 #[derive(Error, Debug)]
@@ -123,6 +130,20 @@ fn create_jj_dir(workspace_root: &Path) -> Result<PathBuf, WorkspaceInitError> {
     }
 }
 
+fn write_repo_pointer(jj_dir: &Path, repo_dir: &Path) -> Result<(), WorkspaceInitError> {
+    let repo_file_path = jj_dir.join("repo");
+    let mut repo_file = File::create(&repo_file_path).context(&repo_file_path)?;
+    repo_file
+        .write_all(
+            repo_dir
+                .to_str()
+                .ok_or(WorkspaceInitError::NonUnicodePath)?
+                .as_bytes(),
+        )
+        .context(&repo_file_path)?;
+    Ok(())
+}
+
 fn init_working_copy(
     repo: &Arc<ReadonlyRepo>,
     workspace_root: &Path,
@@ -307,6 +328,7 @@ impl Workspace {
                 RepoInitError::Backend(err) => WorkspaceInitError::Backend(err),
                 RepoInitError::OpHeadsStore(err) => WorkspaceInitError::OpHeadsStore(err),
                 RepoInitError::Path(err) => WorkspaceInitError::Path(err),
+                RepoInitError::MergeDrivers(err) => WorkspaceInitError::MergeDrivers(err),
             })?;
             let (working_copy, repo) = init_working_copy(
                 &repo,
@@ -317,6 +339,9 @@ impl Workspace {
             )?;
             let repo_loader = repo.loader().clone();
             let workspace = Workspace::new(workspace_root, repo_dir, working_copy, repo_loader)?;
+            workspace
+                .workspace_store()
+                .add(workspace.workspace_id(), workspace.workspace_root())?;
             Ok((workspace, repo))
         })()
         .inspect_err(|_err| {
@@ -354,16 +379,7 @@ impl Workspace {
         let jj_dir = create_jj_dir(workspace_root)?;
 
         let repo_dir = dunce::canonicalize(repo_path).context(repo_path)?;
-        let repo_file_path = jj_dir.join("repo");
-        let mut repo_file = File::create(&repo_file_path).context(&repo_file_path)?;
-        repo_file
-            .write_all(
-                repo_dir
-                    .to_str()
-                    .ok_or(WorkspaceInitError::NonUnicodePath)?
-                    .as_bytes(),
-            )
-            .context(&repo_file_path)?;
+        write_repo_pointer(&jj_dir, &repo_dir)?;
 
         let (working_copy, repo) = init_working_copy(
             repo,
@@ -378,9 +394,31 @@ impl Workspace {
             working_copy,
             repo.loader().clone(),
         )?;
+        workspace
+            .workspace_store()
+            .add(workspace.workspace_id(), workspace.workspace_root())?;
         Ok((workspace, repo))
     }
 
+    /// Points the existing workspace at `workspace_root` to the repo at
+    /// `repo_path`. This is used to reattach a workspace after it or the repo
+    /// has been moved.
+    pub fn repoint_workspace(
+        workspace_root: &Path,
+        repo_path: &Path,
+    ) -> Result<(), WorkspaceInitError> {
+        let repo_dir = dunce::canonicalize(repo_path).context(repo_path)?;
+        write_repo_pointer(&workspace_root.join(".jj"), &repo_dir)
+    }
+
+    /// Reads the name of the workspace at `workspace_root` from its working
+    /// copy state. Unlike loading the workspace, this works if the repo can't
+    /// be found. Returns `None` if the workspace doesn't use the local working
+    /// copy or its state can't be read.
+    pub fn read_workspace_id(workspace_root: &Path) -> Option<WorkspaceId> {
+        read_local_workspace_id(&workspace_root.join(".jj").join("working_copy"))
+    }
+
     pub fn load(
         user_settings: &UserSettings,
         workspace_path: &Path,
@@ -417,6 +455,24 @@ impl Workspace {
         self.working_copy.as_ref()
     }
 
+    /// The store recording the roots of all workspaces attached to the repo.
+    pub fn workspace_store(&self) -> WorkspaceStore {
+        WorkspaceStore::load(&self.repo_path)
+    }
+
+    /// Records the root of this workspace in the workspace store unless it's
+    /// already up to date. Returns `true` if the record was updated, i.e. the
+    /// workspace was moved or added before the store existed.
+    pub fn update_recorded_path(&self) -> Result<bool, WorkspaceStoreError> {
+        let store = self.workspace_store();
+        let recorded_path = store.get_workspace_path(self.workspace_id())?;
+        if recorded_path.as_deref() == Some(self.workspace_root.as_path()) {
+            return Ok(false);
+        }
+        store.add(self.workspace_id(), &self.workspace_root)?;
+        Ok(true)
+    }
+
     pub fn start_working_copy_mutation(
         &mut self,
     ) -> Result<LockedWorkspace, WorkingCopyStateError> {
@@ -519,6 +575,12 @@ pub trait WorkspaceLoader {
     // Returns the type identifier for the WorkingCopy trait in this Workspace.
     fn get_working_copy_type(&self) -> Result<String, StoreLoadError>;
 
+    // Returns the name of this Workspace without loading it, or None if the
+    // name can't be determined that way.
+    fn workspace_id(&self) -> Option<WorkspaceId> {
+        None
+    }
+
     // Loads the WorkingCopy trait for this Workspace.
     fn load_working_copy(
         &self,
@@ -527,6 +589,14 @@ pub trait WorkspaceLoader {
     ) -> Result<Box<dyn WorkingCopy>, WorkspaceLoadError>;
 }
 
+fn read_local_workspace_id(working_copy_state_path: &Path) -> Option<WorkspaceId> {
+    let working_copy_type = read_store_type("working copy", working_copy_state_path.join("type"));
+    if working_copy_type.ok()? != LocalWorkingCopy::name() {
+        return None;
+    }
+    LocalWorkingCopy::load_workspace_id(working_copy_state_path)
+}
+
 pub struct DefaultWorkspaceLoaderFactory;
 
 impl WorkspaceLoaderFactory for DefaultWorkspaceLoaderFactory {
@@ -564,7 +634,20 @@ impl DefaultWorkspaceLoader {
             let buf = fs::read(&repo_dir).context(&repo_dir)?;
             let repo_path_str =
                 String::from_utf8(buf).map_err(|_| WorkspaceLoadError::NonUnicodePath)?;
-            repo_dir = dunce::canonicalize(jj_dir.join(&repo_path_str)).context(&repo_path_str)?;
+            let linked_repo_dir = jj_dir.join(&repo_path_str);
+            repo_dir = match dunce::canonicalize(&linked_repo_dir) {
+                Ok(path) => path,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    return Err(WorkspaceLoadError::RepoDoesNotExist(linked_repo_dir));
+                }
+                Err(error) => {
+                    return Err(PathError {
+                        path: repo_path_str.into(),
+                        error,
+                    }
+                    .into());
+                }
+            };
             if !repo_dir.is_dir() {
                 return Err(WorkspaceLoadError::RepoDoesNotExist(repo_dir));
             }
@@ -610,6 +693,10 @@ impl WorkspaceLoader for DefaultWorkspaceLoader {
         read_store_type("working copy", self.working_copy_state_path.join("type"))
     }
 
+    fn workspace_id(&self) -> Option<WorkspaceId> {
+        read_local_workspace_id(&self.working_copy_state_path)
+    }
+
     fn load_working_copy(
         &self,
         store: &Arc<Store>,
@@ -0,0 +1,200 @@
+// Copyright 2025 The Jujutsu Authors
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Records where the workspaces attached to a repo live on disk.
+//!
+//! The view only knows the names of the workspaces and their working-copy
+//! commits. The workspace store maps the names to the workspace roots so that
+//! moved or deleted workspaces can be detected from any other workspace.
+
+use std::fs;
+use std::io;
+use std::io::Write as _;
+use std::path::Path;
+use std::path::PathBuf;
+
+use prost::Message as _;
+use tempfile::NamedTempFile;
+use thiserror::Error;
+
+use crate::file_util::create_or_reuse_dir;
+use crate::file_util::IoResultExt as _;
+use crate::file_util::PathError;
+use crate::lock::FileLock;
+use crate::lock::FileLockError;
+use crate::op_store::WorkspaceId;
+use crate::protos::workspace_store as proto;
+
+/// Error that may occur while reading or updating the workspace store.
+#[derive(Debug, Error)]
+pub enum WorkspaceStoreError {
+    /// Failed to read or write the index file.
+    #[error(transparent)]
+    Path(#[from] PathError),
+    /// Failed to lock the index file.
+    #[error(transparent)]
+    Lock(#[from] FileLockError),
+    /// The index file is corrupt.
+    #[error("Failed to decode workspace store index")]
+    Decode(#[from] prost::DecodeError),
+    /// The workspace root cannot be stored.
+    #[error("Workspace path could not be interpreted as Unicode text")]
+    NonUnicodePath,
+}
+
+/// Maps workspace names to workspace roots. Stored in `.jj/repo/workspace_store`.
+#[derive(Debug)]
+pub struct WorkspaceStore {
+    store_dir: PathBuf,
+}
+
+impl WorkspaceStore {
+    /// Loads the workspace store of the repo at `repo_path`. The store
+    /// directory is created on the first write.
+    pub fn load(repo_path: &Path) -> Self {
+        WorkspaceStore {
+            store_dir: repo_path.join("workspace_store"),
+        }
+    }
+
+    /// Records `path` as the root of the workspace `workspace_id`, replacing
+    /// any previous record.
+    pub fn add(&self, workspace_id: &WorkspaceId, path: &Path) -> Result<(), WorkspaceStoreError> {
+        let path = path.to_str().ok_or(WorkspaceStoreError::NonUnicodePath)?;
+        self.update(|workspaces| {
+            workspaces.retain(|ws| ws.name != workspace_id.as_str());
+            workspaces.push(proto::Workspace {
+                name: workspace_id.as_str().to_owned(),
+                path: path.to_owned(),
+            });
+        })
+    }
+
+    /// Removes the records of the given workspaces.
+    pub fn forget(&self, workspace_ids: &[&WorkspaceId]) -> Result<(), WorkspaceStoreError> {
+        self.update(|workspaces| {
+            workspaces.retain(|ws| !workspace_ids.iter().any(|id| ws.name == id.as_str()));
+        })
+    }
+
+    /// Moves the record of `old_workspace_id` to `new_workspace_id`.
+    pub fn rename(
+        &self,
+        old_workspace_id: &WorkspaceId,
+        new_workspace_id: &WorkspaceId,
+    ) -> Result<(), WorkspaceStoreError> {
+        self.update(|workspaces| {
+            workspaces.retain(|ws| ws.name != new_workspace_id.as_str());
+            for ws in workspaces.iter_mut() {
+                if ws.name == old_workspace_id.as_str() {
+                    ws.name = new_workspace_id.as_str().to_owned();
+                }
+            }
+        })
+    }
+
+    /// Returns the recorded root of the workspace `workspace_id`, if any.
+    pub fn get_workspace_path(
+        &self,
+        workspace_id: &WorkspaceId,
+    ) -> Result<Option<PathBuf>, WorkspaceStoreError> {
+        let workspaces = self.read_index()?;
+        Ok(workspaces
+            .into_iter()
+            .find(|ws| ws.name == workspace_id.as_str())
+            .map(|ws| PathBuf::from(ws.path)))
+    }
+
+    fn index_path(&self) -> PathBuf {
+        self.store_dir.join("index")
+    }
+
+    fn read_index(&self) -> Result<Vec<proto::Workspace>, WorkspaceStoreError> {
+        let index_path = self.index_path();
+        let buf = match fs::read(&index_path) {
+            Ok(buf) => buf,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(error) => {
+                return Err(PathError {
+                    path: index_path,
+                    error,
+                }
+                .into())
+            }
+        };
+        Ok(proto::Workspaces::decode(&*buf)?.workspaces)
+    }
+
+    fn update(
+        &self,
+        f: impl FnOnce(&mut Vec<proto::Workspace>),
+    ) -> Result<(), WorkspaceStoreError> {
+        create_or_reuse_dir(&self.store_dir).context(&self.store_dir)?;
+        let _lock = FileLock::lock(self.store_dir.join("lock"))?;
+        let mut workspaces = self.read_index()?;
+        f(&mut workspaces);
+        workspaces.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let index_path = self.index_path();
+        let mut temp_file = NamedTempFile::new_in(&self.store_dir).context(&self.store_dir)?;
+        temp_file
+            .as_file_mut()
+            .write_all(&proto::Workspaces { workspaces }.encode_to_vec())
+            .context(temp_file.path())?;
+        temp_file
+            .persist(&index_path)
+            .map_err(|err| err.error)
+            .context(&index_path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::new_temp_dir;
+
+    #[test]
+    fn test_add_rename_forget() {
+        let temp_dir = new_temp_dir();
+        let store = WorkspaceStore::load(temp_dir.path());
+        let default = WorkspaceId::default();
+        let second = WorkspaceId::new("second".to_owned());
+        let third = WorkspaceId::new("third".to_owned());
+        assert_eq!(store.get_workspace_path(&default).unwrap(), None);
+
+        store.add(&default, Path::new("/default")).unwrap();
+        store.add(&second, Path::new("/second")).unwrap();
+        store.add(&second, Path::new("/moved")).unwrap();
+        assert_eq!(
+            store.get_workspace_path(&default).unwrap(),
+            Some(PathBuf::from("/default"))
+        );
+        assert_eq!(
+            store.get_workspace_path(&second).unwrap(),
+            Some(PathBuf::from("/moved"))
+        );
+
+        store.rename(&second, &third).unwrap();
+        assert_eq!(store.get_workspace_path(&second).unwrap(), None);
+        assert_eq!(
+            store.get_workspace_path(&third).unwrap(),
+            Some(PathBuf::from("/moved"))
+        );
+
+        store.forget(&[&default, &third]).unwrap();
+        assert_eq!(store.get_workspace_path(&default).unwrap(), None);
+        assert_eq!(store.get_workspace_path(&third).unwrap(), None);
+    }
+}
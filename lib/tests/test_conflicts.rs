@@ -811,6 +811,57 @@ fn test_materialize_conflict_two_forward_diffs() {
     );
 }
 
+#[test]
+fn test_materialize_conflict_zdiff3() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_id = testutils::write_file(store, path, "line 1\nline 2\nline 3\nline 4\n");
+    let left_id = testutils::write_file(store, path, "line 1\ncommon a\nleft\ncommon b\nline 4\n");
+    let right_id =
+        testutils::write_file(store, path, "line 1\ncommon a\nright\ncommon b\nline 4\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(right_id.clone())],
+    );
+
+    // Lines common to both sides are moved out of the conflict
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Zdiff3),
+        @r"
+    line 1
+    common a
+    <<<<<<< Side #1 (Conflict 1 of 1)
+    left
+    ||||||| Base
+    line 2
+    line 3
+    =======
+    right
+    >>>>>>> Side #2 (Conflict 1 of 1 ends)
+    common b
+    line 4
+    "
+    );
+
+    // Without common lines, the markers are the same as the "git" style
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_id.clone())],
+        vec![Some(left_id.clone()), Some(base_id.clone())],
+    );
+    insta::assert_snapshot!(
+        &materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Zdiff3),
+        @r"
+    line 1
+    common a
+    left
+    common b
+    line 4
+    "
+    );
+}
+
 #[test]
 fn test_parse_conflict_resolved() {
     assert_eq!(
@@ -1695,6 +1746,68 @@ fn test_update_conflict_from_content() {
     );
 }
 
+#[test]
+fn test_update_conflict_from_content_zdiff3() {
+    let test_repo = TestRepo::init();
+    let store = test_repo.repo.store();
+
+    let path = RepoPath::from_internal_string("file");
+    let base_file_id = testutils::write_file(store, path, "line 1\nline 2\nline 3\n");
+    let left_file_id =
+        testutils::write_file(store, path, "line 1\ncommon a\nleft\ncommon b\nline 3\n");
+    let right_file_id =
+        testutils::write_file(store, path, "line 1\ncommon a\nright\ncommon b\nline 3\n");
+    let conflict = Merge::from_removes_adds(
+        vec![Some(base_file_id.clone())],
+        vec![Some(left_file_id.clone()), Some(right_file_id.clone())],
+    );
+    let parse = |content: &str| {
+        update_from_content(
+            &conflict,
+            store,
+            path,
+            content.as_bytes(),
+            ConflictMarkerStyle::Zdiff3,
+            MIN_CONFLICT_MARKER_LEN,
+        )
+        .block_on()
+        .unwrap()
+    };
+    let materialized =
+        materialize_conflict_string(store, path, &conflict, ConflictMarkerStyle::Zdiff3);
+    assert_eq!(parse(&materialized), conflict);
+
+    // Editing a line outside the conflict doesn't add the common lines to the
+    // base
+    let new_conflict = parse(&materialized.replace("line 1", "edited 1"));
+    let new_base_file_id = testutils::write_file(store, path, "edited 1\nline 2\nline 3\n");
+    let new_left_file_id =
+        testutils::write_file(store, path, "edited 1\ncommon a\nleft\ncommon b\nline 3\n");
+    let new_right_file_id =
+        testutils::write_file(store, path, "edited 1\ncommon a\nright\ncommon b\nline 3\n");
+    assert_eq!(
+        new_conflict,
+        Merge::from_removes_adds(
+            vec![Some(new_base_file_id)],
+            vec![Some(new_left_file_id), Some(new_right_file_id)]
+        )
+    );
+
+    // Editing a common line updates both sides
+    let new_conflict = parse(&materialized.replace("common b", "edited b"));
+    let new_left_file_id =
+        testutils::write_file(store, path, "line 1\ncommon a\nleft\nedited b\nline 3\n");
+    let new_right_file_id =
+        testutils::write_file(store, path, "line 1\ncommon a\nright\nedited b\nline 3\n");
+    assert_eq!(
+        new_conflict,
+        Merge::from_removes_adds(
+            vec![Some(base_file_id.clone())],
+            vec![Some(new_left_file_id), Some(new_right_file_id)]
+        )
+    );
+}
+
 #[test]
 fn test_update_conflict_from_content_modify_delete() {
     let test_repo = TestRepo::init();
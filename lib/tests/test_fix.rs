@@ -15,12 +15,14 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use itertools::Itertools as _;
 use jj_lib::backend::CommitId;
 use jj_lib::backend::FileId;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::fix::fix_files;
+use jj_lib::fix::ChangedLines;
 use jj_lib::fix::FileFixer;
 use jj_lib::fix::FileToFix;
 use jj_lib::fix::FixError;
@@ -547,3 +549,64 @@ fn test_fix_multiple_revisions() {
         .unwrap();
     assert_eq!(*new_commit_a.tree_id(), expected_tree_a.id());
 }
+
+#[test]
+fn test_fix_changed_lines() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // C
+    // B
+    // A
+    let mut tx = repo.start_transaction();
+    let path1 = RepoPath::from_internal_string("file1");
+    let path2 = RepoPath::from_internal_string("file2");
+    let path3 = RepoPath::from_internal_string("file3");
+    let tree1 = create_tree(repo, &[(path1, "a\nb\nc\n")]);
+    let commit_a = create_commit(
+        &mut tx,
+        vec![repo.store().root_commit_id().clone()],
+        tree1.id(),
+    );
+    let tree2 = create_tree(repo, &[(path1, "a\nB\nc\nd\n"), (path2, "new\n")]);
+    let commit_b = create_commit(&mut tx, vec![commit_a.clone()], tree2.id());
+    let tree3 = create_tree(
+        repo,
+        &[(path1, "a\nB\nc\nd\ne\n"), (path2, "new\n"), (path3, "x\n")],
+    );
+    let _commit_c = create_commit(&mut tx, vec![commit_b.clone()], tree3.id());
+
+    let root_commits = vec![commit_b.clone()];
+    let changed_lines = Mutex::new(HashSet::new());
+    let file_fixer = ParallelFileFixer::new(|_store: &Store, file_to_fix: &FileToFix| {
+        changed_lines.lock().unwrap().insert((
+            file_to_fix.repo_path.as_internal_file_string().to_owned(),
+            file_to_fix.changed_lines.clone(),
+        ));
+        Ok(None)
+    })
+    .with_changed_lines(true);
+    let include_unchanged_files = false;
+
+    fix_files(
+        root_commits,
+        &EverythingMatcher,
+        include_unchanged_files,
+        tx.repo_mut(),
+        &file_fixer,
+    )
+    .unwrap();
+
+    // The unchanged file2 in commit C is fixed with the changed lines from
+    // commit B, so there's only one input for it. The file1 modified in commit
+    // C again includes the lines changed in commit B.
+    assert_eq!(
+        changed_lines.into_inner().unwrap(),
+        HashSet::from([
+            ("file1".to_owned(), ChangedLines::Ranges(vec![1..2, 3..4])),
+            ("file1".to_owned(), ChangedLines::Ranges(vec![1..2, 3..5])),
+            ("file2".to_owned(), ChangedLines::All),
+            ("file3".to_owned(), ChangedLines::All),
+        ])
+    );
+}
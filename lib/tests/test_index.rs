@@ -17,16 +17,21 @@ use std::fs;
 use std::sync::Arc;
 
 use assert_matches::assert_matches;
+use itertools::Itertools as _;
 use jj_lib::backend::ChangeId;
 use jj_lib::backend::CommitId;
 use jj_lib::commit::Commit;
 use jj_lib::commit_builder::CommitBuilder;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
 use jj_lib::default_index::AsCompositeIndex as _;
 use jj_lib::default_index::CompositeIndex;
 use jj_lib::default_index::DefaultIndexStore;
 use jj_lib::default_index::DefaultIndexStoreError;
 use jj_lib::default_index::DefaultMutableIndex;
 use jj_lib::default_index::DefaultReadonlyIndex;
+use jj_lib::default_index::IndexCorruption;
+use jj_lib::fileset::FilesetExpression;
 use jj_lib::index::Index as _;
 use jj_lib::object_id::HexPrefix;
 use jj_lib::object_id::ObjectId as _;
@@ -39,11 +44,17 @@ use jj_lib::ref_name::RemoteRefSymbol;
 use jj_lib::repo::MutableRepo;
 use jj_lib::repo::ReadonlyRepo;
 use jj_lib::repo::Repo as _;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::revset::ResolvedExpression;
+use jj_lib::revset::RevsetExpression;
+use jj_lib::revset::RevsetFilterPredicate;
 use jj_lib::revset::GENERATION_RANGE_FULL;
+use jj_lib::settings::UserSettings;
 use maplit::hashset;
 use testutils::commit_transactions;
 use testutils::create_random_commit;
+use testutils::create_tree;
 use testutils::test_backend::TestBackend;
 use testutils::write_random_commit;
 use testutils::CommitGraphBuilder;
@@ -148,6 +159,56 @@ fn test_index_commits_standard_cases() {
     assert!(index.is_ancestor(commit_a.id(), commit_h.id()));
 }
 
+#[test]
+fn test_index_generation_number_and_position() {
+    let test_repo = TestRepo::init();
+    let repo = &test_repo.repo;
+
+    // o C
+    // |\
+    // o | B
+    // | o D
+    // |/
+    // o A
+    // o root
+
+    let root_commit_id = repo.store().root_commit_id();
+    let mut tx = repo.start_transaction();
+    let mut graph_builder = CommitGraphBuilder::new(tx.repo_mut());
+    let commit_a = graph_builder.initial_commit();
+    let commit_b = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_d = graph_builder.commit_with_parents(&[&commit_a]);
+    let commit_c = graph_builder.commit_with_parents(&[&commit_b, &commit_d]);
+    let missing_id = CommitId::from_hex("ffffffffffff");
+
+    // Uncommitted commits are visible through the mutable index
+    let index = tx.repo().index();
+    assert_eq!(index.generation_number(root_commit_id), Some(0));
+    assert_eq!(index.generation_number(commit_c.id()), Some(3));
+    assert_eq!(index.generation_number(&missing_id), None);
+    assert_eq!(index.topological_position(&missing_id), None);
+
+    let repo = tx.commit("test").unwrap();
+    let index = repo.index();
+    assert_eq!(index.generation_number(root_commit_id), Some(0));
+    assert_eq!(index.generation_number(commit_a.id()), Some(1));
+    assert_eq!(index.generation_number(commit_b.id()), Some(2));
+    assert_eq!(index.generation_number(commit_d.id()), Some(2));
+    assert_eq!(index.generation_number(commit_c.id()), Some(3));
+    assert_eq!(index.generation_number(&missing_id), None);
+
+    // Ancestors come before descendants
+    let position = |id| index.topological_position(id).unwrap();
+    assert_eq!(position(root_commit_id), 0);
+    assert!(position(root_commit_id) < position(commit_a.id()));
+    assert!(position(commit_a.id()) < position(commit_b.id()));
+    assert!(position(commit_a.id()) < position(commit_d.id()));
+    assert!(position(commit_b.id()) < position(commit_c.id()));
+    assert!(position(commit_d.id()) < position(commit_c.id()));
+    assert_ne!(position(commit_b.id()), position(commit_d.id()));
+    assert_eq!(index.topological_position(&missing_id), None);
+}
+
 #[test]
 fn test_index_commits_criss_cross() {
     let test_repo = TestRepo::init();
@@ -651,6 +712,46 @@ fn test_reindex_corrupt_segment_files() {
     assert!(repo.index().has_id(commit_a.id()));
 }
 
+#[test]
+fn test_reindex_old_format_version() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let test_env = &test_repo.env;
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit_a = write_random_commit(tx.repo_mut());
+    let repo = tx.commit("test").unwrap();
+    assert!(repo.index().has_id(commit_a.id()));
+
+    // Pretend the index files were written by old jj
+    let segments_dir = test_repo.repo_path().join("index").join("segments");
+    let read_versions = || {
+        segments_dir
+            .read_dir()
+            .unwrap()
+            .map(|entry| {
+                let data = fs::read(entry.unwrap().path()).unwrap();
+                u32::from_le_bytes(data[..4].try_into().unwrap())
+            })
+            .collect_vec()
+    };
+    let current_version = read_versions()[0];
+    for entry in segments_dir.read_dir().unwrap() {
+        let path = entry.unwrap().path();
+        let mut data = fs::read(&path).unwrap();
+        data[..4].copy_from_slice(&(current_version - 1).to_le_bytes());
+        fs::write(&path, data).unwrap();
+    }
+
+    // The old files should be replaced
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    assert!(repo.index().has_id(commit_a.id()));
+    let versions = read_versions();
+    assert!(!versions.is_empty());
+    assert!(versions.iter().all(|&version| version == current_version));
+}
+
 #[test]
 fn test_reindex_from_merged_operation() {
     let test_repo = TestRepo::init();
@@ -732,6 +833,195 @@ fn test_reindex_missing_commit() {
     assert_matches!(err, DefaultIndexStoreError::IndexCommits { op_id, .. } if op_id == *bad_op_id);
 }
 
+#[test]
+fn test_compact_index() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let test_env = &test_repo.env;
+    let mut repo = test_repo.repo.clone();
+    let mut commits = vec![];
+    for _ in 0..3 {
+        let mut tx = repo.start_transaction();
+        commits.push(write_random_commit(tx.repo_mut()));
+        repo = tx.commit("test").unwrap();
+    }
+    assert!(as_readonly_composite(&repo).stats().levels.len() > 1);
+
+    let index_dir = test_repo.repo_path().join("index");
+    let count_files = |name: &str| index_dir.join(name).read_dir().unwrap().count();
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    let index = default_index_store
+        .compact_index_at_operation(repo.operation(), repo.store())
+        .unwrap();
+    let stats = index.as_composite().stats();
+    assert_eq!(stats.num_commits, 1 + 3);
+    assert_eq!(stats.levels.len(), 1);
+    assert_eq!(count_files("operations"), 1);
+    assert_eq!(count_files("segments"), 1);
+
+    // The compacted index should be loaded, and new operations should be
+    // indexed on top of it.
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    assert_eq!(as_readonly_composite(&repo).stats().levels.len(), 1);
+    let mut tx = repo.start_transaction();
+    let commit = write_random_commit(tx.repo_mut());
+    let repo = tx.commit("test").unwrap();
+    let index = as_readonly_composite(&repo);
+    assert_eq!(index.num_commits(), 1 + 4);
+    assert!(index.has_id(commit.id()));
+    assert!(commits.iter().all(|commit| index.has_id(commit.id())));
+
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    let problems = default_index_store
+        .verify_index_at_operation(repo.operation(), repo.store())
+        .unwrap();
+    assert!(problems.is_empty(), "{problems:?}");
+}
+
+#[test]
+fn test_verify_index() {
+    let settings = testutils::user_settings();
+    let test_repo = TestRepo::init();
+    let test_env = &test_repo.env;
+    let repo = &test_repo.repo;
+
+    let mut tx = repo.start_transaction();
+    let commit_a = write_random_commit(tx.repo_mut());
+    let commit_b = write_random_commit(tx.repo_mut());
+    let repo = tx.commit("test").unwrap();
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    let problems = default_index_store
+        .verify_index_at_operation(repo.operation(), repo.store())
+        .unwrap();
+    assert!(problems.is_empty(), "{problems:?}");
+
+    // Corrupt the link from the parent operation, and remove a commit from
+    // the backend.
+    let parent_op_id = repo.operation().parent_ids()[0].clone();
+    let operations_dir = test_repo.repo_path().join("index").join("operations");
+    fs::write(operations_dir.join(parent_op_id.hex()), "0".repeat(128)).unwrap();
+    let test_backend: &TestBackend = repo.store().backend_impl().downcast_ref().unwrap();
+    test_backend.remove_commit_unchecked(commit_b.id());
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path()); // discard cache
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+
+    let problems = default_index_store
+        .verify_index_at_operation(repo.operation(), repo.store())
+        .unwrap();
+    assert_eq!(problems.len(), 2, "{problems:?}");
+    assert_matches!(
+        &problems[0],
+        IndexCorruption::LoadOperationIndex { op_id, .. } if *op_id == parent_op_id
+    );
+    assert_matches!(
+        &problems[1],
+        IndexCorruption::ReadCommit { commit_id, .. } if commit_id == commit_b.id()
+    );
+    assert!(repo.index().has_id(commit_a.id()));
+}
+
+#[test]
+fn test_index_changed_path_filters() {
+    let mut config = testutils::base_user_config();
+    config.add_layer(ConfigLayer::parse(ConfigSource::User, "index.changed-paths = true").unwrap());
+    let settings = UserSettings::from_config(config).unwrap();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let test_env = &test_repo.env;
+    let repo = &test_repo.repo;
+
+    let dir_file = RepoPath::from_internal_string("dir/file");
+    let other_file = RepoPath::from_internal_string("other");
+    let tree1 = create_tree(repo, &[(dir_file, "1")]);
+    let tree2 = create_tree(repo, &[(dir_file, "1"), (other_file, "1")]);
+    let mut tx = repo.start_transaction();
+    let commit1 = tx
+        .repo_mut()
+        .new_commit(vec![repo.store().root_commit_id().clone()], tree1.id())
+        .write()
+        .unwrap();
+    let commit2 = tx
+        .repo_mut()
+        .new_commit(vec![commit1.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    // Empty commit
+    tx.repo_mut()
+        .new_commit(vec![commit2.id().clone()], tree2.id())
+        .write()
+        .unwrap();
+    let repo = tx.commit("test").unwrap();
+
+    let resolve = |repo: &Arc<ReadonlyRepo>, expression: FilesetExpression| -> Vec<CommitId> {
+        let expression = RevsetExpression::filter(RevsetFilterPredicate::File(expression));
+        let revset = expression.evaluate(repo.as_ref()).unwrap();
+        revset.iter().map(Result::unwrap).collect()
+    };
+    let check_files = |repo: &Arc<ReadonlyRepo>| {
+        assert_eq!(
+            resolve(repo, FilesetExpression::prefix_path(dir_file.to_owned())),
+            vec![commit1.id().clone()]
+        );
+        assert_eq!(
+            resolve(
+                repo,
+                FilesetExpression::prefix_path(dir_file.parent().unwrap().to_owned())
+            ),
+            vec![commit1.id().clone()]
+        );
+        assert_eq!(
+            resolve(repo, FilesetExpression::file_path(other_file.to_owned())),
+            vec![commit2.id().clone()]
+        );
+        assert_eq!(
+            resolve(repo, FilesetExpression::all()),
+            vec![commit2.id().clone(), commit1.id().clone()]
+        );
+        assert_eq!(
+            resolve(
+                repo,
+                FilesetExpression::file_path(RepoPathBuf::from_internal_string("missing"))
+            ),
+            vec![]
+        );
+    };
+
+    // All commits, including the root commit, have filters
+    let stats = as_readonly_composite(&repo).stats();
+    assert_eq!(stats.num_commits, 4);
+    assert_eq!(stats.num_changed_path_filters, 4);
+    check_files(&repo);
+
+    // Filters are rebuilt by reindexing
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    default_index_store.reinit().unwrap();
+    let repo = test_env.load_repo_at_head(&settings, test_repo.repo_path());
+    assert_eq!(
+        as_readonly_composite(&repo)
+            .stats()
+            .num_changed_path_filters,
+        4
+    );
+    check_files(&repo);
+
+    // Filters aren't built if disabled
+    let default_index_store: &DefaultIndexStore =
+        repo.index_store().as_any().downcast_ref().unwrap();
+    default_index_store.reinit().unwrap();
+    let repo = test_env.load_repo_at_head(&testutils::user_settings(), test_repo.repo_path());
+    assert_eq!(
+        as_readonly_composite(&repo)
+            .stats()
+            .num_changed_path_filters,
+        0
+    );
+    check_files(&repo);
+}
+
 /// Test that .jj/repo/index/type is created when the repo is created.
 #[test]
 fn test_index_store_type() {
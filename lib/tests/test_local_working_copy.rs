@@ -47,6 +47,7 @@ use jj_lib::working_copy::CheckoutError;
 use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
 use jj_lib::working_copy::SnapshotOptions;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::working_copy::UntrackedReason;
 use jj_lib::working_copy::WorkingCopy as _;
 use jj_lib::workspace::default_working_copy_factories;
@@ -110,7 +111,7 @@ fn test_root() {
     let mut test_workspace = TestWorkspace::init();
 
     let wc = test_workspace.workspace.working_copy();
-    assert_eq!(wc.sparse_patterns().unwrap(), vec![RepoPathBuf::root()]);
+    assert_eq!(wc.sparse_patterns().unwrap(), &SparsePatterns::everything());
     let new_tree = test_workspace.snapshot().unwrap();
     let repo = &test_workspace.repo;
     let wc_commit_id = repo
@@ -2061,7 +2062,7 @@ fn test_fsmonitor() {
     let ws = &mut test_workspace.workspace;
     assert_eq!(
         ws.working_copy().sparse_patterns().unwrap(),
-        vec![RepoPathBuf::root()]
+        &SparsePatterns::everything()
     );
 
     let foo_path = RepoPath::from_internal_string("foo");
@@ -21,6 +21,7 @@ use jj_lib::repo_path::RepoPath;
 use jj_lib::repo_path::RepoPathBuf;
 use jj_lib::working_copy::CheckoutOptions;
 use jj_lib::working_copy::CheckoutStats;
+use jj_lib::working_copy::SparsePatterns;
 use jj_lib::working_copy::WorkingCopy as _;
 use pollster::FutureExt as _;
 use testutils::commit_with_tree;
@@ -73,7 +74,7 @@ fn test_sparse_checkout() {
 
     // Set sparse patterns to only dir1/
     let mut locked_ws = ws.start_working_copy_mutation().unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = SparsePatterns::Prefixes(to_owned_path_vec(&[dir1_path]));
     let stats = locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
@@ -89,7 +90,7 @@ fn test_sparse_checkout() {
     );
     assert_eq!(
         locked_ws.locked_wc().sparse_patterns().unwrap(),
-        sparse_patterns
+        &sparse_patterns
     );
     assert!(!root_file1_path
         .to_fs_path_unchecked(&working_copy_path)
@@ -117,7 +118,7 @@ fn test_sparse_checkout() {
         wc.file_states().unwrap().paths().collect_vec(),
         vec![dir1_file1_path, dir1_file2_path, dir1_subdir1_file1_path]
     );
-    assert_eq!(wc.sparse_patterns().unwrap(), sparse_patterns);
+    assert_eq!(wc.sparse_patterns().unwrap(), &sparse_patterns);
 
     // Reload the state to check that it was persisted
     let wc = LocalWorkingCopy::load(
@@ -129,11 +130,15 @@ fn test_sparse_checkout() {
         wc.file_states().unwrap().paths().collect_vec(),
         vec![dir1_file1_path, dir1_file2_path, dir1_subdir1_file1_path]
     );
-    assert_eq!(wc.sparse_patterns().unwrap(), sparse_patterns);
+    assert_eq!(wc.sparse_patterns().unwrap(), &sparse_patterns);
 
     // Set sparse patterns to file2, dir1/subdir1/ and dir2/
     let mut locked_wc = wc.start_mutation().unwrap();
-    let sparse_patterns = to_owned_path_vec(&[root_file1_path, dir1_subdir1_path, dir2_path]);
+    let sparse_patterns = SparsePatterns::Prefixes(to_owned_path_vec(&[
+        root_file1_path,
+        dir1_subdir1_path,
+        dir2_path,
+    ]));
     let stats = locked_wc
         .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
         .unwrap();
@@ -146,7 +151,7 @@ fn test_sparse_checkout() {
             skipped_files: 0,
         }
     );
-    assert_eq!(locked_wc.sparse_patterns().unwrap(), sparse_patterns);
+    assert_eq!(locked_wc.sparse_patterns().unwrap(), &sparse_patterns);
     assert!(root_file1_path
         .to_fs_path_unchecked(&working_copy_path)
         .exists());
@@ -173,6 +178,106 @@ fn test_sparse_checkout() {
     );
 }
 
+#[test]
+fn test_sparse_checkout_fileset() {
+    let mut test_workspace = TestWorkspace::init();
+    let repo = &test_workspace.repo;
+    let working_copy_path = test_workspace.workspace.workspace_root().to_owned();
+
+    let root_file1_path = RepoPath::from_internal_string("file1");
+    let dir1_file1_path = RepoPath::from_internal_string("dir1/file1");
+    let dir1_testdata_file1_path = RepoPath::from_internal_string("dir1/testdata/file1");
+    let dir1_subdir1_testdata_file1_path =
+        RepoPath::from_internal_string("dir1/subdir1/testdata/file1");
+    let dir1_subdir1_file1_path = RepoPath::from_internal_string("dir1/subdir1/file1");
+
+    let tree = create_tree(
+        repo,
+        &[
+            (root_file1_path, "contents"),
+            (dir1_file1_path, "contents"),
+            (dir1_testdata_file1_path, "contents"),
+            (dir1_subdir1_testdata_file1_path, "contents"),
+            (dir1_subdir1_file1_path, "contents"),
+        ],
+    );
+    let commit = commit_with_tree(repo.store(), tree.id());
+
+    test_workspace
+        .workspace
+        .check_out(
+            repo.op_id().clone(),
+            None,
+            &commit,
+            &CheckoutOptions::empty_for_test(),
+        )
+        .unwrap();
+    let ws = &mut test_workspace.workspace;
+
+    // Check out dir1/ except for the testdata/ directories in it
+    let mut locked_ws = ws.start_working_copy_mutation().unwrap();
+    let sparse_patterns =
+        SparsePatterns::parse_fileset(r#"dir1 ~ glob:"dir1/**/testdata/**""#).unwrap();
+    let stats = locked_ws
+        .locked_wc()
+        .set_sparse_patterns(sparse_patterns.clone(), &CheckoutOptions::empty_for_test())
+        .unwrap();
+    assert_eq!(
+        stats,
+        CheckoutStats {
+            updated_files: 0,
+            added_files: 0,
+            removed_files: 3,
+            skipped_files: 0,
+        }
+    );
+    assert!(!root_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    assert!(dir1_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    assert!(!dir1_testdata_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    assert!(!dir1_subdir1_testdata_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    assert!(dir1_subdir1_file1_path
+        .to_fs_path_unchecked(&working_copy_path)
+        .exists());
+    locked_ws.finish(repo.op_id().clone()).unwrap();
+
+    // Reload the state to check that it was persisted
+    let wc: &LocalWorkingCopy = ws.working_copy().as_any().downcast_ref().unwrap();
+    let wc = LocalWorkingCopy::load(
+        repo.store().clone(),
+        ws.workspace_root().to_path_buf(),
+        wc.state_path().to_path_buf(),
+    );
+    assert_eq!(
+        wc.file_states().unwrap().paths().collect_vec(),
+        vec![dir1_file1_path, dir1_subdir1_file1_path]
+    );
+    assert_eq!(wc.sparse_patterns().unwrap(), &sparse_patterns);
+}
+
+#[test]
+fn test_sparse_patterns_to_fileset_text() {
+    let prefixes = SparsePatterns::Prefixes(to_owned_path_vec(&[
+        RepoPath::from_internal_string("dir1"),
+        RepoPath::from_internal_string("dir \"2\""),
+    ]));
+    let text = prefixes.to_fileset_text();
+    assert_eq!(text, r#"root:"dir1" | root:"dir \"2\"""#);
+    assert!(SparsePatterns::parse_fileset(&text)
+        .unwrap()
+        .to_matcher()
+        .matches(RepoPath::from_internal_string("dir \"2\"/file")));
+    assert_eq!(SparsePatterns::everything().to_fileset_text(), "all()");
+    assert_eq!(SparsePatterns::Prefixes(vec![]).to_fileset_text(), "none()");
+}
+
 /// Test that sparse patterns are respected on commit
 #[test]
 fn test_sparse_commit() {
@@ -212,7 +317,7 @@ fn test_sparse_commit() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = SparsePatterns::Prefixes(to_owned_path_vec(&[dir1_path]));
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
@@ -253,7 +358,7 @@ fn test_sparse_commit() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path, dir2_path]);
+    let sparse_patterns = SparsePatterns::Prefixes(to_owned_path_vec(&[dir1_path, dir2_path]));
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
@@ -288,7 +393,7 @@ fn test_sparse_commit_gitignore() {
         .workspace
         .start_working_copy_mutation()
         .unwrap();
-    let sparse_patterns = to_owned_path_vec(&[dir1_path]);
+    let sparse_patterns = SparsePatterns::Prefixes(to_owned_path_vec(&[dir1_path]));
     locked_ws
         .locked_wc()
         .set_sparse_patterns(sparse_patterns, &CheckoutOptions::empty_for_test())
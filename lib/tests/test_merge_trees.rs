@@ -12,12 +12,16 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use indoc::indoc;
 use jj_lib::backend::MergedTreeId;
 use jj_lib::backend::TreeValue;
+use jj_lib::config::ConfigLayer;
+use jj_lib::config::ConfigSource;
 use jj_lib::merge::Merge;
 use jj_lib::repo::Repo as _;
 use jj_lib::repo_path::RepoPath;
 use jj_lib::rewrite::rebase_commit;
+use jj_lib::settings::UserSettings;
 use testutils::create_tree;
 use testutils::TestRepo;
 
@@ -224,3 +228,119 @@ fn test_rebase_on_lossy_merge() {
     .flatten();
     assert_eq!(*commit_d2.tree_id(), MergedTreeId::Merge(expected_tree_id));
 }
+
+#[test]
+fn test_merge_with_merge_drivers() {
+    let mut config = testutils::base_user_config();
+    config.add_layer(
+        ConfigLayer::parse(
+            ConfigSource::User,
+            indoc! {r#"
+                [merge.drivers.changelog]
+                patterns = ["CHANGELOG"]
+                strategy = "union"
+                [merge.drivers.docs]
+                patterns = ["glob:'*.md'"]
+                strategy = "words"
+                [merge.drivers.json]
+                patterns = ["glob:'*.json'"]
+                strategy = "json"
+                [merge.drivers.lockfiles]
+                patterns = ["glob:'**/*.lock'"]
+                strategy = "theirs"
+                [merge.drivers.vendored]
+                patterns = ["vendor"]
+                strategy = "ours"
+            "#},
+        )
+        .unwrap(),
+    );
+    let settings = UserSettings::from_config(config).unwrap();
+    let test_repo = TestRepo::init_with_settings(&settings);
+    let repo = &test_repo.repo;
+
+    let changelog_path = RepoPath::from_internal_string("CHANGELOG");
+    let lock_path = RepoPath::from_internal_string("dir/Cargo.lock");
+    let vendor_path = RepoPath::from_internal_string("vendor/file");
+    let docs_path = RepoPath::from_internal_string("README.md");
+    let docs_conflict_path = RepoPath::from_internal_string("INSTALL.md");
+    let json_path = RepoPath::from_internal_string("package.json");
+    let other_path = RepoPath::from_internal_string("other");
+    let base_tree = create_tree(
+        repo,
+        &[
+            (changelog_path, "a\nz\n"),
+            (lock_path, "a\nb\nc\nd\n"),
+            (vendor_path, "a\nb\nc\nd\n"),
+            (docs_path, "The quick fox.\n"),
+            (docs_conflict_path, "The quick fox.\nand\nRead the docs.\n"),
+            (json_path, "{\"a\": 1, \"b\": 1}\n"),
+            (other_path, "a\n"),
+        ],
+    );
+    let left_tree = create_tree(
+        repo,
+        &[
+            (changelog_path, "a\nleft\nz\n"),
+            (lock_path, "a\nleft\nc\nd\n"),
+            (vendor_path, "a\nleft\nc\nd\n"),
+            (docs_path, "The slow fox.\n"),
+            (
+                docs_conflict_path,
+                "The slow fox.\nand\nRead the new docs.\n",
+            ),
+            (json_path, "{\"a\": 2, \"b\": 1}\n"),
+            (other_path, "left\n"),
+        ],
+    );
+    let right_tree = create_tree(
+        repo,
+        &[
+            (changelog_path, "a\nright\nz\n"),
+            (lock_path, "a\nright\nc\nD\n"),
+            (vendor_path, "a\nright\nc\nD\n"),
+            (docs_path, "The quick dog.\n"),
+            (
+                docs_conflict_path,
+                "The fast fox.\nand\nRead the docs here.\n",
+            ),
+            (json_path, "{\"a\": 1, \"b\": 2}\n"),
+            (other_path, "right\n"),
+        ],
+    );
+    let merged_tree = left_tree.merge(&base_tree, &right_tree).unwrap();
+
+    let read_resolved =
+        |path: &RepoPath| match merged_tree.path_value(path).unwrap().into_resolved() {
+            Ok(Some(TreeValue::File { id, .. })) => testutils::read_file(repo.store(), path, &id),
+            other => panic!("unexpected value: {other:#?}"),
+        };
+    // Conflicting hunks are resolved by the driver, other hunks are merged as
+    // usual.
+    assert_eq!(read_resolved(changelog_path), b"a\nleft\nright\nz\n");
+    assert_eq!(read_resolved(lock_path), b"a\nright\nc\nD\n");
+    assert_eq!(read_resolved(vendor_path), b"a\nleft\nc\nD\n");
+    // Edits to different words of the same line are merged, but overlapping
+    // edits remain conflicted. The merged hunks are applied to every side of
+    // the remaining conflict.
+    assert_eq!(read_resolved(docs_path), b"The slow dog.\n");
+    let docs_conflict = merged_tree.path_value(docs_conflict_path).unwrap();
+    let docs_conflict_contents = docs_conflict.map(|value| match value {
+        Some(TreeValue::File { id, .. }) => {
+            String::from_utf8(testutils::read_file(repo.store(), docs_conflict_path, id)).unwrap()
+        }
+        other => panic!("unexpected value: {other:#?}"),
+    });
+    assert_eq!(
+        docs_conflict_contents,
+        Merge::from_vec(vec![
+            "The slow fox.\nand\nRead the new docs here.\n".to_owned(),
+            "The quick fox.\nand\nRead the new docs here.\n".to_owned(),
+            "The fast fox.\nand\nRead the new docs here.\n".to_owned(),
+        ])
+    );
+    // Structured mergers merge the whole file.
+    assert_eq!(read_resolved(json_path), b"{\"a\":2,\"b\":2}\n");
+    // Files without a driver remain conflicted.
+    assert!(!merged_tree.path_value(other_path).unwrap().is_resolved());
+}
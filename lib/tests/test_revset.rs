@@ -55,7 +55,11 @@ use jj_lib::revset::RevsetResolutionError;
 use jj_lib::revset::RevsetWorkspaceContext;
 use jj_lib::revset::SymbolResolver as _;
 use jj_lib::revset::SymbolResolverExtension;
+use jj_lib::signing::SignBehavior;
+use jj_lib::signing::Signer;
+use jj_lib::test_signing_backend::TestSigningBackend;
 use jj_lib::workspace::Workspace;
+use pollster::FutureExt as _;
 use test_case::test_case;
 use testutils::create_random_commit;
 use testutils::create_tree;
@@ -1539,6 +1543,33 @@ fn test_evaluate_expression_dag_range() {
             root_commit_id.clone(),
         ]
     );
+
+    // Limited by depth from roots
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("dag_range({}, {}, 2)", commit1.id(), commit5.id())
+        ),
+        vec![
+            commit4.id().clone(),
+            commit2.id().clone(),
+            commit1.id().clone(),
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("dag_range({}, {}, 0)", commit1.id(), commit5.id())
+        ),
+        vec![]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("dag_range({}, {})", commit1.id(), commit5.id())
+        ),
+        resolve_commit_ids(mut_repo, &format!("{}::{}", commit1.id(), commit5.id()))
+    );
 }
 
 #[test]
@@ -3969,6 +4000,88 @@ fn test_evaluate_expression_conflict() {
     );
 }
 
+#[test]
+fn test_evaluate_expression_signature() {
+    let settings = testutils::user_settings();
+    let signer = Signer::new(Some(Box::new(TestSigningBackend)), vec![]);
+    let test_workspace =
+        TestWorkspace::init_with_backend_and_signer(TestRepoBackend::Simple, signer, &settings);
+    let repo = &test_workspace.repo;
+
+    let mut tx = repo.start_transaction();
+    let mut_repo = tx.repo_mut();
+
+    let mut write_commit_with_signature = |signature: &'static [u8]| {
+        let data = create_random_commit(mut_repo)
+            .detach()
+            .write_hidden()
+            .unwrap();
+        let mut sign_fn = |_: &[u8]| Ok(signature.to_vec());
+        let commit = repo
+            .store()
+            .write_commit(data.store_commit().clone(), Some(&mut sign_fn))
+            .block_on()
+            .unwrap();
+        mut_repo.add_head(&commit).unwrap();
+        commit
+    };
+    let commit_bad = write_commit_with_signature(b"--- JJ-TEST-SIGNATURE ---\nKEY: alice\nbad\n");
+    let commit_unknown = write_commit_with_signature(b"unknown signature");
+    let commit_unsigned = write_random_commit(mut_repo);
+    let commit_alice = create_random_commit(mut_repo)
+        .set_sign_behavior(SignBehavior::Force)
+        .set_sign_key("alice".to_owned())
+        .write()
+        .unwrap();
+    let commit_bob = create_random_commit(mut_repo)
+        .set_sign_behavior(SignBehavior::Force)
+        .set_sign_key("bob".to_owned())
+        .write()
+        .unwrap();
+
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed()"),
+        vec![
+            commit_bob.id().clone(),
+            commit_alice.id().clone(),
+            commit_unknown.id().clone(),
+            commit_bad.id().clone(),
+        ]
+    );
+    assert_eq!(
+        resolve_commit_ids(
+            mut_repo,
+            &format!("~signed() & {}", commit_unsigned.id().hex())
+        ),
+        vec![commit_unsigned.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature(good)"),
+        vec![commit_bob.id().clone(), commit_alice.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature(bad)"),
+        vec![commit_bad.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signature('unknown')"),
+        vec![commit_unknown.id().clone()]
+    );
+    // Bad signatures don't count, even if the key matches
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed_by(alice)"),
+        vec![commit_alice.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed_by(glob:'*')"),
+        vec![commit_bob.id().clone(), commit_alice.id().clone()]
+    );
+    assert_eq!(
+        resolve_commit_ids(mut_repo, "signed_by('test-display')"),
+        vec![commit_bob.id().clone(), commit_alice.id().clone()]
+    );
+}
+
 #[test]
 fn test_reverse_graph() {
     let test_repo = TestRepo::init();
@@ -1,3 +1,8 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use jj_lib::backend::CommitId;
 use jj_lib::backend::MillisSinceEpoch;
 use jj_lib::backend::Signature;
 use jj_lib::backend::Timestamp;
@@ -7,8 +12,11 @@ use jj_lib::repo::Repo as _;
 use jj_lib::settings::UserSettings;
 use jj_lib::signing::SigStatus;
 use jj_lib::signing::SignBehavior;
+use jj_lib::signing::SignResult;
 use jj_lib::signing::Signer;
+use jj_lib::signing::SigningBackend;
 use jj_lib::signing::Verification;
+use jj_lib::signing::VerificationState;
 use jj_lib::test_signing_backend::TestSigningBackend;
 use test_case::test_case;
 use testutils::create_random_commit;
@@ -31,6 +39,7 @@ fn user_settings(behavior: SignBehavior) -> UserSettings {
                     SignBehavior::Keep => "keep",
                     SignBehavior::Own => "own",
                     SignBehavior::Force => "force",
+                    SignBehavior::OnPush => "on-push",
                 }
             ),
         )
@@ -208,3 +217,80 @@ fn drop_behavior(backend: TestRepoBackend) {
     let rewritten_commit = repo.store().get_commit(rewritten.id()).unwrap();
     assert_eq!(rewritten_commit.verification().unwrap(), None);
 }
+
+/// Wraps `TestSigningBackend` to count the verifications and to report a
+/// configurable verification state.
+#[derive(Debug)]
+struct CountingBackend {
+    num_verified: Arc<AtomicUsize>,
+    state: &'static str,
+}
+
+impl SigningBackend for CountingBackend {
+    fn name(&self) -> &str {
+        "counting"
+    }
+
+    fn can_read(&self, signature: &[u8]) -> bool {
+        TestSigningBackend.can_read(signature)
+    }
+
+    fn sign(&self, data: &[u8], key: Option<&str>) -> SignResult<Vec<u8>> {
+        TestSigningBackend.sign(data, key)
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> SignResult<Verification> {
+        self.num_verified.fetch_add(1, Ordering::Relaxed);
+        TestSigningBackend.verify(data, signature)
+    }
+
+    fn verification_state(&self) -> Option<VerificationState> {
+        let mut state = VerificationState::new();
+        state.add_value(self.state);
+        Some(state)
+    }
+}
+
+#[test]
+fn persistent_verification_cache() {
+    let temp_dir = testutils::new_temp_dir();
+    let num_verified = Arc::new(AtomicUsize::new(0));
+    let new_signer = |state| {
+        let backend = CountingBackend {
+            num_verified: num_verified.clone(),
+            state,
+        };
+        Signer::new(Some(Box::new(backend)), vec![]).with_persistent_cache(temp_dir.path())
+    };
+    let commit_id = CommitId::from_hex("abcd");
+    let signature = TestSigningBackend.sign(b"data", Some("key")).unwrap();
+    let verification = Some(Verification::new(
+        SigStatus::Good,
+        Some("key".to_owned()),
+        Some("test-display".to_owned()),
+    ));
+
+    let signer = new_signer("state1");
+    let verify = |signer: &Signer| signer.verify(&commit_id, b"data", &signature).ok();
+    assert_eq!(verify(&signer), verification);
+    assert_eq!(verify(&signer), verification);
+    assert_eq!(num_verified.load(Ordering::Relaxed), 1);
+    drop(signer);
+
+    // The result is reused by another signer with the same state
+    let signer = new_signer("state1");
+    assert_eq!(verify(&signer), verification);
+    assert_eq!(num_verified.load(Ordering::Relaxed), 1);
+    drop(signer);
+
+    // Changing the state invalidates the cache
+    let signer = new_signer("state2");
+    assert_eq!(verify(&signer), verification);
+    assert_eq!(num_verified.load(Ordering::Relaxed), 2);
+    drop(signer);
+
+    // Recently used caches for other states are kept
+    let signer = new_signer("state1");
+    assert_eq!(verify(&signer), verification);
+    assert_eq!(num_verified.load(Ordering::Relaxed), 2);
+}